@@ -1,4 +1,5 @@
 use crate::common::AppSysConfig;
+use crate::config::config_webhook::ConfigWebhookManager;
 use crate::config::core::ConfigActor;
 use crate::grpc::bistream_manage::BiStreamManage;
 use crate::naming::cluster::node_manage::{InnerNodeManage, NodeManage};
@@ -34,5 +35,6 @@ pub struct AppShareData {
     pub raft_cache_route: Arc<CacheRoute>,
     pub factory_data: FactoryData,
     pub user_manager: Addr<UserManager>,
+    pub config_webhook_manager: Addr<ConfigWebhookManager>,
     pub cache_manager: Addr<CacheManager>,
 }
@@ -0,0 +1,119 @@
+use std::io::{Read, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use serde::{Deserialize, Serialize};
+
+use super::APP_SYS_CONFIG;
+
+lazy_static::lazy_static! {
+    pub static ref RAFT_DATA_COMPRESS: DataCompress =
+        DataCompress::new(APP_SYS_CONFIG.raft_compress_threshold);
+}
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+///
+/// raft日志及快照中的大数据压缩，压缩后的数据以gzip头标识，读取时自动识别
+#[derive(Debug, Default)]
+pub struct DataCompress {
+    ///为0时不压缩
+    threshold: usize,
+    compress_count: AtomicU64,
+    raw_bytes: AtomicU64,
+    compressed_bytes: AtomicU64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataCompressMetrics {
+    pub threshold: usize,
+    pub compress_count: u64,
+    pub raw_bytes: u64,
+    pub compressed_bytes: u64,
+    ///压缩后大小/压缩前大小
+    pub ratio: f64,
+}
+
+impl DataCompress {
+    pub fn new(threshold: usize) -> Self {
+        Self {
+            threshold,
+            ..Default::default()
+        }
+    }
+
+    pub fn is_compressed(data: &[u8]) -> bool {
+        data.starts_with(&GZIP_MAGIC)
+    }
+
+    ///
+    /// 超过阈值时压缩，压缩后没有变小则返回原数据
+    pub fn compress(&self, data: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+        if self.threshold == 0 || data.len() < self.threshold {
+            return Ok(data);
+        }
+        let mut encoder = GzEncoder::new(
+            Vec::with_capacity(data.len() / 4),
+            flate2::Compression::fast(),
+        );
+        encoder.write_all(&data)?;
+        let compressed = encoder.finish()?;
+        if compressed.len() >= data.len() {
+            return Ok(data);
+        }
+        self.compress_count.fetch_add(1, Ordering::Relaxed);
+        self.raw_bytes
+            .fetch_add(data.len() as u64, Ordering::Relaxed);
+        self.compressed_bytes
+            .fetch_add(compressed.len() as u64, Ordering::Relaxed);
+        Ok(compressed)
+    }
+
+    ///
+    /// 未压缩的数据直接返回
+    pub fn decompress(data: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+        if !Self::is_compressed(&data) {
+            return Ok(data);
+        }
+        let mut decoder = GzDecoder::new(&data[..]);
+        let mut v = Vec::with_capacity(data.len() * 4);
+        decoder.read_to_end(&mut v)?;
+        Ok(v)
+    }
+
+    pub fn get_metrics(&self) -> DataCompressMetrics {
+        let raw_bytes = self.raw_bytes.load(Ordering::Relaxed);
+        let compressed_bytes = self.compressed_bytes.load(Ordering::Relaxed);
+        DataCompressMetrics {
+            threshold: self.threshold,
+            compress_count: self.compress_count.load(Ordering::Relaxed),
+            raw_bytes,
+            compressed_bytes,
+            ratio: if raw_bytes == 0 {
+                1f64
+            } else {
+                compressed_bytes as f64 / raw_bytes as f64
+            },
+        }
+    }
+}
+
+#[test]
+fn compress_raft_data() {
+    let compress = DataCompress::new(16);
+    let small = b"{\"a\":1}".to_vec();
+    assert_eq!(compress.compress(small.clone()).unwrap(), small);
+    let large = "rnacos config content ".repeat(100).into_bytes();
+    let compressed = compress.compress(large.clone()).unwrap();
+    assert!(DataCompress::is_compressed(&compressed));
+    assert!(compressed.len() < large.len());
+    assert_eq!(DataCompress::decompress(compressed).unwrap(), large);
+    assert_eq!(DataCompress::decompress(small.clone()).unwrap(), small);
+    let metrics = compress.get_metrics();
+    assert_eq!(metrics.compress_count, 1);
+    assert_eq!(metrics.raw_bytes, large.len() as u64);
+    assert!(metrics.ratio < 0.5);
+    assert_eq!(DataCompress::new(0).compress(large.clone()).unwrap(), large);
+}
@@ -17,4 +17,10 @@ lazy_static::lazy_static! {
     pub static ref SEQUENCE_TREE_NAME: Arc<String> =  Arc::new("T_SEQUENCE".to_string());
     pub static ref USER_TREE_NAME: Arc<String> =  Arc::new("T_USER".to_string());
     pub static ref CACHE_TREE_NAME: Arc<String> =  Arc::new("T_CACHE".to_string());
+    pub static ref CONFIG_HISTORY_TREE_NAME: Arc<String> =  Arc::new("T_CONFIG_HISTORY".to_string());
+    pub static ref CONFIG_WEBHOOK_TREE_NAME: Arc<String> =  Arc::new("T_CONFIG_WEBHOOK".to_string());
+    pub static ref CONFIG_WEBHOOK_LOG_TREE_NAME: Arc<String> =  Arc::new("T_CONFIG_WEBHOOK_LOG".to_string());
+    pub static ref CONFIG_RECYCLE_TREE_NAME: Arc<String> =  Arc::new("T_CONFIG_RECYCLE".to_string());
+    pub static ref CONFIG_SCHEMA_TREE_NAME: Arc<String> =  Arc::new("T_CONFIG_SCHEMA".to_string());
+    pub static ref NAMING_PERSISTENT_INSTANCE_TREE_NAME: Arc<String> =  Arc::new("T_NAMING_PERSISTENT_INSTANCE".to_string());
 }
@@ -14,7 +14,11 @@ pub fn encode_base64(data: &[u8]) -> String {
 
 /// 加密
 /// key,iv长度需要是16的倍数
-pub fn encrypt_aes128(key: &str, iv: &str, plain: &[u8]) -> anyhow::Result<Vec<u8>> {
+pub fn encrypt_aes128<K: AsRef<[u8]>, I: AsRef<[u8]>>(
+    key: K,
+    iv: I,
+    plain: &[u8],
+) -> anyhow::Result<Vec<u8>> {
     let pt_len = plain.len();
     let buf_len = if pt_len % 48 == 0 {
         pt_len
@@ -23,7 +27,7 @@ pub fn encrypt_aes128(key: &str, iv: &str, plain: &[u8]) -> anyhow::Result<Vec<u
     };
     let mut buf = vec![0u8; buf_len];
     (buf[..pt_len]).copy_from_slice(plain);
-    match Aes128CbcEnc::new(key.as_bytes().into(), iv.as_bytes().into())
+    match Aes128CbcEnc::new(key.as_ref().into(), iv.as_ref().into())
         .encrypt_padded_b2b_mut::<Pkcs7>(plain, &mut buf)
     {
         Ok(ct) => Ok(ct.to_vec()),
@@ -33,7 +37,11 @@ pub fn encrypt_aes128(key: &str, iv: &str, plain: &[u8]) -> anyhow::Result<Vec<u
 
 /// 解密
 /// key,iv长度需要是16的倍数
-pub fn decrypt_aes128(key: &str, iv: &str, cipher: &[u8]) -> anyhow::Result<Vec<u8>> {
+pub fn decrypt_aes128<K: AsRef<[u8]>, I: AsRef<[u8]>>(
+    key: K,
+    iv: I,
+    cipher: &[u8],
+) -> anyhow::Result<Vec<u8>> {
     let cipher_len = cipher.len();
     let buf_len = if cipher_len % 48 == 0 {
         cipher_len
@@ -43,7 +51,7 @@ pub fn decrypt_aes128(key: &str, iv: &str, cipher: &[u8]) -> anyhow::Result<Vec<
     let mut buf = vec![0u8; buf_len];
     (buf[..cipher_len]).copy_from_slice(cipher);
 
-    match Aes128CbcDec::new(key.as_bytes().into(), iv.as_bytes().into())
+    match Aes128CbcDec::new(key.as_ref().into(), iv.as_ref().into())
         .decrypt_padded_b2b_mut::<Pkcs7>(cipher, &mut buf)
     {
         Ok(pt) => Ok(pt.to_vec()),
@@ -5,6 +5,7 @@ use uuid::Uuid;
 pub mod actor_utils;
 pub mod appdata;
 pub mod byte_utils;
+pub mod compress_utils;
 pub mod constant;
 pub mod crypto_utils;
 pub mod cycle_queue;
@@ -66,6 +67,20 @@ pub struct AppSysConfig {
     pub raft_snapshot_log_size: u64,
     pub console_login_timeout: i32,
     pub console_login_one_hour_limit: u32,
+    pub config_encryption_key: String,
+    pub config_history_max_count: usize,
+    pub config_history_max_age_days: u64,
+    pub config_history_max_size_mb: u64,
+    pub config_webhook_max_retry: u32,
+    pub config_webhook_timeout_ms: u64,
+    pub config_webhook_log_size: usize,
+    pub config_push_trace_size: usize,
+    pub raft_compress_threshold: usize,
+    pub config_recycle_retention_days: u64,
+    pub naming_health_check_interval_ms: u64,
+    pub naming_health_check_timeout_ms: u64,
+    pub naming_health_check_rate: u64,
+    pub http_trusted_proxy_ips: Vec<String>,
 }
 
 impl AppSysConfig {
@@ -120,6 +135,63 @@ impl AppSysConfig {
             .unwrap_or("false".to_owned())
             .parse()
             .unwrap_or(false);
+        let config_encryption_key =
+            std::env::var("RNACOS_CONFIG_ENCRYPTION_KEY").unwrap_or_default();
+        let config_history_max_count = std::env::var("RNACOS_CONFIG_HISTORY_MAX_COUNT")
+            .unwrap_or("100".to_owned())
+            .parse()
+            .unwrap_or(100);
+        let config_history_max_age_days = std::env::var("RNACOS_CONFIG_HISTORY_MAX_AGE_DAYS")
+            .unwrap_or("0".to_owned())
+            .parse()
+            .unwrap_or(0);
+        let config_history_max_size_mb = std::env::var("RNACOS_CONFIG_HISTORY_MAX_SIZE_MB")
+            .unwrap_or("0".to_owned())
+            .parse()
+            .unwrap_or(0);
+        let config_webhook_max_retry = std::env::var("RNACOS_CONFIG_WEBHOOK_MAX_RETRY")
+            .unwrap_or("3".to_owned())
+            .parse()
+            .unwrap_or(3);
+        let config_webhook_timeout_ms = std::env::var("RNACOS_CONFIG_WEBHOOK_TIMEOUT_MS")
+            .unwrap_or("3000".to_owned())
+            .parse()
+            .unwrap_or(3000);
+        let config_webhook_log_size = std::env::var("RNACOS_CONFIG_WEBHOOK_LOG_SIZE")
+            .unwrap_or("1000".to_owned())
+            .parse()
+            .unwrap_or(1000);
+        let config_push_trace_size = std::env::var("RNACOS_CONFIG_PUSH_TRACE_SIZE")
+            .unwrap_or("1000".to_owned())
+            .parse()
+            .unwrap_or(1000);
+        let raft_compress_threshold = std::env::var("RNACOS_RAFT_COMPRESS_THRESHOLD")
+            .unwrap_or("4096".to_owned())
+            .parse()
+            .unwrap_or(4096);
+        let config_recycle_retention_days = std::env::var("RNACOS_CONFIG_RECYCLE_RETENTION_DAYS")
+            .unwrap_or("30".to_owned())
+            .parse()
+            .unwrap_or(30);
+        let naming_health_check_interval_ms =
+            std::env::var("RNACOS_NAMING_HEALTH_CHECK_INTERVAL_MS")
+                .unwrap_or("5000".to_owned())
+                .parse()
+                .unwrap_or(5000);
+        let naming_health_check_timeout_ms = std::env::var("RNACOS_NAMING_HEALTH_CHECK_TIMEOUT_MS")
+            .unwrap_or("2000".to_owned())
+            .parse()
+            .unwrap_or(2000);
+        let naming_health_check_rate = std::env::var("RNACOS_NAMING_HEALTH_CHECK_RATE")
+            .unwrap_or("100".to_owned())
+            .parse()
+            .unwrap_or(100);
+        let http_trusted_proxy_ips = std::env::var("RNACOS_HTTP_TRUSTED_PROXY_IPS")
+            .unwrap_or_default()
+            .split(',')
+            .map(|e| e.trim().to_owned())
+            .filter(|e| !e.is_empty())
+            .collect();
         Self {
             config_db_dir,
             config_db_file,
@@ -136,9 +208,29 @@ impl AppSysConfig {
             raft_snapshot_log_size,
             console_login_timeout,
             console_login_one_hour_limit,
+            config_encryption_key,
+            config_history_max_count,
+            config_history_max_age_days,
+            config_history_max_size_mb,
+            config_webhook_max_retry,
+            config_webhook_timeout_ms,
+            config_webhook_log_size,
+            config_push_trace_size,
+            raft_compress_threshold,
+            config_recycle_retention_days,
+            naming_health_check_interval_ms,
+            naming_health_check_timeout_ms,
+            naming_health_check_rate,
+            http_trusted_proxy_ips,
         }
     }
 
+    ///
+    /// 只有来自可信代理的请求才使用转发头中的客户端ip
+    pub fn is_trusted_proxy(&self, ip: &str) -> bool {
+        self.http_trusted_proxy_ips.iter().any(|e| e == ip)
+    }
+
     pub fn get_grpc_addr(&self) -> String {
         format!("0.0.0.0:{}", &self.grpc_port)
     }
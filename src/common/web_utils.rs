@@ -1,6 +1,10 @@
-use actix_web::web;
+use std::sync::Arc;
+
+use actix_web::{web, HttpRequest};
 use tokio_stream::StreamExt;
 
+use super::appdata::AppShareData;
+
 const MAX_SIZE: usize = 10485760;
 
 pub async fn get_req_body(mut payload: web::Payload) -> anyhow::Result<Vec<u8>> {
@@ -15,3 +19,26 @@ pub async fn get_req_body(mut payload: web::Payload) -> anyhow::Result<Vec<u8>>
     }
     Ok(body.to_vec())
 }
+
+///
+/// 获取请求客户端ip;
+/// 默认使用连接的对端地址，对端为配置的可信代理时才使用代理转发头
+pub fn get_client_ip(req: &HttpRequest) -> String {
+    let peer_ip = req
+        .peer_addr()
+        .map(|e| e.ip().to_string())
+        .unwrap_or_default();
+    let trust_forwarded = req
+        .app_data::<web::Data<Arc<AppShareData>>>()
+        .map(|app| app.sys_config.is_trusted_proxy(&peer_ip))
+        .unwrap_or(false);
+    if !trust_forwarded {
+        return peer_ip;
+    }
+    let conn_info = req.connection_info();
+    let addr = conn_info.realip_remote_addr().unwrap_or_default();
+    match addr.parse::<std::net::SocketAddr>() {
+        Ok(v) => v.ip().to_string(),
+        Err(_) => addr.to_owned(),
+    }
+}
@@ -1,8 +1,17 @@
-use super::core::{ConfigActor, ConfigCmd, ConfigKey, ConfigResult, ListenerItem, ListenerResult};
+use super::config_encryption::CONFIG_ENCRYPTION;
+use super::config_index::{ConfigIndexMeta, ConfigQueryParam};
+use super::config_watch::ConfigWatcher;
+use super::core::{
+    ConfigActor, ConfigCmd, ConfigInfoDto, ConfigKey, ConfigResult, ListenerItem, ListenerResult,
+};
+use super::model::{ConfigBetaRule, ConfigClientInfo, ConfigError, ConfigListenerQueryParam};
 use super::utils;
+use super::ConfigUtils;
 use crate::common::appdata::AppShareData;
-use crate::common::web_utils::get_req_body;
-use crate::raft::cluster::model::{DelConfigReq, SetConfigReq};
+use crate::common::web_utils::{get_client_ip, get_req_body};
+use crate::console::config_api::get_op_user;
+use crate::raft::cluster::model::{DelConfigReq, SetBetaConfigReq, SetConfigReq};
+use crate::raft::cluster::query_cluster_config_listener;
 use crate::utils::select_option_by_clone;
 use chrono::Local;
 use std::cmp::max;
@@ -23,6 +32,17 @@ pub struct ConfigWebParams {
     pub group: Option<String>,
     pub tenant: Option<String>,
     pub content: Option<String>,
+    #[serde(rename = "type")]
+    pub config_type: Option<String>,
+    pub desc: Option<String>,
+    #[serde(rename = "config_tags")]
+    pub config_tags: Option<String>,
+    pub app_name: Option<String>,
+    pub cas_md5: Option<String>,
+    ///accurate或blur,有值时按分页查询配置列表
+    pub search: Option<String>,
+    pub page_no: Option<usize>,
+    pub page_size: Option<usize>,
 }
 
 impl ConfigWebParams {
@@ -32,6 +52,14 @@ impl ConfigWebParams {
             group: select_option_by_clone(&self.group, &o.group),
             tenant: select_option_by_clone(&self.tenant, &o.tenant),
             content: select_option_by_clone(&self.content, &o.content),
+            config_type: select_option_by_clone(&self.config_type, &o.config_type),
+            desc: select_option_by_clone(&self.desc, &o.desc),
+            config_tags: select_option_by_clone(&self.config_tags, &o.config_tags),
+            app_name: select_option_by_clone(&self.app_name, &o.app_name),
+            cas_md5: select_option_by_clone(&self.cas_md5, &o.cas_md5),
+            search: select_option_by_clone(&self.search, &o.search),
+            page_no: self.page_no.or(o.page_no),
+            page_size: self.page_size.or(o.page_size),
         }
     }
 
@@ -58,6 +86,19 @@ impl ConfigWebParams {
                 param.content = v.to_owned();
             }
         }
+        if let Some(v) = self.config_type.as_ref() {
+            if !v.is_empty() {
+                param.config_type = Some(Arc::new(v.to_owned()));
+            }
+        }
+        param.desc = self.desc.as_ref().map(|v| Arc::new(v.to_owned()));
+        param.config_tags = self.config_tags.as_ref().map(|v| Arc::new(v.to_owned()));
+        param.app_name = self.app_name.as_ref().map(|v| Arc::new(v.to_owned()));
+        param.cas_md5 = self
+            .cas_md5
+            .as_ref()
+            .filter(|v| !v.is_empty())
+            .map(|v| Arc::new(v.to_owned()));
         Ok(param)
     }
 }
@@ -68,9 +109,15 @@ pub struct ConfigWebConfirmedParam {
     pub group: String,
     pub tenant: String,
     pub content: String,
+    pub config_type: Option<Arc<String>>,
+    pub desc: Option<Arc<String>>,
+    pub config_tags: Option<Arc<String>>,
+    pub app_name: Option<Arc<String>>,
+    pub cas_md5: Option<Arc<String>>,
 }
 
 pub(crate) async fn add_config(
+    req: HttpRequest,
     a: web::Query<ConfigWebParams>,
     payload: web::Payload,
     appdata: web::Data<Arc<AppShareData>>,
@@ -107,17 +154,40 @@ pub(crate) async fn add_config(
         }
     }
 
+    let beta_ips = match req.headers().get("betaIps") {
+        Some(v) => ConfigBetaRule::parse_ips(&String::from_utf8_lossy(v.as_bytes())),
+        None => vec![],
+    };
     let param = selected_param.to_confirmed_param();
     match param {
         Ok(p) => {
-            let req = SetConfigReq::new(
-                ConfigKey::new(&p.data_id, &p.group, &p.tenant),
-                Arc::new(p.content.to_owned()),
-            );
-            match appdata.config_route.set_config(req).await {
+            let config_key = ConfigKey::new(&p.data_id, &p.group, &p.tenant);
+            let result = if beta_ips.is_empty() {
+                let mut set_req = SetConfigReq::new(config_key, Arc::new(p.content.to_owned()));
+                set_req.op_user = get_op_user(&req);
+                set_req.config_type = p.config_type;
+                set_req.desc = p.desc;
+                set_req.config_tags = p.config_tags;
+                set_req.app_name = p.app_name;
+                set_req.cas_md5 = p.cas_md5;
+                appdata.config_route.set_config(set_req).await
+            } else {
+                let rule = ConfigBetaRule::new(beta_ips, Default::default());
+                let mut beta_req =
+                    SetBetaConfigReq::new(config_key, Arc::new(p.content.to_owned()), rule);
+                beta_req.op_user = get_op_user(&req);
+                appdata.config_route.set_beta_config(beta_req).await
+            };
+            match result {
                 Ok(_) => HttpResponse::Ok()
                     .content_type("text/html; charset=utf-8")
                     .body("true"),
+                Err(err) if ConfigError::is_cas_conflict(&err) => {
+                    HttpResponse::Conflict().body(err.to_string())
+                }
+                Err(err) if ConfigError::is_schema_invalid(&err) => {
+                    HttpResponse::BadRequest().body(err.to_string())
+                }
                 Err(err) => HttpResponse::InternalServerError().body(err.to_string()),
             }
         }
@@ -126,6 +196,7 @@ pub(crate) async fn add_config(
 }
 
 pub(crate) async fn del_config(
+    req: HttpRequest,
     a: web::Query<ConfigWebParams>,
     payload: web::Payload,
     appdata: web::Data<Arc<AppShareData>>,
@@ -166,8 +237,9 @@ pub(crate) async fn del_config(
     let param = selected_param.to_confirmed_param();
     match param {
         Ok(p) => {
-            let req = DelConfigReq::new(ConfigKey::new(&p.data_id, &p.group, &p.tenant));
-            match appdata.config_route.del_config(req).await {
+            let mut del_req = DelConfigReq::new(ConfigKey::new(&p.data_id, &p.group, &p.tenant));
+            del_req.op_user = get_op_user(&req);
+            match appdata.config_route.del_config(del_req).await {
                 Ok(_) => HttpResponse::Ok()
                     .content_type("text/html; charset=utf-8")
                     .body("true"),
@@ -179,21 +251,51 @@ pub(crate) async fn del_config(
 }
 
 pub(crate) async fn get_config(
+    req: HttpRequest,
     a: web::Query<ConfigWebParams>,
     config_addr: web::Data<Addr<ConfigActor>>,
 ) -> impl Responder {
+    if a.search.is_some() {
+        return search_config(&a, &config_addr).await;
+    }
     let param = a.to_confirmed_param();
     match param {
         Ok(p) => {
-            let cmd = ConfigCmd::GET(ConfigKey::new(&p.data_id, &p.group, &p.tenant));
+            let client = ConfigClientInfo::new(get_client_ip(&req), Default::default());
+            let cmd =
+                ConfigCmd::GetByClient(ConfigKey::new(&p.data_id, &p.group, &p.tenant), client);
             match config_addr.send(cmd).await {
                 Ok(res) => {
                     let r: ConfigResult = res.unwrap();
                     match r {
-                        ConfigResult::DATA(v, md5) => HttpResponse::Ok()
-                            .content_type("text/html; charset=utf-8")
-                            .insert_header(("content-md5", md5.as_ref().to_string()))
-                            .body(v.as_ref().as_bytes().to_vec()),
+                        ConfigResult::QueryData(data) => {
+                            //与nacos一致，http接口返回解密后的内容
+                            let content = match CONFIG_ENCRYPTION.decrypt(
+                                &p.data_id,
+                                data.encrypted_data_key.as_ref(),
+                                data.content,
+                            ) {
+                                Ok(v) => v,
+                                Err(err) => {
+                                    return HttpResponse::InternalServerError()
+                                        .body(err.to_string())
+                                }
+                            };
+                            let mut builder = HttpResponse::Ok();
+                            builder
+                                .content_type("text/html; charset=utf-8")
+                                .insert_header(("content-md5", data.md5.as_ref().to_string()));
+                            if let Some(config_type) = &data.config_type {
+                                builder.insert_header((
+                                    "Config-Type",
+                                    config_type.as_ref().to_string(),
+                                ));
+                            }
+                            if data.beta {
+                                builder.insert_header(("isBeta", "true"));
+                            }
+                            builder.body(content.as_ref().as_bytes().to_vec())
+                        }
                         _ => HttpResponse::NotFound().body("config data not exist"),
                     }
                 }
@@ -204,6 +306,64 @@ pub(crate) async fn get_config(
     }
 }
 
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigSearchPage {
+    pub total_count: usize,
+    pub page_number: usize,
+    pub pages_available: usize,
+    pub page_items: Vec<ConfigInfoDto>,
+}
+
+///
+/// 兼容nacos配置列表查询，search=accurate精确匹配，search=blur模糊匹配
+async fn search_config(a: &ConfigWebParams, config_addr: &Addr<ConfigActor>) -> HttpResponse {
+    let page_no = max(a.page_no.unwrap_or(1), 1);
+    let page_size = a.page_size.unwrap_or(100).clamp(1, 500);
+    let tenant = ConfigUtils::default_tenant(a.tenant.clone().unwrap_or_default());
+    let mut param = ConfigQueryParam {
+        tenant: Some(Arc::new(tenant)),
+        query_context: true,
+        app_name: a
+            .app_name
+            .as_ref()
+            .filter(|e| !e.is_empty())
+            .map(|e| Arc::new(e.to_owned())),
+        config_tags: a
+            .config_tags
+            .as_ref()
+            .map(|e| ConfigIndexMeta::split_tags(e))
+            .unwrap_or_default(),
+        offset: (page_no - 1) * page_size,
+        limit: page_size,
+        ..Default::default()
+    };
+    if a.search.as_deref() == Some("blur") {
+        param.like_data_id = a.data_id.as_ref().map(|e| e.replace('*', ""));
+        param.like_group = a.group.as_ref().map(|e| e.replace('*', ""));
+    } else {
+        param.data_id = a.data_id.as_ref().map(|e| Arc::new(e.to_owned()));
+        param.group = a.group.as_ref().map(|e| Arc::new(e.to_owned()));
+    }
+    let cmd = ConfigCmd::QueryPageInfo(Box::new(param));
+    match config_addr.send(cmd).await {
+        Ok(res) => match res {
+            Ok(ConfigResult::ConfigInfoPage(size, list)) => {
+                let page = ConfigSearchPage {
+                    total_count: size,
+                    page_number: page_no,
+                    pages_available: size.div_ceil(page_size),
+                    page_items: list,
+                };
+                HttpResponse::Ok().json(page)
+            }
+            Ok(_) => HttpResponse::InternalServerError().body("config result type is error"),
+            Err(err) => HttpResponse::InternalServerError().body(err.to_string()),
+        },
+        Err(err) => HttpResponse::InternalServerError().body(err.to_string()),
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct ListenerParams {
     #[serde(rename(serialize = "Listening-Configs", deserialize = "Listening-Configs"))]
@@ -262,7 +422,8 @@ async fn listener_config(
         }
     }
     //println!("timeout header:{:?},time_out:{}",_req.headers().get("Long-Pulling-Timeout") ,time_out);
-    let cmd = ConfigCmd::LISTENER(list, tx, time_out);
+    let client = ConfigClientInfo::new(get_client_ip(&_req), Default::default());
+    let cmd = ConfigCmd::LISTENER(list, tx, time_out, client);
     let _ = config_addr.send(cmd).await;
     let res = rx.await.unwrap();
     let v = match res {
@@ -284,6 +445,138 @@ async fn listener_config(
         .body(v)
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConfigWatchParams {
+    ///推送事件是否带上配置内容
+    pub content: Option<bool>,
+}
+
+///
+/// 以SSE持续推送监听配置的变更，监听参数与长轮询一致；
+/// 客户端重连时上报最后收到的md5即可获取断开期间的变更
+async fn watch_config(
+    req: HttpRequest,
+    a: web::Query<ListenerParams>,
+    option: web::Query<ConfigWatchParams>,
+    payload: web::Payload,
+    config_addr: web::Data<Addr<ConfigActor>>,
+) -> impl Responder {
+    let body = match get_req_body(payload).await {
+        Ok(v) => v,
+        Err(err) => {
+            return HttpResponse::InternalServerError().body(err.to_string());
+        }
+    };
+    let b = match serde_urlencoded::from_bytes(&body) {
+        Ok(v) => v,
+        Err(err) => {
+            return HttpResponse::InternalServerError().body(err.to_string());
+        }
+    };
+    let list = a.select_option(&b).to_items();
+    if list.is_empty() {
+        return HttpResponse::BadRequest().body("error:listener empty");
+    }
+    let client = ConfigClientInfo::new(get_client_ip(&req), Default::default());
+    match ConfigWatcher::register(
+        config_addr.get_ref().clone(),
+        list,
+        client,
+        option.content.unwrap_or(false),
+    )
+    .await
+    {
+        Ok(watcher) => HttpResponse::Ok()
+            .content_type("text/event-stream")
+            .insert_header(("Cache-Control", "no-cache"))
+            .streaming(watcher.into_stream()),
+        Err(err) => HttpResponse::InternalServerError().body(err.to_string()),
+    }
+}
+
+///
+/// 监听查询结果，与nacos GroupkeyListenserStatus结构保持一致
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ListenerStatusResult {
+    pub collection_status: u16,
+    pub lisenters_groupkey_status: HashMap<String, String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ListenerIpParams {
+    pub ip: Option<String>,
+}
+
+///
+/// 查询监听指定配置的客户端，返回客户端ip及最后上报的md5
+async fn get_config_listener(
+    a: web::Query<ConfigWebParams>,
+    app: web::Data<Arc<AppShareData>>,
+) -> impl Responder {
+    let p = match a.to_confirmed_param() {
+        Ok(p) if !p.data_id.is_empty() => p,
+        Ok(_) => return HttpResponse::BadRequest().body("dataId is empty"),
+        Err(e) => return HttpResponse::BadRequest().body(e),
+    };
+    let param = ConfigListenerQueryParam {
+        key: Some(ConfigKey::new(&p.data_id, &p.group, &p.tenant).build_key()),
+        ..Default::default()
+    };
+    match query_cluster_config_listener(&app, param).await {
+        Ok(list) => {
+            let mut result = ListenerStatusResult {
+                collection_status: 200,
+                ..Default::default()
+            };
+            for item in list {
+                result.lisenters_groupkey_status.insert(
+                    item.client_ip.as_ref().to_owned(),
+                    item.md5.as_ref().to_owned(),
+                );
+            }
+            HttpResponse::Ok().json(result)
+        }
+        Err(err) => HttpResponse::InternalServerError().body(err.to_string()),
+    }
+}
+
+///
+/// 查询指定客户端ip监听的配置，返回dataId+group+tenant及最后上报的md5
+async fn get_listener_by_ip(
+    a: web::Query<ListenerIpParams>,
+    app: web::Data<Arc<AppShareData>>,
+) -> impl Responder {
+    let ip = match &a.ip {
+        Some(ip) if !ip.is_empty() => ip.to_owned(),
+        _ => return HttpResponse::BadRequest().body("ip is empty"),
+    };
+    let param = ConfigListenerQueryParam {
+        client_ip: Some(ip),
+        ..Default::default()
+    };
+    match query_cluster_config_listener(&app, param).await {
+        Ok(list) => {
+            let mut result = ListenerStatusResult {
+                collection_status: 200,
+                ..Default::default()
+            };
+            for item in list {
+                let group_key = if item.tenant.is_empty() {
+                    format!("{}+{}", item.data_id, item.group)
+                } else {
+                    format!("{}+{}+{}", item.data_id, item.group, item.tenant)
+                };
+                result
+                    .lisenters_groupkey_status
+                    .insert(group_key, item.md5.as_ref().to_owned());
+            }
+            HttpResponse::Ok().json(result)
+        }
+        Err(err) => HttpResponse::InternalServerError().body(err.to_string()),
+    }
+}
+
 pub fn app_config(config: &mut web::ServiceConfig) {
     config.service(
         web::scope("/nacos/v1/cs")
@@ -294,6 +587,16 @@ pub fn app_config(config: &mut web::ServiceConfig) {
                     .route(web::put().to(add_config))
                     .route(web::delete().to(del_config)),
             )
-            .service(web::resource("/configs/listener").route(web::post().to(listener_config))),
+            .service(
+                web::resource("/configs/listener")
+                    .route(web::get().to(get_config_listener))
+                    .route(web::post().to(listener_config)),
+            )
+            .service(
+                web::resource("/configs/watch")
+                    .route(web::get().to(watch_config))
+                    .route(web::post().to(watch_config)),
+            )
+            .service(web::resource("/listener").route(web::get().to(get_listener_by_ip))),
     );
 }
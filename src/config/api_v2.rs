@@ -0,0 +1,476 @@
+use std::cmp::max;
+use std::sync::Arc;
+
+use actix_web::{http::StatusCode, web, HttpRequest, HttpResponse, Responder};
+use serde::{Deserialize, Serialize};
+
+use super::api::ConfigWebParams;
+use super::config_encryption::CONFIG_ENCRYPTION;
+use super::config_history::ConfigHistoryUtils;
+use super::core::{ConfigCmd, ConfigHistoryInfoDto, ConfigKey, ConfigResult};
+use super::model::{ConfigClientInfo, ConfigError, HistoryItem};
+use super::utils::param_utils;
+use crate::common::appdata::AppShareData;
+use crate::common::web_utils::{get_client_ip, get_req_body};
+use crate::config::dal::ConfigHistoryParam;
+use crate::console::config_api::get_op_user;
+use crate::raft::cluster::model::{DelConfigReq, SetConfigReq};
+use crate::utils::{get_md5, select_option_by_clone};
+
+///
+/// nacos v2 openapi错误码
+pub const SUCCESS: i32 = 0;
+pub const PARAMETER_MISSING: i32 = 10000;
+pub const TENANT_PARAM_ERROR: i32 = 20001;
+pub const PARAMETER_VALIDATE_ERROR: i32 = 20002;
+pub const RESOURCE_NOT_FOUND: i32 = 20004;
+pub const RESOURCE_CONFLICT: i32 = 20005;
+pub const SERVER_ERROR: i32 = 30000;
+
+///
+/// nacos v2 openapi统一返回结构
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct V2Result<T> {
+    pub code: i32,
+    pub message: String,
+    pub data: Option<T>,
+}
+
+impl<T> V2Result<T> {
+    pub fn success(data: T) -> Self {
+        Self {
+            code: SUCCESS,
+            message: "success".to_owned(),
+            data: Some(data),
+        }
+    }
+}
+
+impl V2Result<()> {
+    pub fn error(code: i32, message: String) -> Self {
+        Self {
+            code,
+            message,
+            data: None,
+        }
+    }
+
+    fn error_response(status: StatusCode, code: i32, message: String) -> HttpResponse {
+        HttpResponse::build(status).json(Self::error(code, message))
+    }
+
+    fn param_missing(name: &str) -> HttpResponse {
+        Self::error_response(
+            StatusCode::BAD_REQUEST,
+            PARAMETER_MISSING,
+            format!("Required parameter '{}' type String is not present", name),
+        )
+    }
+
+    fn param_error(code: i32, message: String) -> HttpResponse {
+        Self::error_response(StatusCode::BAD_REQUEST, code, message)
+    }
+
+    fn not_found(message: String) -> HttpResponse {
+        Self::error_response(StatusCode::NOT_FOUND, RESOURCE_NOT_FOUND, message)
+    }
+
+    fn server_error(message: String) -> HttpResponse {
+        Self::error_response(StatusCode::INTERNAL_SERVER_ERROR, SERVER_ERROR, message)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigV2Params {
+    pub data_id: Option<String>,
+    pub group: Option<String>,
+    pub namespace_id: Option<String>,
+    pub content: Option<String>,
+    #[serde(rename = "type")]
+    pub config_type: Option<String>,
+    pub desc: Option<String>,
+    pub config_tags: Option<String>,
+    pub app_name: Option<String>,
+    pub src_user: Option<String>,
+    pub cas_md5: Option<String>,
+}
+
+impl ConfigV2Params {
+    pub fn select_option(&self, o: &Self) -> Self {
+        Self {
+            data_id: select_option_by_clone(&self.data_id, &o.data_id),
+            group: select_option_by_clone(&self.group, &o.group),
+            namespace_id: select_option_by_clone(&self.namespace_id, &o.namespace_id),
+            content: select_option_by_clone(&self.content, &o.content),
+            config_type: select_option_by_clone(&self.config_type, &o.config_type),
+            desc: select_option_by_clone(&self.desc, &o.desc),
+            config_tags: select_option_by_clone(&self.config_tags, &o.config_tags),
+            app_name: select_option_by_clone(&self.app_name, &o.app_name),
+            src_user: select_option_by_clone(&self.src_user, &o.src_user),
+            cas_md5: select_option_by_clone(&self.cas_md5, &o.cas_md5),
+        }
+    }
+
+    ///
+    /// v2接口dataId与group为必填参数，校验后转为v1参数复用其处理逻辑
+    fn to_web_params(&self, check_content: bool) -> Result<ConfigWebParams, Box<HttpResponse>> {
+        if self.data_id.as_ref().is_none_or(|e| e.is_empty()) {
+            return Err(Box::new(V2Result::param_missing("dataId")));
+        }
+        if self.group.as_ref().is_none_or(|e| e.is_empty()) {
+            return Err(Box::new(V2Result::param_missing("group")));
+        }
+        if check_content && self.content.is_none() {
+            return Err(Box::new(V2Result::param_missing("content")));
+        }
+        if let Err(err) = param_utils::check_tenant(&self.namespace_id) {
+            return Err(Box::new(V2Result::param_error(
+                TENANT_PARAM_ERROR,
+                err.to_string(),
+            )));
+        }
+        let content = if check_content {
+            self.content.clone()
+        } else {
+            Some("rm".to_owned())
+        };
+        if let Err(err) = param_utils::check_param(
+            &self.data_id,
+            &self.group,
+            &Some(String::from("datumId")),
+            &content,
+        ) {
+            return Err(Box::new(V2Result::param_error(
+                PARAMETER_VALIDATE_ERROR,
+                err.to_string(),
+            )));
+        }
+        Ok(ConfigWebParams {
+            data_id: self.data_id.clone(),
+            group: self.group.clone(),
+            tenant: self.namespace_id.clone(),
+            content: self.content.clone(),
+            config_type: self.config_type.clone(),
+            desc: self.desc.clone(),
+            config_tags: self.config_tags.clone(),
+            app_name: self.app_name.clone(),
+            cas_md5: self.cas_md5.clone(),
+            search: None,
+            page_no: None,
+            page_size: None,
+        })
+    }
+
+    fn to_config_key(&self) -> Result<ConfigKey, Box<HttpResponse>> {
+        let p = self
+            .to_web_params(false)?
+            .to_confirmed_param()
+            .map_err(|e| Box::new(V2Result::param_error(PARAMETER_VALIDATE_ERROR, e)))?;
+        Ok(ConfigKey::new(&p.data_id, &p.group, &p.tenant))
+    }
+}
+
+async fn get_body_params(payload: web::Payload) -> Result<ConfigV2Params, HttpResponse> {
+    let body = get_req_body(payload)
+        .await
+        .map_err(|e| V2Result::server_error(e.to_string()))?;
+    serde_urlencoded::from_bytes(&body)
+        .map_err(|e| V2Result::param_error(PARAMETER_VALIDATE_ERROR, e.to_string()))
+}
+
+async fn get_config(
+    req: HttpRequest,
+    a: web::Query<ConfigV2Params>,
+    app: web::Data<Arc<AppShareData>>,
+) -> impl Responder {
+    let key = match a.to_config_key() {
+        Ok(v) => v,
+        Err(res) => return *res,
+    };
+    let client = ConfigClientInfo::new(get_client_ip(&req), Default::default());
+    let cmd = ConfigCmd::GetByClient(key.clone(), client);
+    match app.config_addr.send(cmd).await {
+        Ok(Ok(ConfigResult::QueryData(data))) => {
+            match CONFIG_ENCRYPTION.decrypt(
+                &key.data_id,
+                data.encrypted_data_key.as_ref(),
+                data.content,
+            ) {
+                Ok(content) => HttpResponse::Ok().json(V2Result::success(content)),
+                Err(err) => V2Result::server_error(err.to_string()),
+            }
+        }
+        Ok(Ok(_)) => V2Result::not_found("config data not exist".to_owned()),
+        Ok(Err(err)) => V2Result::server_error(err.to_string()),
+        Err(err) => V2Result::server_error(err.to_string()),
+    }
+}
+
+async fn publish_config(
+    req: HttpRequest,
+    a: web::Query<ConfigV2Params>,
+    payload: web::Payload,
+    app: web::Data<Arc<AppShareData>>,
+) -> impl Responder {
+    let b = match get_body_params(payload).await {
+        Ok(v) => v,
+        Err(res) => return res,
+    };
+    let selected_param = a.select_option(&b);
+    let p = match selected_param.to_web_params(true).and_then(|v| {
+        v.to_confirmed_param()
+            .map_err(|e| Box::new(V2Result::param_error(PARAMETER_VALIDATE_ERROR, e)))
+    }) {
+        Ok(v) => v,
+        Err(res) => return *res,
+    };
+    let config_key = ConfigKey::new(&p.data_id, &p.group, &p.tenant);
+    let mut set_req = SetConfigReq::new(config_key, Arc::new(p.content));
+    set_req.op_user = get_op_user(&req).or_else(|| {
+        selected_param
+            .src_user
+            .filter(|e| !e.is_empty())
+            .map(Arc::new)
+    });
+    set_req.config_type = p.config_type;
+    set_req.desc = p.desc;
+    set_req.config_tags = p.config_tags;
+    set_req.app_name = p.app_name;
+    set_req.cas_md5 = p.cas_md5;
+    match app.config_route.set_config(set_req).await {
+        Ok(_) => HttpResponse::Ok().json(V2Result::success(true)),
+        Err(err) if ConfigError::is_cas_conflict(&err) => {
+            V2Result::error_response(StatusCode::CONFLICT, RESOURCE_CONFLICT, err.to_string())
+        }
+        Err(err) if ConfigError::is_schema_invalid(&err) => {
+            V2Result::param_error(PARAMETER_VALIDATE_ERROR, err.to_string())
+        }
+        Err(err) => V2Result::server_error(err.to_string()),
+    }
+}
+
+async fn delete_config(
+    req: HttpRequest,
+    a: web::Query<ConfigV2Params>,
+    payload: web::Payload,
+    app: web::Data<Arc<AppShareData>>,
+) -> impl Responder {
+    let b = match get_body_params(payload).await {
+        Ok(v) => v,
+        Err(res) => return res,
+    };
+    let key = match a.select_option(&b).to_config_key() {
+        Ok(v) => v,
+        Err(res) => return *res,
+    };
+    let mut del_req = DelConfigReq::new(key);
+    del_req.op_user = get_op_user(&req);
+    match app.config_route.del_config(del_req).await {
+        Ok(_) => HttpResponse::Ok().json(V2Result::success(true)),
+        Err(err) => V2Result::server_error(err.to_string()),
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigHistoryV2Params {
+    pub data_id: Option<String>,
+    pub group: Option<String>,
+    pub namespace_id: Option<String>,
+    ///历史记录id，查询详情时使用
+    pub nid: Option<u64>,
+    ///历史记录id，查询上一版本时使用
+    pub id: Option<u64>,
+    pub page_no: Option<usize>,
+    pub page_size: Option<usize>,
+}
+
+impl ConfigHistoryV2Params {
+    fn to_config_key(&self) -> Result<ConfigKey, Box<HttpResponse>> {
+        ConfigV2Params {
+            data_id: self.data_id.clone(),
+            group: self.group.clone(),
+            namespace_id: self.namespace_id.clone(),
+            ..Default::default()
+        }
+        .to_config_key()
+    }
+}
+
+///
+/// 与nacos ConfigHistoryInfo保持一致，省略rnacos未记录的字段
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigHistoryV2Info {
+    pub id: u64,
+    pub data_id: String,
+    pub group: String,
+    pub tenant: String,
+    pub md5: String,
+    pub content: String,
+    pub src_user: Option<String>,
+    pub created_time: i64,
+    pub last_modified_time: i64,
+}
+
+impl ConfigHistoryV2Info {
+    fn new(key: &ConfigKey, item: &HistoryItem) -> Self {
+        Self::from_dto(key, item.to_dto(key))
+    }
+
+    fn from_dto(key: &ConfigKey, dto: ConfigHistoryInfoDto) -> Self {
+        let content = dto.content.unwrap_or_default();
+        let modified_time = dto.modified_time.unwrap_or_default();
+        Self {
+            id: dto.id.unwrap_or_default() as u64,
+            data_id: key.data_id.as_ref().to_owned(),
+            group: key.group.as_ref().to_owned(),
+            tenant: key.tenant.as_ref().to_owned(),
+            md5: get_md5(&content),
+            content,
+            src_user: dto.op_user,
+            created_time: modified_time,
+            last_modified_time: modified_time,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigHistoryV2Page {
+    pub total_count: usize,
+    pub page_number: usize,
+    pub pages_available: usize,
+    pub page_items: Vec<ConfigHistoryV2Info>,
+}
+
+async fn query_history_list(
+    a: web::Query<ConfigHistoryV2Params>,
+    app: web::Data<Arc<AppShareData>>,
+) -> impl Responder {
+    let key = match a.to_config_key() {
+        Ok(v) => v,
+        Err(res) => return *res,
+    };
+    let page_no = max(a.page_no.unwrap_or(1), 1);
+    let page_size = a.page_size.unwrap_or(100).clamp(1, 500);
+    let param = ConfigHistoryParam {
+        data_id: Some(key.data_id.as_ref().to_owned()),
+        group: Some(key.group.as_ref().to_owned()),
+        tenant: Some(key.tenant.as_ref().to_owned()),
+        offset: Some(((page_no - 1) * page_size) as i64),
+        limit: Some(page_size as i64),
+        ..Default::default()
+    };
+    match ConfigHistoryUtils::query_history_page(&app.raft_table_manage, &param).await {
+        Ok((size, list)) => {
+            let page_items = list
+                .into_iter()
+                .map(|dto| ConfigHistoryV2Info::from_dto(&key, dto))
+                .collect();
+            HttpResponse::Ok().json(V2Result::success(ConfigHistoryV2Page {
+                total_count: size,
+                page_number: page_no,
+                pages_available: size.div_ceil(page_size),
+                page_items,
+            }))
+        }
+        Err(err) => V2Result::server_error(err.to_string()),
+    }
+}
+
+async fn get_history(
+    a: web::Query<ConfigHistoryV2Params>,
+    app: web::Data<Arc<AppShareData>>,
+) -> impl Responder {
+    let key = match a.to_config_key() {
+        Ok(v) => v,
+        Err(res) => return *res,
+    };
+    let nid = match a.nid {
+        Some(v) => v,
+        None => return V2Result::param_missing("nid"),
+    };
+    match ConfigHistoryUtils::get_history(&app.raft_table_manage, &key, nid).await {
+        Ok(Some(item)) => {
+            HttpResponse::Ok().json(V2Result::success(ConfigHistoryV2Info::new(&key, &item)))
+        }
+        Ok(None) => V2Result::not_found(format!(
+            "certain config history for nid = {} not exist",
+            nid
+        )),
+        Err(err) => V2Result::server_error(err.to_string()),
+    }
+}
+
+async fn get_previous_history(
+    a: web::Query<ConfigHistoryV2Params>,
+    app: web::Data<Arc<AppShareData>>,
+) -> impl Responder {
+    let key = match a.to_config_key() {
+        Ok(v) => v,
+        Err(res) => return *res,
+    };
+    let id = match a.id {
+        Some(v) => v,
+        None => return V2Result::param_missing("id"),
+    };
+    match ConfigHistoryUtils::get_previous_history(&app.raft_table_manage, &key, id).await {
+        Ok(Some(item)) => {
+            HttpResponse::Ok().json(V2Result::success(ConfigHistoryV2Info::new(&key, &item)))
+        }
+        Ok(None) => {
+            V2Result::not_found(format!("previous config history for id = {} not exist", id))
+        }
+        Err(err) => V2Result::server_error(err.to_string()),
+    }
+}
+
+pub fn app_config(config: &mut web::ServiceConfig) {
+    config.service(
+        web::scope("/nacos/v2/cs")
+            .service(
+                web::resource("/config")
+                    .route(web::get().to(get_config))
+                    .route(web::post().to(publish_config))
+                    .route(web::put().to(publish_config))
+                    .route(web::delete().to(delete_config)),
+            )
+            .service(web::resource("/history").route(web::get().to(get_history)))
+            .service(web::resource("/history/list").route(web::get().to(query_history_list)))
+            .service(web::resource("/history/previous").route(web::get().to(get_previous_history))),
+    );
+}
+
+#[test]
+fn config_v2_params() {
+    let params = ConfigV2Params {
+        data_id: Some("app.yaml".to_owned()),
+        namespace_id: Some("dev".to_owned()),
+        ..Default::default()
+    };
+    assert!(params.to_config_key().is_err());
+    let params = ConfigV2Params {
+        group: Some("DEFAULT_GROUP".to_owned()),
+        ..params
+    };
+    let key = params.to_config_key().unwrap();
+    assert_eq!(
+        key.build_key(),
+        ConfigKey::new("app.yaml", "DEFAULT_GROUP", "dev").build_key()
+    );
+    assert!(params.to_web_params(true).is_err());
+    let params = ConfigV2Params {
+        namespace_id: Some("public".to_owned()),
+        content: Some("a: 1".to_owned()),
+        ..params
+    };
+    let p = params
+        .to_web_params(true)
+        .unwrap()
+        .to_confirmed_param()
+        .unwrap();
+    assert_eq!(p.tenant, "");
+    assert_eq!(p.content, "a: 1");
+}
@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+use similar::{ChangeTag, TextDiff};
+
+///
+/// 两个配置版本按行比较的结果
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigDiffDto {
+    pub tenant: String,
+    pub group: String,
+    pub data_id: String,
+    pub from_id: u64,
+    ///为空表示与当前配置比较
+    pub to_id: Option<u64>,
+    pub from_md5: String,
+    pub to_md5: String,
+    ///unified格式的差异内容
+    pub diff: String,
+    pub added_lines: usize,
+    pub removed_lines: usize,
+    pub unchanged_lines: usize,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DiffStat {
+    pub added_lines: usize,
+    pub removed_lines: usize,
+    pub unchanged_lines: usize,
+}
+
+pub struct ConfigDiffUtils;
+
+impl ConfigDiffUtils {
+    ///
+    /// 生成unified diff及变更行数统计
+    pub fn unified_diff(
+        from_name: &str,
+        from: &str,
+        to_name: &str,
+        to: &str,
+        context_radius: usize,
+    ) -> (String, DiffStat) {
+        let text_diff = TextDiff::from_lines(from, to);
+        let mut stat = DiffStat::default();
+        for change in text_diff.iter_all_changes() {
+            match change.tag() {
+                ChangeTag::Equal => stat.unchanged_lines += 1,
+                ChangeTag::Delete => stat.removed_lines += 1,
+                ChangeTag::Insert => stat.added_lines += 1,
+            }
+        }
+        let diff = text_diff
+            .unified_diff()
+            .context_radius(context_radius)
+            .header(from_name, to_name)
+            .to_string();
+        (diff, stat)
+    }
+}
+
+#[test]
+fn config_unified_diff() {
+    let from = "a=1\nb=2\nc=3\n";
+    let to = "a=1\nb=20\nc=3\nd=4\n";
+    let (diff, stat) = ConfigDiffUtils::unified_diff("1", from, "2", to, 3);
+    assert_eq!(
+        stat,
+        DiffStat {
+            added_lines: 2,
+            removed_lines: 1,
+            unchanged_lines: 2,
+        }
+    );
+    assert!(diff.starts_with("--- 1\n+++ 2\n"));
+    assert!(diff.contains("-b=2\n"));
+    assert!(diff.contains("+b=20\n"));
+    assert!(diff.contains("+d=4\n"));
+
+    let (diff, stat) = ConfigDiffUtils::unified_diff("1", from, "2", from, 3);
+    assert!(diff.is_empty());
+    assert_eq!(stat.unchanged_lines, 3);
+}
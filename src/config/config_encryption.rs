@@ -0,0 +1,271 @@
+use std::{collections::HashMap, sync::Arc};
+
+use rand::{rngs::OsRng, RngCore};
+
+use crate::common::{crypto_utils, APP_SYS_CONFIG};
+
+pub const CIPHER_PREFIX: &str = "cipher-";
+///
+/// 新格式数据密钥前缀，内容为base64(随机iv + 主密钥加密的数据密钥)
+pub const DATA_KEY_V2_PREFIX: &str = "v2:";
+const AES_BLOCK_SIZE: usize = 16;
+
+lazy_static::lazy_static! {
+    pub static ref CONFIG_ENCRYPTION: ConfigEncryptionManager =
+        ConfigEncryptionManager::new(&APP_SYS_CONFIG.config_encryption_key);
+}
+
+///
+/// 配置加密插件，与nacos的cipher-{算法}-dataId约定保持一致
+pub trait ConfigEncryptionPlugin: Send + Sync {
+    fn algorithm_name(&self) -> &str;
+
+    ///
+    /// 返回(encryptedDataKey,加密后的内容)
+    fn encrypt(&self, content: &str) -> anyhow::Result<(String, String)>;
+
+    fn decrypt(&self, encrypted_data_key: &str, content: &str) -> anyhow::Result<String>;
+}
+
+///
+/// 每次加密随机生成数据密钥(16字节密钥+16字节iv)，数据密钥使用主密钥及随机iv加密后保存
+pub struct AesEncryptionPlugin {
+    key: String,
+    ///只用于解密旧格式的数据密钥
+    legacy_iv: String,
+}
+
+impl AesEncryptionPlugin {
+    ///
+    /// 主密钥长度不小于16,取前16位作为密钥
+    pub fn new(master_key: &str) -> anyhow::Result<Self> {
+        if !master_key.is_ascii() || master_key.len() < 16 {
+            return Err(anyhow::anyhow!(
+                "config encryption key length must not be less than 16"
+            ));
+        }
+        let key = master_key[0..16].to_owned();
+        let legacy_iv = if master_key.len() >= 32 {
+            master_key[16..32].to_owned()
+        } else {
+            key.clone()
+        };
+        Ok(Self { key, legacy_iv })
+    }
+
+    fn random_bytes<const N: usize>() -> [u8; N] {
+        let mut buf = [0u8; N];
+        OsRng.fill_bytes(&mut buf);
+        buf
+    }
+
+    fn encrypt_data_key(&self, data_key: &[u8]) -> anyhow::Result<String> {
+        let iv: [u8; AES_BLOCK_SIZE] = Self::random_bytes();
+        let mut data = iv.to_vec();
+        data.extend(crypto_utils::encrypt_aes128(&self.key, iv, data_key)?);
+        Ok(format!(
+            "{}{}",
+            DATA_KEY_V2_PREFIX,
+            crypto_utils::encode_base64(&data)
+        ))
+    }
+
+    ///
+    /// 兼容旧格式：数据密钥为32位hex字符串，使用主密钥派生的iv加密
+    fn decrypt_data_key(&self, encrypted_data_key: &str) -> anyhow::Result<Vec<u8>> {
+        let data_key = if let Some(v) = encrypted_data_key.strip_prefix(DATA_KEY_V2_PREFIX) {
+            let data = crypto_utils::decode_base64(v)?;
+            if data.len() <= AES_BLOCK_SIZE {
+                return Err(anyhow::anyhow!("encrypted data key is invalid"));
+            }
+            crypto_utils::decrypt_aes128(
+                &self.key,
+                &data[..AES_BLOCK_SIZE],
+                &data[AES_BLOCK_SIZE..],
+            )?
+        } else {
+            let data = crypto_utils::decode_base64(encrypted_data_key)?;
+            crypto_utils::decrypt_aes128(&self.key, &self.legacy_iv, &data)?
+        };
+        if data_key.len() != AES_BLOCK_SIZE * 2 {
+            return Err(anyhow::anyhow!("encrypted data key is invalid"));
+        }
+        Ok(data_key)
+    }
+}
+
+impl ConfigEncryptionPlugin for AesEncryptionPlugin {
+    fn algorithm_name(&self) -> &str {
+        "aes"
+    }
+
+    fn encrypt(&self, content: &str) -> anyhow::Result<(String, String)> {
+        let data_key: [u8; AES_BLOCK_SIZE * 2] = Self::random_bytes();
+        let cipher = crypto_utils::encrypt_aes128(
+            &data_key[..AES_BLOCK_SIZE],
+            &data_key[AES_BLOCK_SIZE..],
+            content.as_bytes(),
+        )?;
+        let encrypted_data_key = self.encrypt_data_key(&data_key)?;
+        Ok((encrypted_data_key, crypto_utils::encode_base64(&cipher)))
+    }
+
+    fn decrypt(&self, encrypted_data_key: &str, content: &str) -> anyhow::Result<String> {
+        let data_key = self.decrypt_data_key(encrypted_data_key)?;
+        let cipher = crypto_utils::decode_base64(content)?;
+        let plain = crypto_utils::decrypt_aes128(
+            &data_key[..AES_BLOCK_SIZE],
+            &data_key[AES_BLOCK_SIZE..],
+            &cipher,
+        )?;
+        Ok(String::from_utf8(plain)?)
+    }
+}
+
+#[derive(Default)]
+pub struct ConfigEncryptionManager {
+    plugins: HashMap<String, Box<dyn ConfigEncryptionPlugin>>,
+}
+
+impl ConfigEncryptionManager {
+    ///
+    /// 主密钥为空时不启用加密插件，cipher-开头的配置按明文保存
+    pub fn new(master_key: &str) -> Self {
+        let mut manager = Self::default();
+        if master_key.is_empty() {
+            return manager;
+        }
+        match AesEncryptionPlugin::new(master_key) {
+            Ok(plugin) => manager.add_plugin(Box::new(plugin)),
+            Err(err) => log::warn!("config encryption plugin init error,{}", err),
+        }
+        manager
+    }
+
+    pub fn add_plugin(&mut self, plugin: Box<dyn ConfigEncryptionPlugin>) {
+        self.plugins
+            .insert(plugin.algorithm_name().to_owned(), plugin);
+    }
+
+    ///
+    /// cipher-aes-xxx => aes
+    pub fn parse_algorithm_name(data_id: &str) -> Option<&str> {
+        let name = data_id.strip_prefix(CIPHER_PREFIX)?;
+        match name.find('-') {
+            Some(index) if index > 0 => Some(&name[..index]),
+            _ => None,
+        }
+    }
+
+    fn find_plugin(&self, data_id: &str) -> Option<&dyn ConfigEncryptionPlugin> {
+        let name = Self::parse_algorithm_name(data_id)?;
+        self.plugins.get(name).map(|e| e.as_ref())
+    }
+
+    pub fn need_encrypt(&self, data_id: &str) -> bool {
+        self.find_plugin(data_id).is_some()
+    }
+
+    ///
+    /// 不需要加密时返回None
+    pub fn encrypt(
+        &self,
+        data_id: &str,
+        content: &str,
+    ) -> anyhow::Result<Option<(Arc<String>, Arc<String>)>> {
+        if let Some(plugin) = self.find_plugin(data_id) {
+            let (data_key, content) = plugin.encrypt(content)?;
+            Ok(Some((Arc::new(data_key), Arc::new(content))))
+        } else {
+            if Self::parse_algorithm_name(data_id).is_some() {
+                log::warn!(
+                    "config encryption plugin not found, dataId:{} will be saved in plaintext",
+                    data_id
+                );
+            }
+            Ok(None)
+        }
+    }
+
+    ///
+    /// encryptedDataKey为空时内容未加密，直接返回
+    pub fn decrypt(
+        &self,
+        data_id: &str,
+        encrypted_data_key: Option<&Arc<String>>,
+        content: Arc<String>,
+    ) -> anyhow::Result<Arc<String>> {
+        let encrypted_data_key = match encrypted_data_key {
+            Some(v) if !v.is_empty() => v,
+            _ => return Ok(content),
+        };
+        if let Some(plugin) = self.find_plugin(data_id) {
+            Ok(Arc::new(plugin.decrypt(encrypted_data_key, &content)?))
+        } else {
+            Err(anyhow::anyhow!(
+                "config encryption plugin not found, dataId:{}",
+                data_id
+            ))
+        }
+    }
+}
+
+#[test]
+fn config_encryption() {
+    assert_eq!(
+        ConfigEncryptionManager::parse_algorithm_name("cipher-aes-db.yaml"),
+        Some("aes")
+    );
+    assert!(ConfigEncryptionManager::parse_algorithm_name("cipher-aes").is_none());
+    assert!(ConfigEncryptionManager::parse_algorithm_name("db.yaml").is_none());
+
+    let manager = ConfigEncryptionManager::new("0123456789abcdef0123456789abcdef");
+    assert!(manager.encrypt("db.yaml", "password=1").unwrap().is_none());
+    let (data_key, content) = manager
+        .encrypt("cipher-aes-db.yaml", "password=1")
+        .unwrap()
+        .unwrap();
+    assert_ne!(content.as_str(), "password=1");
+    let plain = manager
+        .decrypt("cipher-aes-db.yaml", Some(&data_key), content.clone())
+        .unwrap();
+    assert_eq!(plain.as_str(), "password=1");
+
+    let (other_data_key, other_content) = manager
+        .encrypt("cipher-aes-db.yaml", "password=1")
+        .unwrap()
+        .unwrap();
+    assert_ne!(other_data_key, data_key);
+    assert_ne!(other_content, content);
+
+    //主密钥长度小于32时iv不复用主密钥
+    let short = AesEncryptionPlugin::new("0123456789abcdef").unwrap();
+    let (data_key, content) = short.encrypt("password=2").unwrap();
+    assert!(data_key.starts_with(DATA_KEY_V2_PREFIX));
+    assert_eq!(short.decrypt(&data_key, &content).unwrap(), "password=2");
+
+    //旧格式数据密钥
+    let legacy_key = "00112233445566778899aabbccddeeff";
+    let legacy_data_key = crypto_utils::encode_base64(
+        &crypto_utils::encrypt_aes128(
+            "0123456789abcdef",
+            "0123456789abcdef",
+            legacy_key.as_bytes(),
+        )
+        .unwrap(),
+    );
+    let legacy_content = crypto_utils::encode_base64(
+        &crypto_utils::encrypt_aes128(&legacy_key[0..16], &legacy_key[16..32], b"password=3")
+            .unwrap(),
+    );
+    assert_eq!(
+        short.decrypt(&legacy_data_key, &legacy_content).unwrap(),
+        "password=3"
+    );
+
+    let disabled = ConfigEncryptionManager::new("");
+    assert!(disabled
+        .encrypt("cipher-aes-db.yaml", "password=1")
+        .unwrap()
+        .is_none());
+}
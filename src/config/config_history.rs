@@ -0,0 +1,326 @@
+use std::collections::BTreeMap;
+
+use actix::Addr;
+
+use crate::common::byte_utils::{bin_to_id, id_to_bin};
+use crate::common::constant::CONFIG_HISTORY_TREE_NAME;
+use crate::common::{AppSysConfig, APP_SYS_CONFIG};
+use crate::config::core::{ConfigHistoryInfoDto, ConfigKey};
+use crate::config::dal::ConfigHistoryParam;
+use crate::config::model::{ConfigHistoryItemDO, HistoryItem};
+use crate::raft::db::table::{TableManager, TableManagerQueryReq, TableManagerResult};
+
+lazy_static::lazy_static! {
+    pub static ref CONFIG_HISTORY_RETENTION: ConfigHistoryRetention =
+        ConfigHistoryRetention::new_by_config(&APP_SYS_CONFIG);
+}
+
+const HISTORY_KEY_SPLIT: u8 = 3;
+
+pub struct ConfigHistoryUtils;
+
+impl ConfigHistoryUtils {
+    ///
+    /// 历史记录key: 配置key + \x03 + 8字节历史id(大端)，同一配置的历史记录按id有序
+    pub fn build_key_prefix(key: &ConfigKey) -> Vec<u8> {
+        let mut v = key.build_key().into_bytes();
+        v.push(HISTORY_KEY_SPLIT);
+        v
+    }
+
+    pub fn build_key(key: &ConfigKey, history_id: u64) -> Vec<u8> {
+        let mut v = Self::build_key_prefix(key);
+        v.extend(id_to_bin(history_id));
+        v
+    }
+
+    ///
+    /// 拆分历史记录key，返回(配置key前缀,历史id)
+    pub fn split_key(key: &[u8]) -> Option<(&[u8], u64)> {
+        if key.len() < 9 {
+            return None;
+        }
+        let (prefix, id) = key.split_at(key.len() - 8);
+        Some((prefix, bin_to_id(id)))
+    }
+
+    ///
+    /// 按id倒序分页查询配置的历史记录
+    pub async fn query_history_page(
+        table_manager: &Addr<TableManager>,
+        param: &ConfigHistoryParam,
+    ) -> anyhow::Result<(usize, Vec<ConfigHistoryInfoDto>)> {
+        let key =
+            if let (Some(t), Some(g), Some(id)) = (&param.tenant, &param.group, &param.data_id) {
+                ConfigKey::new(id, g, t)
+            } else {
+                return Ok((0, vec![]));
+            };
+        let req = TableManagerQueryReq::QueryPrefixPageList {
+            table_name: CONFIG_HISTORY_TREE_NAME.clone(),
+            prefix: Self::build_key_prefix(&key),
+            offset: param.offset,
+            limit: param.limit,
+            is_rev: true,
+        };
+        match table_manager.send(req).await?? {
+            TableManagerResult::PageListResult(size, list) => {
+                let mut ret = Vec::with_capacity(list.len());
+                for (_, v) in list {
+                    let item: HistoryItem = ConfigHistoryItemDO::from_bytes(&v)?.into();
+                    ret.push(item.to_dto(&key));
+                }
+                Ok((size, ret))
+            }
+            _ => Ok((0, vec![])),
+        }
+    }
+
+    pub async fn get_history(
+        table_manager: &Addr<TableManager>,
+        key: &ConfigKey,
+        history_id: u64,
+    ) -> anyhow::Result<Option<HistoryItem>> {
+        let req = TableManagerQueryReq::GetByBytes {
+            table_name: CONFIG_HISTORY_TREE_NAME.clone(),
+            key: Self::build_key(key, history_id),
+        };
+        match table_manager.send(req).await?? {
+            TableManagerResult::Value(v) => Ok(Some(ConfigHistoryItemDO::from_bytes(&v)?.into())),
+            _ => Ok(None),
+        }
+    }
+
+    ///
+    /// 查询指定历史记录的上一条历史记录
+    pub async fn get_previous_history(
+        table_manager: &Addr<TableManager>,
+        key: &ConfigKey,
+        history_id: u64,
+    ) -> anyhow::Result<Option<HistoryItem>> {
+        let req = TableManagerQueryReq::QueryPrefixPageList {
+            table_name: CONFIG_HISTORY_TREE_NAME.clone(),
+            prefix: Self::build_key_prefix(key),
+            offset: None,
+            limit: None,
+            is_rev: true,
+        };
+        if let TableManagerResult::PageListResult(_, list) = table_manager.send(req).await?? {
+            for (k, v) in list {
+                match Self::split_key(&k) {
+                    Some((_, id)) if id < history_id => {
+                        return Ok(Some(ConfigHistoryItemDO::from_bytes(&v)?.into()));
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+///
+/// 配置历史记录保留策略
+#[derive(Debug, Clone, Default)]
+pub struct ConfigHistoryRetention {
+    ///每个配置保留的最大条数，0表示不限制
+    pub max_count: usize,
+    ///最长保留时间(毫秒)，0表示不限制
+    pub max_age: i64,
+    ///历史记录总大小上限(字节)，0表示不限制
+    pub max_size: u64,
+}
+
+impl ConfigHistoryRetention {
+    pub fn new_by_config(config: &AppSysConfig) -> Self {
+        Self {
+            max_count: config.config_history_max_count,
+            max_age: config.config_history_max_age_days as i64 * 24 * 3600 * 1000,
+            max_size: config.config_history_max_size_mb * 1024 * 1024,
+        }
+    }
+
+    ///
+    /// 写入历史记录后按条数裁剪同一配置的旧记录，返回移除的数量
+    pub fn trim_by_count(&self, table_data: &mut BTreeMap<Vec<u8>, Vec<u8>>, key: &[u8]) -> usize {
+        if self.max_count == 0 {
+            return 0;
+        }
+        let prefix = match ConfigHistoryUtils::split_key(key) {
+            Some((prefix, _)) => prefix.to_vec(),
+            None => return 0,
+        };
+        let keys: Vec<Vec<u8>> = table_data
+            .range(prefix.clone()..)
+            .take_while(|(k, _)| k.starts_with(&prefix))
+            .filter(|(k, _)| k.len() == prefix.len() + 8)
+            .map(|(k, _)| k.to_owned())
+            .collect();
+        if keys.len() <= self.max_count {
+            return 0;
+        }
+        let n = keys.len() - self.max_count;
+        for k in keys.into_iter().take(n) {
+            table_data.remove(&k);
+        }
+        n
+    }
+
+    ///
+    /// 按保留策略压缩历史记录，每个配置至少保留最后一条；
+    /// 超出总大小上限时按历史id从旧到新淘汰。返回移除的数量
+    pub fn compact(&self, table_data: &mut BTreeMap<Vec<u8>, Vec<u8>>, now: i64) -> usize {
+        let mut removes = vec![];
+        //可按总大小淘汰的记录:(历史id,key,大小)
+        let mut candidates = vec![];
+        let mut total_size = 0u64;
+        let mut group: Vec<(&Vec<u8>, &Vec<u8>)> = vec![];
+        let mut iter = table_data.iter().peekable();
+        while let Some((k, v)) = iter.next() {
+            group.push((k, v));
+            let prefix = ConfigHistoryUtils::split_key(k).map(|e| e.0);
+            let is_group_end = match iter.peek() {
+                Some((next, _)) => ConfigHistoryUtils::split_key(next).map(|e| e.0) != prefix,
+                None => true,
+            };
+            if !is_group_end {
+                continue;
+            }
+            let keep_from = if self.max_count > 0 {
+                group.len().saturating_sub(self.max_count)
+            } else {
+                0
+            };
+            let last_index = group.len() - 1;
+            for (i, (k, v)) in group.drain(..).enumerate() {
+                let size = v.len() as u64;
+                if i == last_index {
+                    total_size += size;
+                } else if i < keep_from || self.is_expired(v, now) {
+                    removes.push(k.to_owned());
+                } else {
+                    total_size += size;
+                    let id = ConfigHistoryUtils::split_key(k)
+                        .map(|e| e.1)
+                        .unwrap_or_default();
+                    candidates.push((id, k.to_owned(), size));
+                }
+            }
+        }
+        if self.max_size > 0 && total_size > self.max_size {
+            candidates.sort_by_key(|e| e.0);
+            for (_, k, size) in candidates {
+                if total_size <= self.max_size {
+                    break;
+                }
+                total_size -= size;
+                removes.push(k);
+            }
+        }
+        let count = removes.len();
+        for k in removes {
+            table_data.remove(&k);
+        }
+        count
+    }
+
+    ///
+    /// 以历史记录中最新的修改时间作为过期判断的基准时间;
+    /// 修改时间来自raft日志，各节点对相同数据的压缩结果一致，不依赖本地时钟
+    pub fn compact_by_log_time(&self, table_data: &mut BTreeMap<Vec<u8>, Vec<u8>>) -> usize {
+        let now = if self.max_age > 0 {
+            table_data
+                .values()
+                .filter_map(|v| ConfigHistoryItemDO::from_bytes(v).ok()?.last_time)
+                .max()
+                .unwrap_or_default()
+        } else {
+            0
+        };
+        self.compact(table_data, now)
+    }
+
+    fn is_expired(&self, value: &[u8], now: i64) -> bool {
+        if self.max_age <= 0 {
+            return false;
+        }
+        match ConfigHistoryItemDO::from_bytes(value) {
+            Ok(v) => v.last_time.unwrap_or_default() < now - self.max_age,
+            Err(_) => false,
+        }
+    }
+}
+
+#[cfg(test)]
+fn build_history_table(items: &[(&ConfigKey, u64, i64, &str)]) -> BTreeMap<Vec<u8>, Vec<u8>> {
+    let mut table_data = BTreeMap::new();
+    for (key, id, time, content) in items {
+        let item = ConfigHistoryItemDO {
+            id: Some(*id),
+            content: Some(content.to_string()),
+            last_time: Some(*time),
+            ..Default::default()
+        };
+        table_data.insert(
+            ConfigHistoryUtils::build_key(key, *id),
+            item.to_bytes().unwrap(),
+        );
+    }
+    table_data
+}
+
+#[test]
+fn history_retention() {
+    let a = ConfigKey::new("a", "DEFAULT_GROUP", "");
+    let b = ConfigKey::new("b", "DEFAULT_GROUP", "dev");
+    let items: Vec<(&ConfigKey, u64, i64, &str)> = vec![
+        (&a, 1, 100, "a1"),
+        (&b, 2, 100, "b1"),
+        (&a, 3, 200, "a2"),
+        (&a, 4, 300, "a3"),
+        (&b, 5, 300, "b2"),
+    ];
+    let key_a4 = ConfigHistoryUtils::build_key(&a, 4);
+    assert_eq!(ConfigHistoryUtils::split_key(&key_a4).unwrap().1, 4);
+
+    let retention = ConfigHistoryRetention {
+        max_count: 2,
+        ..Default::default()
+    };
+    let mut table_data = build_history_table(&items);
+    assert_eq!(retention.trim_by_count(&mut table_data, &key_a4), 1);
+    assert!(!table_data.contains_key(&ConfigHistoryUtils::build_key(&a, 1)));
+    assert_eq!(table_data.len(), 4);
+
+    let retention = ConfigHistoryRetention {
+        max_age: 150,
+        ..Default::default()
+    };
+    let mut table_data = build_history_table(&items);
+    //a1、b1过期被移除，每个配置的最后一条始终保留
+    assert_eq!(retention.compact(&mut table_data, 300), 2);
+    assert!(table_data.contains_key(&ConfigHistoryUtils::build_key(&a, 3)));
+    assert!(table_data.contains_key(&ConfigHistoryUtils::build_key(&b, 5)));
+    //基准时间取最新一条记录的修改时间
+    let mut table_data = build_history_table(&items);
+    assert_eq!(retention.compact_by_log_time(&mut table_data), 2);
+    assert_eq!(table_data.len(), 3);
+
+    let mut table_data = build_history_table(&items);
+    let total_size: u64 = table_data.values().map(|v| v.len() as u64).sum();
+    let retention = ConfigHistoryRetention {
+        max_size: total_size - 1,
+        ..Default::default()
+    };
+    //超出总大小时按id从旧到新淘汰
+    assert_eq!(retention.compact(&mut table_data, 300), 1);
+    assert!(!table_data.contains_key(&ConfigHistoryUtils::build_key(&a, 1)));
+    assert!(table_data.contains_key(&ConfigHistoryUtils::build_key(&b, 2)));
+    let retention = ConfigHistoryRetention {
+        max_size: 1,
+        ..Default::default()
+    };
+    //每个配置的最后一条不会被淘汰
+    assert_eq!(retention.compact(&mut table_data, 300), 2);
+    assert_eq!(table_data.len(), 2);
+}
@@ -0,0 +1,489 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use super::core::ConfigKey;
+use crate::utils::get_md5;
+
+lazy_static::lazy_static! {
+    ///
+    /// 引用语法: ${include:dataId} 或 ${include:group/dataId}，只能引用同一命名空间的配置
+    static ref INCLUDE_REGEX: regex::Regex = regex::Regex::new(r"\$\{include:([^}\s]+)\}").unwrap();
+}
+
+///
+/// 配置内容首行为该声明时才展开引用，展开后的内容不包含声明行
+pub const INCLUDE_ENABLE_DIRECTIVE: &str = "#rnacos:include";
+
+///
+/// 最大引用深度，超出时保留引用原文
+const MAX_INCLUDE_DEPTH: usize = 8;
+
+///
+/// 引用展开后的内容，md5按展开后的内容计算
+#[derive(Debug, Clone)]
+pub struct ConfigResolvedValue {
+    pub content: Arc<String>,
+    pub md5: Arc<String>,
+}
+
+///
+/// 配置引用关系索引，维护引用展开后的内容
+#[derive(Debug, Default)]
+pub struct ConfigIncludeIndex {
+    ///配置直接引用的配置
+    refs: HashMap<ConfigKey, Vec<ConfigKey>>,
+    ///引用了该配置的配置
+    dependents: HashMap<ConfigKey, HashSet<ConfigKey>>,
+    resolved: HashMap<ConfigKey, ConfigResolvedValue>,
+    ///展开时存在循环引用的配置
+    cycles: HashSet<ConfigKey>,
+}
+
+impl ConfigIncludeIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn build_ref_key(key: &ConfigKey, target: &str) -> ConfigKey {
+        match target.split_once('/') {
+            Some((group, data_id)) => ConfigKey::new(data_id, group, &key.tenant),
+            None => ConfigKey::new(target, &key.group, &key.tenant),
+        }
+    }
+
+    ///
+    /// 返回去掉声明行后的内容，未声明启用引用时返回None
+    pub fn strip_directive(content: &str) -> Option<&str> {
+        let body = content.strip_prefix(INCLUDE_ENABLE_DIRECTIVE)?;
+        if body.is_empty() {
+            return Some(body);
+        }
+        body.strip_prefix("\r\n")
+            .or_else(|| body.strip_prefix('\n'))
+    }
+
+    pub fn parse_refs(key: &ConfigKey, content: &str) -> Vec<ConfigKey> {
+        let mut list = vec![];
+        let content = match Self::strip_directive(content) {
+            Some(v) => v,
+            None => return list,
+        };
+        for cap in INCLUDE_REGEX.captures_iter(content) {
+            let ref_key = Self::build_ref_key(key, &cap[1]);
+            if !list.contains(&ref_key) {
+                list.push(ref_key);
+            }
+        }
+        list
+    }
+
+    pub fn get_resolved(&self, key: &ConfigKey) -> Option<&ConfigResolvedValue> {
+        self.resolved.get(key)
+    }
+
+    ///
+    /// 发布前检查新内容是否会产生循环引用，返回循环路径
+    pub fn find_cycle(&self, key: &ConfigKey, content: &str) -> Option<Vec<ConfigKey>> {
+        let mut visited = HashSet::new();
+        for ref_key in Self::parse_refs(key, content) {
+            let mut path = vec![key.clone()];
+            if self.find_path(&ref_key, key, &mut path, &mut visited) {
+                return Some(path);
+            }
+        }
+        None
+    }
+
+    fn find_path(
+        &self,
+        from: &ConfigKey,
+        target: &ConfigKey,
+        path: &mut Vec<ConfigKey>,
+        visited: &mut HashSet<ConfigKey>,
+    ) -> bool {
+        path.push(from.clone());
+        if from == target {
+            return true;
+        }
+        if visited.insert(from.clone()) {
+            for next in self.refs.get(from).into_iter().flatten() {
+                if self.find_path(next, target, path, visited) {
+                    return true;
+                }
+            }
+        }
+        path.pop();
+        false
+    }
+
+    ///
+    /// 更新配置的引用关系，content为空表示配置删除或不参与引用展开
+    pub fn update_refs(&mut self, key: &ConfigKey, content: Option<&str>) {
+        let refs = content
+            .map(|e| Self::parse_refs(key, e))
+            .unwrap_or_default();
+        if let Some(old_refs) = self.refs.remove(key) {
+            for ref_key in old_refs {
+                if let Some(set) = self.dependents.get_mut(&ref_key) {
+                    set.remove(key);
+                    if set.is_empty() {
+                        self.dependents.remove(&ref_key);
+                    }
+                }
+            }
+        }
+        if refs.is_empty() {
+            self.resolved.remove(key);
+            self.cycles.remove(key);
+            return;
+        }
+        for ref_key in &refs {
+            self.dependents
+                .entry(ref_key.clone())
+                .or_default()
+                .insert(key.clone());
+        }
+        self.refs.insert(key.clone(), refs);
+    }
+
+    ///
+    /// 配置变更后重新展开该配置及所有直接、间接引用它的配置；
+    /// lookup返回可被引用的配置原始内容。返回展开结果有变化的引用方配置
+    pub fn refresh<F>(&mut self, key: &ConfigKey, lookup: F) -> Vec<ConfigKey>
+    where
+        F: Fn(&ConfigKey) -> Option<Arc<String>>,
+    {
+        let mut affected = vec![key.clone()];
+        let mut visited: HashSet<ConfigKey> = HashSet::new();
+        visited.insert(key.clone());
+        let mut queue = VecDeque::new();
+        queue.push_back(key.clone());
+        while let Some(item) = queue.pop_front() {
+            if let Some(set) = self.dependents.get(&item) {
+                for dependent in set {
+                    if visited.insert(dependent.clone()) {
+                        affected.push(dependent.clone());
+                        queue.push_back(dependent.clone());
+                    }
+                }
+            }
+        }
+        let mut changes = vec![];
+        //同一次刷新中被多个配置引用的配置只展开一次
+        let mut memo = HashMap::new();
+        for item in affected {
+            if !self.refs.contains_key(&item) {
+                continue;
+            }
+            let content = match lookup(&item) {
+                Some(v) => v,
+                None => continue,
+            };
+            let mut ctx = ResolveContext {
+                lookup: &lookup,
+                stack: vec![item.clone()],
+                memo: &mut memo,
+                has_cycle: false,
+                truncated: false,
+            };
+            let resolved = Self::resolve_content(&item, &content, &mut ctx);
+            let has_cycle = ctx.has_cycle;
+            if has_cycle {
+                log::warn!("config include cycle,{:?}", &item);
+                self.cycles.insert(item.clone());
+            } else {
+                self.cycles.remove(&item);
+            }
+            let md5 = Arc::new(get_md5(&resolved));
+            let is_change = self
+                .resolved
+                .get(&item)
+                .map(|e| e.md5 != md5)
+                .unwrap_or(true);
+            self.resolved.insert(
+                item.clone(),
+                ConfigResolvedValue {
+                    content: Arc::new(resolved),
+                    md5,
+                },
+            );
+            if is_change && &item != key {
+                changes.push(item);
+            }
+        }
+        changes
+    }
+
+    ///
+    /// 未声明启用引用的配置按原内容返回;
+    /// 展开过程中没有循环及深度截断的结果与引用路径无关，记录到memo中复用
+    fn resolve_content<F>(key: &ConfigKey, content: &str, ctx: &mut ResolveContext<F>) -> String
+    where
+        F: Fn(&ConfigKey) -> Option<Arc<String>>,
+    {
+        let content = match Self::strip_directive(content) {
+            Some(v) => v,
+            None => return content.to_owned(),
+        };
+        let mut output = String::with_capacity(content.len());
+        let mut last = 0;
+        for cap in INCLUDE_REGEX.captures_iter(content) {
+            let m = cap.get(0).unwrap();
+            output.push_str(&content[last..m.start()]);
+            last = m.end();
+            let ref_key = Self::build_ref_key(key, &cap[1]);
+            if ctx.stack.contains(&ref_key) {
+                ctx.has_cycle = true;
+                output.push_str(m.as_str());
+                continue;
+            }
+            if let Some(v) = ctx.memo.get(&ref_key) {
+                output.push_str(v);
+                continue;
+            }
+            match (ctx.lookup)(&ref_key) {
+                Some(ref_content) if ctx.stack.len() < MAX_INCLUDE_DEPTH => {
+                    let (has_cycle, truncated) = (ctx.has_cycle, ctx.truncated);
+                    ctx.has_cycle = false;
+                    ctx.truncated = false;
+                    ctx.stack.push(ref_key.clone());
+                    let v = Self::resolve_content(&ref_key, &ref_content, ctx);
+                    ctx.stack.pop();
+                    if !ctx.has_cycle && !ctx.truncated {
+                        ctx.memo.insert(ref_key, Arc::new(v.clone()));
+                    }
+                    ctx.has_cycle |= has_cycle;
+                    ctx.truncated |= truncated;
+                    output.push_str(&v);
+                }
+                Some(_) => {
+                    ctx.truncated = true;
+                    output.push_str(m.as_str());
+                }
+                None => output.push_str(m.as_str()),
+            }
+        }
+        output.push_str(&content[last..]);
+        output
+    }
+
+    ///
+    /// 查询引用关系图，指定配置时返回与其直接或间接关联的配置，否则返回命名空间下所有引用关系
+    pub fn query_graph<F>(
+        &self,
+        tenant: &Arc<String>,
+        root: Option<&ConfigKey>,
+        exist: F,
+    ) -> ConfigIncludeGraph
+    where
+        F: Fn(&ConfigKey) -> bool,
+    {
+        let mut keys: Vec<ConfigKey> = vec![];
+        let mut visited: HashSet<ConfigKey> = HashSet::new();
+        match root {
+            Some(root) => {
+                let mut queue = VecDeque::new();
+                visited.insert(root.clone());
+                queue.push_back(root.clone());
+                while let Some(item) = queue.pop_front() {
+                    let refs = self.refs.get(&item).into_iter().flatten();
+                    let dependents = self.dependents.get(&item).into_iter().flatten();
+                    for next in refs.chain(dependents) {
+                        if visited.insert(next.clone()) {
+                            queue.push_back(next.clone());
+                        }
+                    }
+                    keys.push(item);
+                }
+            }
+            None => {
+                for (key, refs) in &self.refs {
+                    if &key.tenant != tenant {
+                        continue;
+                    }
+                    for item in std::iter::once(key).chain(refs.iter()) {
+                        if visited.insert(item.clone()) {
+                            keys.push(item.clone());
+                        }
+                    }
+                }
+            }
+        }
+        let mut graph = ConfigIncludeGraph {
+            tenant: tenant.clone(),
+            ..Default::default()
+        };
+        for key in &keys {
+            graph.nodes.push(ConfigIncludeNode {
+                id: ConfigIncludeNode::build_id(key),
+                group: key.group.clone(),
+                data_id: key.data_id.clone(),
+                exist: exist(key),
+                cycle: self.cycles.contains(key),
+            });
+            if let Some(refs) = self.refs.get(key) {
+                for ref_key in refs {
+                    graph.edges.push(ConfigIncludeEdge {
+                        from: ConfigIncludeNode::build_id(key),
+                        to: ConfigIncludeNode::build_id(ref_key),
+                    });
+                }
+            }
+        }
+        graph
+    }
+}
+
+struct ResolveContext<'a, F> {
+    lookup: &'a F,
+    stack: Vec<ConfigKey>,
+    memo: &'a mut HashMap<ConfigKey, Arc<String>>,
+    has_cycle: bool,
+    truncated: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigIncludeNode {
+    ///group/dataId
+    pub id: String,
+    pub group: Arc<String>,
+    pub data_id: Arc<String>,
+    pub exist: bool,
+    pub cycle: bool,
+}
+
+impl ConfigIncludeNode {
+    fn build_id(key: &ConfigKey) -> String {
+        format!("{}/{}", &key.group, &key.data_id)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigIncludeEdge {
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigIncludeGraph {
+    pub tenant: Arc<String>,
+    pub nodes: Vec<ConfigIncludeNode>,
+    pub edges: Vec<ConfigIncludeEdge>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ConfigIncludeGraphParam {
+    pub tenant: Arc<String>,
+    pub key: Option<ConfigKey>,
+}
+
+#[test]
+fn resolve_config_include() {
+    let app = ConfigKey::new("app.yaml", "DEFAULT_GROUP", "");
+    let common = ConfigKey::new("common.yaml", "SHARED", "");
+    let db = ConfigKey::new("db.yaml", "SHARED", "");
+    let mut data: HashMap<ConfigKey, Arc<String>> = HashMap::new();
+    data.insert(
+        app.clone(),
+        Arc::new("#rnacos:include\nname: app\n${include:SHARED/common.yaml}".to_owned()),
+    );
+    data.insert(
+        common.clone(),
+        Arc::new("#rnacos:include\nlog: info\n${include:db.yaml}".to_owned()),
+    );
+    data.insert(db.clone(), Arc::new("db: mysql\n".to_owned()));
+    let mut index = ConfigIncludeIndex::new();
+    for (key, content) in &data {
+        index.update_refs(key, Some(content.as_str()));
+    }
+    for key in data.keys() {
+        index.refresh(key, |k| data.get(k).cloned());
+    }
+    let resolved = index.get_resolved(&app).unwrap();
+    assert_eq!(
+        resolved.content.as_str(),
+        "name: app\nlog: info\ndb: mysql\n"
+    );
+    assert_eq!(
+        resolved.md5.as_str(),
+        get_md5("name: app\nlog: info\ndb: mysql\n")
+    );
+    assert!(index.get_resolved(&db).is_none());
+
+    //被引用的配置变更时，返回展开结果变化的引用方
+    data.insert(db.clone(), Arc::new("db: pg\n".to_owned()));
+    let mut changes = index.refresh(&db, |k| data.get(k).cloned());
+    changes.sort_by_key(|e| e.data_id.clone());
+    assert_eq!(changes, vec![app.clone(), common.clone()]);
+
+    //未声明启用引用的配置不展开
+    let plain = ConfigKey::new("plain.yaml", "DEFAULT_GROUP", "");
+    data.insert(
+        plain.clone(),
+        Arc::new("${include:SHARED/db.yaml}".to_owned()),
+    );
+    index.update_refs(&plain, data.get(&plain).map(|e| e.as_str()));
+    index.refresh(&plain, |k| data.get(k).cloned());
+    assert!(index.get_resolved(&plain).is_none());
+    data.remove(&plain);
+    index.update_refs(&plain, None);
+
+    //发布前检查循环引用
+    let cycle_content = "#rnacos:include\n${include:DEFAULT_GROUP/app.yaml}";
+    let path = index.find_cycle(&db, cycle_content).unwrap();
+    assert_eq!(
+        path,
+        vec![db.clone(), app.clone(), common.clone(), db.clone()]
+    );
+    assert!(index
+        .find_cycle(&db, "${include:DEFAULT_GROUP/app.yaml}")
+        .is_none());
+
+    //循环引用保留引用原文
+    data.insert(db.clone(), Arc::new(cycle_content.to_owned()));
+    index.update_refs(&db, data.get(&db).map(|e| e.as_str()));
+    index.refresh(&db, |k| data.get(k).cloned());
+    let graph = index.query_graph(&Arc::new("".to_owned()), Some(&db), |k| {
+        data.contains_key(k)
+    });
+    assert_eq!(graph.nodes.len(), 3);
+    assert_eq!(graph.edges.len(), 3);
+    assert!(graph.nodes.iter().all(|e| e.cycle));
+    assert!(index
+        .get_resolved(&app)
+        .unwrap()
+        .content
+        .ends_with("${include:DEFAULT_GROUP/app.yaml}"));
+
+    //菱形引用: top引用left、right，二者都引用base
+    let mut data: HashMap<ConfigKey, Arc<String>> = HashMap::new();
+    let top = ConfigKey::new("top", "G", "");
+    for (key, content) in [
+        (
+            top.clone(),
+            "#rnacos:include\n${include:left}|${include:right}",
+        ),
+        (
+            ConfigKey::new("left", "G", ""),
+            "#rnacos:include\nL${include:base}",
+        ),
+        (
+            ConfigKey::new("right", "G", ""),
+            "#rnacos:include\nR${include:base}",
+        ),
+        (ConfigKey::new("base", "G", ""), "B"),
+    ] {
+        data.insert(key, Arc::new(content.to_owned()));
+    }
+    let mut index = ConfigIncludeIndex::new();
+    for (key, content) in &data {
+        index.update_refs(key, Some(content.as_str()));
+    }
+    index.refresh(&top, |k| data.get(k).cloned());
+    assert_eq!(index.get_resolved(&top).unwrap().content.as_str(), "LB|RB");
+}
@@ -1,5 +1,5 @@
 use std::{
-    collections::{BTreeMap, BTreeSet},
+    collections::{BTreeMap, BTreeSet, HashMap},
     sync::Arc,
 };
 
@@ -14,6 +14,9 @@ pub struct ConfigQueryParam {
     pub like_group: Option<String>,
     pub like_data_id: Option<String>,
     pub query_context: bool,
+    pub app_name: Option<Arc<String>>,
+    ///命中任意一个标签即可
+    pub config_tags: Vec<Arc<String>>,
     pub offset: usize,
     pub limit: usize,
 }
@@ -37,11 +40,66 @@ impl ConfigQueryParam {
             true
         }
     }
+
+    pub fn match_meta(&self, meta: Option<&ConfigIndexMeta>) -> bool {
+        if self.app_name.is_none() && self.config_tags.is_empty() {
+            return true;
+        }
+        let meta = if let Some(meta) = meta {
+            meta
+        } else {
+            return false;
+        };
+        if let Some(app_name) = &self.app_name {
+            if !app_name.is_empty() && meta.app_name.as_ref() != Some(app_name) {
+                return false;
+            }
+        }
+        if !self.config_tags.is_empty() {
+            return self.config_tags.iter().any(|e| meta.tags.contains(e));
+        }
+        true
+    }
+}
+
+///
+/// 配置的查询元数据，用于按应用、标签过滤
+#[derive(Debug, Clone, Default)]
+pub struct ConfigIndexMeta {
+    pub app_name: Option<Arc<String>>,
+    pub tags: Vec<Arc<String>>,
+}
+
+impl ConfigIndexMeta {
+    pub fn new(app_name: Option<Arc<String>>, config_tags: Option<&Arc<String>>) -> Self {
+        let tags = if let Some(config_tags) = config_tags {
+            Self::split_tags(config_tags)
+        } else {
+            vec![]
+        };
+        Self { app_name, tags }
+    }
+
+    pub fn split_tags(config_tags: &str) -> Vec<Arc<String>> {
+        let mut tags: Vec<Arc<String>> = vec![];
+        for item in config_tags.split(',') {
+            let item = item.trim();
+            if !item.is_empty() && !tags.iter().any(|e| e.as_str() == item) {
+                tags.push(Arc::new(item.to_owned()));
+            }
+        }
+        tags
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.app_name.is_none() && self.tags.is_empty()
+    }
 }
 
 #[derive(Debug, Clone, Default)]
 pub struct ConfigIndex {
     pub(crate) group_data: BTreeMap<Arc<String>, BTreeSet<Arc<String>>>,
+    pub(crate) meta_data: HashMap<(Arc<String>, Arc<String>), ConfigIndexMeta>,
     pub(crate) data_size: usize,
 }
 
@@ -75,6 +133,7 @@ impl ConfigIndex {
             let b = set.remove(config);
             if b {
                 self.data_size -= 1;
+                self.meta_data.remove(&(group.clone(), config.clone()));
                 if set.is_empty() {
                     self.group_data.remove(group);
                 }
@@ -86,6 +145,19 @@ impl ConfigIndex {
         (b, self.group_data.len())
     }
 
+    pub(crate) fn update_meta(
+        &mut self,
+        group: Arc<String>,
+        config: Arc<String>,
+        meta: ConfigIndexMeta,
+    ) {
+        if meta.is_empty() {
+            self.meta_data.remove(&(group, config));
+        } else {
+            self.meta_data.insert((group, config), meta);
+        }
+    }
+
     pub(crate) fn query_config_page(
         &self,
         tenant: &Arc<String>,
@@ -98,7 +170,9 @@ impl ConfigIndex {
         for (g, set) in &self.group_data {
             if param.match_group(g) {
                 for s in set {
-                    if param.match_data_id(s) {
+                    if param.match_data_id(s)
+                        && param.match_meta(self.meta_data.get(&(g.clone(), s.clone())))
+                    {
                         if index >= param.offset && index < end_index {
                             let key = ConfigKey::new_by_arc(s.clone(), g.clone(), tenant.clone());
                             rlist.push(key);
@@ -174,6 +248,14 @@ impl TenantIndex {
         result
     }
 
+    ///
+    /// 更新配置的应用、标签元数据，配置需要已在索引中
+    pub fn update_config_meta(&mut self, key: &ConfigKey, meta: ConfigIndexMeta) {
+        if let Some(config_index) = self.tenant_group.get_mut(&key.tenant) {
+            config_index.update_meta(key.group.clone(), key.data_id.clone(), meta);
+        }
+    }
+
     pub fn query_config_page(&self, param: &ConfigQueryParam) -> (usize, Vec<ConfigKey>) {
         let mut rlist = vec![];
         let mut size = 0;
@@ -260,3 +342,47 @@ fn query_service() {
     assert!(size == 0);
     assert!(list.is_empty());
 }
+
+#[test]
+fn query_by_meta() {
+    let mut index = TenantIndex::new();
+    let key1 = ConfigKey::new("1", "1", "1");
+    let key2 = ConfigKey::new("2", "1", "1");
+    let key3 = ConfigKey::new("3", "1", "1");
+    index.insert_config(key1.clone());
+    index.insert_config(key2.clone());
+    index.insert_config(key3.clone());
+    let app = Arc::new("app1".to_owned());
+    index.update_config_meta(
+        &key1,
+        ConfigIndexMeta::new(Some(app.clone()), Some(&Arc::new("a, b,a".to_owned()))),
+    );
+    index.update_config_meta(
+        &key2,
+        ConfigIndexMeta::new(None, Some(&Arc::new("b,c".to_owned()))),
+    );
+
+    let mut param = ConfigQueryParam {
+        tenant: Some(Arc::new("1".to_owned())),
+        limit: 0xffff_ffff,
+        app_name: Some(app),
+        ..ConfigQueryParam::default()
+    };
+    let (size, list) = index.query_config_page(&param);
+    assert!(size == 1);
+    assert!(list[0] == key1);
+
+    param.app_name = None;
+    param.config_tags = ConfigIndexMeta::split_tags("b");
+    let (size, _) = index.query_config_page(&param);
+    assert!(size == 2);
+
+    param.config_tags = ConfigIndexMeta::split_tags("c,d");
+    let (size, list) = index.query_config_page(&param);
+    assert!(size == 1);
+    assert!(list[0] == key2);
+
+    index.remove_config(&key2);
+    let (size, _) = index.query_config_page(&param);
+    assert!(size == 0);
+}
@@ -0,0 +1,317 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use super::core::ConfigKey;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum ConfigPushStatus {
+    ///已推送，等待客户端响应
+    Pending,
+    Acked,
+    ///推送失败或客户端返回错误
+    Failed,
+    Timeout,
+}
+
+impl ConfigPushStatus {
+    pub fn new_by_value(value: &str) -> anyhow::Result<Self> {
+        match value.to_uppercase().as_str() {
+            "PENDING" => Ok(Self::Pending),
+            "ACKED" => Ok(Self::Acked),
+            "FAILED" => Ok(Self::Failed),
+            "TIMEOUT" => Ok(Self::Timeout),
+            _ => Err(anyhow::anyhow!("invalid push status : {}", value)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigPushClientTrace {
+    pub conn_id: Arc<String>,
+    pub status: ConfigPushStatus,
+    pub ack_time: Option<i64>,
+    pub message: Option<String>,
+}
+
+///
+/// 一次配置变更通知的推送记录
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigPushTraceDto {
+    pub id: u64,
+    pub tenant: Arc<String>,
+    pub group: Arc<String>,
+    pub data_id: Arc<String>,
+    pub request_id: String,
+    pub push_time: i64,
+    #[serde(default)]
+    pub node_id: u64,
+    pub clients: Vec<ConfigPushClientTrace>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigPushTraceQueryParam {
+    ///build_key格式的配置key
+    pub key: Option<String>,
+    pub conn_id: Option<Arc<String>>,
+    pub status: Option<ConfigPushStatus>,
+    pub offset: usize,
+    pub limit: usize,
+}
+
+impl ConfigPushTraceQueryParam {
+    fn match_trace(&self, trace: &ConfigPushTraceDto) -> bool {
+        if let Some(key) = &self.key {
+            let trace_key = ConfigKey::new_by_arc(
+                trace.data_id.clone(),
+                trace.group.clone(),
+                trace.tenant.clone(),
+            );
+            if &trace_key.build_key() != key {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn match_client(&self, client: &ConfigPushClientTrace) -> bool {
+        if let Some(conn_id) = &self.conn_id {
+            if conn_id != &client.conn_id {
+                return false;
+            }
+        }
+        match self.status {
+            Some(status) => status == client.status,
+            None => true,
+        }
+    }
+}
+
+///
+/// 有界的配置推送记录，超出容量时淘汰最早的记录
+#[derive(Default)]
+pub struct ConfigPushTraceStore {
+    traces: VecDeque<ConfigPushTraceDto>,
+    request_index: HashMap<String, u64>,
+    ///等待响应的记录id，按推送时间有序
+    pending: VecDeque<u64>,
+    capacity: usize,
+    next_id: u64,
+}
+
+impl ConfigPushTraceStore {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            traces: VecDeque::new(),
+            request_index: HashMap::new(),
+            pending: VecDeque::new(),
+            capacity,
+            next_id: 1,
+        }
+    }
+
+    fn get_mut(&mut self, id: u64) -> Option<&mut ConfigPushTraceDto> {
+        let first_id = self.traces.front()?.id;
+        if id < first_id {
+            return None;
+        }
+        self.traces.get_mut((id - first_id) as usize)
+    }
+
+    ///
+    /// 记录一次推送，clients为(连接id,连接是否存在)
+    pub fn add_push(
+        &mut self,
+        key: &ConfigKey,
+        request_id: String,
+        clients: Vec<(Arc<String>, bool)>,
+        now: i64,
+    ) {
+        if self.capacity == 0 {
+            return;
+        }
+        let id = self.next_id;
+        self.next_id += 1;
+        let mut has_pending = false;
+        let clients = clients
+            .into_iter()
+            .map(|(conn_id, exist)| {
+                has_pending |= exist;
+                ConfigPushClientTrace {
+                    conn_id,
+                    status: if exist {
+                        ConfigPushStatus::Pending
+                    } else {
+                        ConfigPushStatus::Failed
+                    },
+                    ack_time: None,
+                    message: if exist {
+                        None
+                    } else {
+                        Some("connection not found".to_owned())
+                    },
+                }
+            })
+            .collect();
+        self.request_index.insert(request_id.clone(), id);
+        if has_pending {
+            self.pending.push_back(id);
+        }
+        self.traces.push_back(ConfigPushTraceDto {
+            id,
+            tenant: key.tenant.clone(),
+            group: key.group.clone(),
+            data_id: key.data_id.clone(),
+            request_id,
+            push_time: now,
+            node_id: 0,
+            clients,
+        });
+        while self.traces.len() > self.capacity {
+            if let Some(trace) = self.traces.pop_front() {
+                self.request_index.remove(&trace.request_id);
+            }
+        }
+    }
+
+    ///
+    /// 客户端响应推送请求
+    pub fn ack(
+        &mut self,
+        request_id: &str,
+        conn_id: &Arc<String>,
+        success: bool,
+        message: Option<String>,
+        now: i64,
+    ) {
+        let id = match self.request_index.get(request_id) {
+            Some(v) => *v,
+            None => return,
+        };
+        if let Some(trace) = self.get_mut(id) {
+            for client in trace.clients.iter_mut() {
+                if &client.conn_id == conn_id && client.status == ConfigPushStatus::Pending {
+                    client.ack_time = Some(now);
+                    if success {
+                        client.status = ConfigPushStatus::Acked;
+                    } else {
+                        client.status = ConfigPushStatus::Failed;
+                        client.message = message;
+                    }
+                    break;
+                }
+            }
+        }
+    }
+
+    ///
+    /// 超过响应时间仍未响应的推送标记为超时
+    pub fn check_timeout(&mut self, now: i64, timeout: i64) {
+        while let Some(id) = self.pending.front().cloned() {
+            let is_timeout = match self.get_mut(id) {
+                Some(trace) => trace.push_time + timeout <= now,
+                None => true,
+            };
+            if !is_timeout {
+                break;
+            }
+            self.pending.pop_front();
+            if let Some(trace) = self.get_mut(id) {
+                for client in trace.clients.iter_mut() {
+                    if client.status == ConfigPushStatus::Pending {
+                        client.status = ConfigPushStatus::Timeout;
+                    }
+                }
+            }
+        }
+    }
+
+    ///
+    /// 按推送时间倒序分页查询，按连接或状态查询时只返回匹配的客户端
+    pub fn query(&self, param: &ConfigPushTraceQueryParam) -> (usize, Vec<ConfigPushTraceDto>) {
+        let mut size = 0;
+        let mut list = vec![];
+        for trace in self.traces.iter().rev() {
+            if !param.match_trace(trace) {
+                continue;
+            }
+            let filter_client = param.conn_id.is_some() || param.status.is_some();
+            if filter_client && !trace.clients.iter().any(|e| param.match_client(e)) {
+                continue;
+            }
+            if size >= param.offset && list.len() < param.limit {
+                let mut item = trace.clone();
+                if filter_client {
+                    item.clients.retain(|e| param.match_client(e));
+                }
+                list.push(item);
+            }
+            size += 1;
+        }
+        (size, list)
+    }
+}
+
+#[test]
+fn config_push_trace_store() {
+    let key = ConfigKey::new("app.yaml", "DEFAULT_GROUP", "");
+    let c1 = Arc::new("c1".to_owned());
+    let c2 = Arc::new("c2".to_owned());
+    let c3 = Arc::new("c3".to_owned());
+    let mut store = ConfigPushTraceStore::new(2);
+    store.add_push(
+        &key,
+        "1".to_owned(),
+        vec![(c1.clone(), true), (c2.clone(), true), (c3.clone(), false)],
+        1000,
+    );
+    store.ack("1", &c1, true, None, 1100);
+    store.check_timeout(3000, 3000);
+    store.check_timeout(4000, 3000);
+
+    let param = ConfigPushTraceQueryParam {
+        key: Some(key.build_key()),
+        limit: 10,
+        ..Default::default()
+    };
+    let (size, list) = store.query(&param);
+    assert_eq!(size, 1);
+    let status: Vec<ConfigPushStatus> = list[0].clients.iter().map(|e| e.status).collect();
+    assert_eq!(
+        status,
+        vec![
+            ConfigPushStatus::Acked,
+            ConfigPushStatus::Timeout,
+            ConfigPushStatus::Failed
+        ]
+    );
+    assert_eq!(list[0].clients[0].ack_time, Some(1100));
+
+    let param = ConfigPushTraceQueryParam {
+        conn_id: Some(c2.clone()),
+        limit: 10,
+        ..Default::default()
+    };
+    let (_, list) = store.query(&param);
+    assert_eq!(list[0].clients.len(), 1);
+
+    //超出容量时淘汰最早的记录，旧请求的响应被忽略
+    store.add_push(&key, "2".to_owned(), vec![(c1.clone(), true)], 5000);
+    store.add_push(&key, "3".to_owned(), vec![(c1.clone(), true)], 6000);
+    store.ack("1", &c2, true, None, 6000);
+    store.ack("3", &c1, false, Some("error".to_owned()), 6100);
+    let param = ConfigPushTraceQueryParam {
+        limit: 10,
+        ..Default::default()
+    };
+    let (size, list) = store.query(&param);
+    assert_eq!(size, 2);
+    assert_eq!(list[0].request_id, "3");
+    assert_eq!(list[0].clients[0].status, ConfigPushStatus::Failed);
+    assert_eq!(list[1].clients[0].status, ConfigPushStatus::Pending);
+}
@@ -0,0 +1,483 @@
+use std::cmp::Reverse;
+use std::sync::Arc;
+use std::time::Duration;
+
+use actix::prelude::*;
+use bean_factory::{bean, Inject};
+use serde::{Deserialize, Serialize};
+
+use crate::common::byte_utils::{bin_to_id, id_to_bin};
+use crate::common::constant::{CONFIG_HISTORY_TREE_NAME, CONFIG_RECYCLE_TREE_NAME};
+use crate::common::AppSysConfig;
+use crate::now_millis_i64;
+use crate::raft::cluster::model::{RouteAddr, SetConfigReq};
+use crate::raft::cluster::route::{ConfigRoute, RaftAddrRouter};
+use crate::raft::db::route::TableRoute;
+use crate::raft::db::table::{
+    TableManager, TableManagerQueryReq, TableManagerReq, TableManagerResult,
+};
+
+use super::config_encryption::CONFIG_ENCRYPTION;
+use super::config_history::ConfigHistoryUtils;
+use super::core::{ConfigActor, ConfigCmd, ConfigKey, ConfigResult, ConfigValue};
+use super::model::{ConfigError, CAS_MD5_NOT_EXIST};
+
+const RECYCLE_KEY_SPLIT: u8 = 3;
+
+///
+/// 检查过期回收记录的间隔
+const AUTO_PURGE_INTERVAL: Duration = Duration::from_secs(600);
+
+///
+/// 删除的配置，保存删除前的内容及元信息；历史记录仍保留在历史记录表中
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigRecycleDO {
+    pub tenant: String,
+    pub group: String,
+    pub data_id: String,
+    pub content: String,
+    pub md5: String,
+    #[serde(default)]
+    pub config_type: Option<String>,
+    #[serde(default)]
+    pub desc: Option<String>,
+    #[serde(default)]
+    pub config_tags: Option<String>,
+    #[serde(default)]
+    pub app_name: Option<String>,
+    #[serde(default)]
+    pub encrypted_data_key: Option<String>,
+    ///删除前最后一条历史记录id
+    #[serde(default)]
+    pub last_history_id: Option<u64>,
+    #[serde(default)]
+    pub op_user: Option<String>,
+    pub delete_time: i64,
+}
+
+impl ConfigRecycleDO {
+    pub fn new(
+        key: &ConfigKey,
+        value: &ConfigValue,
+        op_user: Option<Arc<String>>,
+        delete_time: i64,
+    ) -> Self {
+        Self {
+            tenant: key.tenant.as_ref().to_owned(),
+            group: key.group.as_ref().to_owned(),
+            data_id: key.data_id.as_ref().to_owned(),
+            content: value.content.as_ref().to_owned(),
+            md5: value.md5.as_ref().to_owned(),
+            config_type: value.config_type.as_ref().map(|e| e.as_ref().to_owned()),
+            desc: value.desc.as_ref().map(|e| e.as_ref().to_owned()),
+            config_tags: value.config_tags.as_ref().map(|e| e.as_ref().to_owned()),
+            app_name: value.app_name.as_ref().map(|e| e.as_ref().to_owned()),
+            encrypted_data_key: value
+                .encrypted_data_key
+                .as_ref()
+                .map(|e| e.as_ref().to_owned()),
+            last_history_id: value.last_history.as_ref().map(|e| e.id),
+            op_user: op_user.map(|e| e.as_ref().to_owned()),
+            delete_time,
+        }
+    }
+
+    pub fn to_bytes(&self) -> anyhow::Result<Vec<u8>> {
+        Ok(serde_json::to_vec(self)?)
+    }
+
+    pub fn from_bytes(v: &[u8]) -> anyhow::Result<Self> {
+        Ok(serde_json::from_slice(v)?)
+    }
+
+    pub fn get_config_key(&self) -> ConfigKey {
+        ConfigKey::new(&self.data_id, &self.group, &self.tenant)
+    }
+
+    ///
+    /// 列表中不返回内容，加密配置的内容在查询详情时解密
+    pub fn to_dto(&self, with_content: bool) -> anyhow::Result<ConfigRecycleDto> {
+        let content = if with_content {
+            let content = CONFIG_ENCRYPTION.decrypt(
+                &self.data_id,
+                self.encrypted_data_key.clone().map(Arc::new).as_ref(),
+                Arc::new(self.content.to_owned()),
+            )?;
+            Some(content.as_ref().to_owned())
+        } else {
+            None
+        };
+        Ok(ConfigRecycleDto {
+            tenant: self.tenant.clone(),
+            group: self.group.clone(),
+            data_id: self.data_id.clone(),
+            content,
+            md5: self.md5.clone(),
+            config_type: self.config_type.clone(),
+            desc: self.desc.clone(),
+            last_history_id: self.last_history_id,
+            op_user: self.op_user.clone(),
+            delete_time: self.delete_time,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigRecycleDto {
+    pub tenant: String,
+    pub group: String,
+    pub data_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    pub md5: String,
+    pub config_type: Option<String>,
+    pub desc: Option<String>,
+    pub last_history_id: Option<u64>,
+    pub op_user: Option<String>,
+    pub delete_time: i64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ConfigRecycleQueryParam {
+    pub tenant: Option<String>,
+    ///模糊匹配
+    pub group: Option<String>,
+    ///模糊匹配
+    pub data_id: Option<String>,
+    pub offset: usize,
+    pub limit: usize,
+}
+
+impl ConfigRecycleQueryParam {
+    fn is_match(&self, item: &ConfigRecycleDO) -> bool {
+        if let Some(tenant) = &self.tenant {
+            if tenant != &item.tenant {
+                return false;
+            }
+        }
+        if let Some(group) = &self.group {
+            if !group.is_empty() && !item.group.contains(group.as_str()) {
+                return false;
+            }
+        }
+        if let Some(data_id) = &self.data_id {
+            if !data_id.is_empty() && !item.data_id.contains(data_id.as_str()) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+pub struct ConfigRecycleUtils;
+
+impl ConfigRecycleUtils {
+    ///
+    /// 回收记录key: 配置key + \x03 + 8字节删除时间(大端)，同一配置可以有多条删除记录
+    pub fn build_key_prefix(key: &ConfigKey) -> Vec<u8> {
+        let mut v = key.build_key().into_bytes();
+        v.push(RECYCLE_KEY_SPLIT);
+        v
+    }
+
+    pub fn build_key(key: &ConfigKey, delete_time: i64) -> Vec<u8> {
+        let mut v = Self::build_key_prefix(key);
+        v.extend(id_to_bin(delete_time as u64));
+        v
+    }
+
+    pub fn split_key(key: &[u8]) -> Option<(&[u8], i64)> {
+        if key.len() < 9 {
+            return None;
+        }
+        let (prefix, time) = key.split_at(key.len() - 8);
+        Some((prefix, bin_to_id(time) as i64))
+    }
+
+    async fn query_all(table_manager: &Addr<TableManager>) -> anyhow::Result<Vec<ConfigRecycleDO>> {
+        let req = TableManagerQueryReq::QueryPageList {
+            table_name: CONFIG_RECYCLE_TREE_NAME.clone(),
+            like_key: None,
+            offset: None,
+            limit: None,
+            is_rev: false,
+        };
+        match table_manager.send(req).await?? {
+            TableManagerResult::PageListResult(_, list) => {
+                let mut ret = Vec::with_capacity(list.len());
+                for (_, v) in list {
+                    ret.push(ConfigRecycleDO::from_bytes(&v)?);
+                }
+                Ok(ret)
+            }
+            _ => Ok(vec![]),
+        }
+    }
+
+    ///
+    /// 按删除时间倒序分页查询
+    pub async fn query_page(
+        table_manager: &Addr<TableManager>,
+        param: &ConfigRecycleQueryParam,
+    ) -> anyhow::Result<(usize, Vec<ConfigRecycleDto>)> {
+        let mut list: Vec<ConfigRecycleDO> = Self::query_all(table_manager)
+            .await?
+            .into_iter()
+            .filter(|e| param.is_match(e))
+            .collect();
+        list.sort_by_key(|e| Reverse(e.delete_time));
+        let size = list.len();
+        let mut ret = Vec::with_capacity(param.limit.min(size));
+        for item in list.iter().skip(param.offset).take(param.limit) {
+            ret.push(item.to_dto(false)?);
+        }
+        Ok((size, ret))
+    }
+
+    pub async fn get(
+        table_manager: &Addr<TableManager>,
+        key: &ConfigKey,
+        delete_time: i64,
+    ) -> anyhow::Result<Option<ConfigRecycleDO>> {
+        let req = TableManagerQueryReq::GetByBytes {
+            table_name: CONFIG_RECYCLE_TREE_NAME.clone(),
+            key: Self::build_key(key, delete_time),
+        };
+        match table_manager.send(req).await?? {
+            TableManagerResult::Value(v) => Ok(Some(ConfigRecycleDO::from_bytes(&v)?)),
+            _ => Ok(None),
+        }
+    }
+
+    ///
+    /// 恢复配置，配置已重新创建时不允许恢复；恢复的内容按原样写入，不重复加密
+    pub async fn restore(
+        table_manager: &Addr<TableManager>,
+        table_route: &Arc<TableRoute>,
+        config_route: &Arc<ConfigRoute>,
+        config_addr: &Addr<ConfigActor>,
+        key: &ConfigKey,
+        delete_time: i64,
+        op_user: Option<Arc<String>>,
+    ) -> anyhow::Result<()> {
+        let item = Self::get(table_manager, key, delete_time)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("recycle config not exist"))?;
+        if Self::config_exist(config_addr, key).await? {
+            return Err(anyhow::anyhow!("config is exist, can't restore"));
+        }
+        let mut req = SetConfigReq::new(key.clone(), Arc::new(item.content));
+        //检查后到写入前配置可能被重新创建，写入时要求配置仍不存在
+        req.cas_md5 = Some(Arc::new(CAS_MD5_NOT_EXIST.to_owned()));
+        req.op_user = op_user;
+        req.config_type = item.config_type.map(Arc::new);
+        req.desc = item.desc.map(Arc::new);
+        req.config_tags = item.config_tags.map(Arc::new);
+        req.app_name = item.app_name.map(Arc::new);
+        req.encrypted_data_key = item.encrypted_data_key.map(Arc::new);
+        if let Err(err) = config_route.set_config(req).await {
+            if ConfigError::is_cas_conflict(&err) {
+                return Err(anyhow::anyhow!("config is exist, can't restore"));
+            }
+            return Err(err);
+        }
+        Self::remove_record(table_route, key, delete_time).await
+    }
+
+    ///
+    /// 彻底删除回收记录；配置未重新创建且没有其它回收记录时同时删除历史记录
+    pub async fn purge(
+        table_manager: &Addr<TableManager>,
+        table_route: &Arc<TableRoute>,
+        config_addr: &Addr<ConfigActor>,
+        key: &ConfigKey,
+        delete_time: i64,
+    ) -> anyhow::Result<()> {
+        Self::remove_record(table_route, key, delete_time).await?;
+        if Self::config_exist(config_addr, key).await? {
+            return Ok(());
+        }
+        let req = TableManagerQueryReq::QueryPrefixPageList {
+            table_name: CONFIG_RECYCLE_TREE_NAME.clone(),
+            prefix: Self::build_key_prefix(key),
+            offset: None,
+            limit: Some(1),
+            is_rev: false,
+        };
+        if let TableManagerResult::PageListResult(size, _) = table_manager.send(req).await?? {
+            if size > 0 {
+                return Ok(());
+            }
+        }
+        let req = TableManagerQueryReq::QueryPrefixPageList {
+            table_name: CONFIG_HISTORY_TREE_NAME.clone(),
+            prefix: ConfigHistoryUtils::build_key_prefix(key),
+            offset: None,
+            limit: None,
+            is_rev: false,
+        };
+        if let TableManagerResult::PageListResult(_, list) = table_manager.send(req).await?? {
+            for (k, _) in list {
+                let req = TableManagerReq::Remove {
+                    table_name: CONFIG_HISTORY_TREE_NAME.clone(),
+                    key: k,
+                };
+                table_route.request(req).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn remove_record(
+        table_route: &Arc<TableRoute>,
+        key: &ConfigKey,
+        delete_time: i64,
+    ) -> anyhow::Result<()> {
+        let req = TableManagerReq::Remove {
+            table_name: CONFIG_RECYCLE_TREE_NAME.clone(),
+            key: Self::build_key(key, delete_time),
+        };
+        table_route.request(req).await?;
+        Ok(())
+    }
+
+    async fn config_exist(
+        config_addr: &Addr<ConfigActor>,
+        key: &ConfigKey,
+    ) -> anyhow::Result<bool> {
+        match config_addr.send(ConfigCmd::GET(key.clone())).await?? {
+            ConfigResult::DATA(_, _) => Ok(true),
+            _ => Ok(false),
+        }
+    }
+}
+
+///
+/// 定时清理超过保留时间的回收记录，只由主节点执行
+#[bean(inject)]
+pub struct ConfigRecycleManager {
+    raft_addr_route: Option<Arc<RaftAddrRouter>>,
+    raft_table_route: Option<Arc<TableRoute>>,
+    table_manager: Option<Addr<TableManager>>,
+    config_addr: Option<Addr<ConfigActor>>,
+    ///保留时间(毫秒)，0表示不自动清理
+    retention: i64,
+}
+
+impl ConfigRecycleManager {
+    pub fn new(sys_config: &AppSysConfig) -> Self {
+        Self {
+            raft_addr_route: None,
+            raft_table_route: None,
+            table_manager: None,
+            config_addr: None,
+            retention: sys_config.config_recycle_retention_days as i64 * 24 * 3600 * 1000,
+        }
+    }
+
+    fn auto_purge_timer(&self, ctx: &mut Context<Self>) {
+        ctx.run_later(AUTO_PURGE_INTERVAL, |act, ctx| {
+            act.auto_purge(ctx);
+            act.auto_purge_timer(ctx);
+        });
+    }
+
+    fn auto_purge(&self, ctx: &mut Context<Self>) {
+        let (raft_addr_route, table_route, table_manager, config_addr) = match (
+            &self.raft_addr_route,
+            &self.raft_table_route,
+            &self.table_manager,
+            &self.config_addr,
+        ) {
+            (Some(a), Some(b), Some(c), Some(d)) => (a.clone(), b.clone(), c.clone(), d.clone()),
+            _ => return,
+        };
+        let retention = self.retention;
+        async move {
+            if !matches!(raft_addr_route.get_route_addr().await?, RouteAddr::Local) {
+                return Ok(());
+            }
+            let expire_time = now_millis_i64() - retention;
+            let mut count = 0;
+            for item in ConfigRecycleUtils::query_all(&table_manager).await? {
+                if item.delete_time > expire_time {
+                    continue;
+                }
+                let key = item.get_config_key();
+                ConfigRecycleUtils::purge(
+                    &table_manager,
+                    &table_route,
+                    &config_addr,
+                    &key,
+                    item.delete_time,
+                )
+                .await?;
+                count += 1;
+            }
+            if count > 0 {
+                log::info!("auto purge config recycle, remove count:{}", count);
+            }
+            Ok(())
+        }
+        .into_actor(self)
+        .map(|r: anyhow::Result<()>, _act, _ctx| {
+            if let Err(err) = r {
+                log::warn!("auto purge config recycle error,{}", err);
+            }
+        })
+        .spawn(ctx);
+    }
+}
+
+impl Actor for ConfigRecycleManager {
+    type Context = Context<Self>;
+
+    fn started(&mut self, _ctx: &mut Self::Context) {
+        log::info!("ConfigRecycleManager started")
+    }
+}
+
+impl Inject for ConfigRecycleManager {
+    type Context = Context<Self>;
+
+    fn inject(
+        &mut self,
+        factory_data: bean_factory::FactoryData,
+        _factory: bean_factory::BeanFactory,
+        ctx: &mut Self::Context,
+    ) {
+        self.raft_addr_route = factory_data.get_bean();
+        self.raft_table_route = factory_data.get_bean();
+        self.table_manager = factory_data.get_actor();
+        self.config_addr = factory_data.get_actor();
+        if self.retention > 0 {
+            self.auto_purge_timer(ctx);
+        }
+    }
+}
+
+#[test]
+fn config_recycle_key() {
+    let key = ConfigKey::new("app.yaml", "DEFAULT_GROUP", "dev");
+    let recycle_key = ConfigRecycleUtils::build_key(&key, 1700000000000);
+    let (prefix, delete_time) = ConfigRecycleUtils::split_key(&recycle_key).unwrap();
+    assert_eq!(prefix, &ConfigRecycleUtils::build_key_prefix(&key)[..]);
+    assert_eq!(delete_time, 1700000000000);
+
+    let value = ConfigValue::new(Arc::new("a=1".to_owned()));
+    let item = ConfigRecycleDO::new(&key, &value, Some(Arc::new("admin".to_owned())), 1);
+    let item = ConfigRecycleDO::from_bytes(&item.to_bytes().unwrap()).unwrap();
+    assert_eq!(item.get_config_key(), key);
+    let param = ConfigRecycleQueryParam {
+        tenant: Some("dev".to_owned()),
+        data_id: Some("app".to_owned()),
+        limit: 10,
+        ..Default::default()
+    };
+    assert!(param.is_match(&item));
+    let dto = item.to_dto(true).unwrap();
+    assert_eq!(dto.content.as_deref(), Some("a=1"));
+    assert_eq!(dto.op_user.as_deref(), Some("admin"));
+}
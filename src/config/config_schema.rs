@@ -0,0 +1,412 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use actix::Addr;
+use serde::{Deserialize, Serialize};
+
+use crate::common::constant::CONFIG_SCHEMA_TREE_NAME;
+use crate::now_millis_i64;
+use crate::raft::db::route::TableRoute;
+use crate::raft::db::table::{
+    TableManager, TableManagerQueryReq, TableManagerReq, TableManagerResult,
+};
+
+use super::config_type::ConfigType;
+use super::config_webhook::wildcard_match;
+use super::core::ConfigKey;
+use super::model::ConfigError;
+
+lazy_static::lazy_static! {
+    static ref SCHEMA_VALIDATOR_CACHE: ConfigSchemaValidatorCache = ConfigSchemaValidatorCache::default();
+}
+
+///
+/// 配置内容的JSON Schema约束，绑定到命名空间+分组或dataId匹配规则，存储在raft表中
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigSchemaDO {
+    pub name: String,
+    ///为空时匹配所有命名空间
+    #[serde(default)]
+    pub tenant: Option<String>,
+    ///为空时匹配所有分组
+    #[serde(default)]
+    pub group: Option<String>,
+    ///dataId匹配规则，支持*通配符，为空时匹配所有dataId
+    #[serde(default)]
+    pub data_id_pattern: Option<String>,
+    ///JSON Schema内容
+    pub schema: String,
+    #[serde(default)]
+    pub desc: Option<String>,
+    #[serde(default)]
+    pub enable: bool,
+    #[serde(default)]
+    pub gmt_modified: i64,
+}
+
+impl ConfigSchemaDO {
+    pub fn to_bytes(&self) -> anyhow::Result<Vec<u8>> {
+        Ok(serde_json::to_vec(self)?)
+    }
+
+    pub fn from_bytes(v: &[u8]) -> anyhow::Result<Self> {
+        Ok(serde_json::from_slice(v)?)
+    }
+
+    pub fn check(&self) -> anyhow::Result<()> {
+        if self.name.is_empty() {
+            return Err(anyhow::anyhow!("schema name can't empty"));
+        }
+        let has_group = self.group.as_ref().map(|e| !e.is_empty()).unwrap_or(false);
+        let has_pattern = self
+            .data_id_pattern
+            .as_ref()
+            .map(|e| !e.is_empty())
+            .unwrap_or(false);
+        if !has_group && !has_pattern {
+            return Err(anyhow::anyhow!("group and dataIdPattern can't both empty"));
+        }
+        ConfigSchemaValidator::compile(&self.schema)?;
+        Ok(())
+    }
+
+    pub fn is_match(&self, key: &ConfigKey) -> bool {
+        if !self.enable {
+            return false;
+        }
+        if let Some(tenant) = &self.tenant {
+            if !tenant.is_empty() && tenant != key.tenant.as_str() {
+                return false;
+            }
+        }
+        if let Some(group) = &self.group {
+            if !group.is_empty() && group != key.group.as_str() {
+                return false;
+            }
+        }
+        match &self.data_id_pattern {
+            Some(pattern) if !pattern.is_empty() => wildcard_match(pattern, &key.data_id),
+            _ => true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigSchemaError {
+    ///不匹配的内容路径(JSON Pointer)，多文档yaml以文档序号开头
+    pub path: String,
+    pub message: String,
+    #[serde(default)]
+    pub schema_name: String,
+}
+
+impl ConfigSchemaError {
+    pub fn to_message(&self) -> String {
+        format!("[{}] {}: {}", &self.schema_name, &self.path, &self.message)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigSchemaValidateResult {
+    pub valid: bool,
+    pub errors: Vec<ConfigSchemaError>,
+}
+
+pub struct ConfigSchemaValidator {
+    schema: jsonschema::JSONSchema,
+}
+
+impl ConfigSchemaValidator {
+    pub fn compile(schema: &str) -> anyhow::Result<Self> {
+        let value: serde_json::Value = serde_json::from_str(schema)
+            .map_err(|e| anyhow::anyhow!("schema is not valid json, {}", e))?;
+        let schema = jsonschema::JSONSchema::compile(&value)
+            .map_err(|e| anyhow::anyhow!("schema is not valid, {}", e))?;
+        Ok(Self { schema })
+    }
+
+    ///
+    /// 只校验json及yaml内容，其它类型不做校验
+    pub fn parse_documents(
+        config_type: ConfigType,
+        content: &str,
+    ) -> anyhow::Result<Vec<serde_json::Value>> {
+        match config_type {
+            ConfigType::Json => Ok(vec![serde_json::from_str(content)?]),
+            ConfigType::Yaml => {
+                let mut documents = vec![];
+                for document in serde_yaml::Deserializer::from_str(content) {
+                    documents.push(serde_json::Value::deserialize(document)?);
+                }
+                Ok(documents)
+            }
+            _ => Ok(vec![]),
+        }
+    }
+
+    pub fn validate(&self, documents: &[serde_json::Value]) -> Vec<ConfigSchemaError> {
+        let mut errors = vec![];
+        for (i, document) in documents.iter().enumerate() {
+            if let Err(iter) = self.schema.validate(document) {
+                for err in iter {
+                    let mut path = err.instance_path.to_string();
+                    if documents.len() > 1 {
+                        path = format!("/{}{}", i, path);
+                    }
+                    if path.is_empty() {
+                        path = "/".to_owned();
+                    }
+                    errors.push(ConfigSchemaError {
+                        path,
+                        message: err.to_string(),
+                        schema_name: String::new(),
+                    });
+                }
+            }
+        }
+        errors
+    }
+}
+
+///
+/// 编译后的schema按名称缓存，schema内容变更后重新编译
+#[derive(Default)]
+pub struct ConfigSchemaValidatorCache {
+    cache: Mutex<HashMap<String, (String, Arc<ConfigSchemaValidator>)>>,
+}
+
+impl ConfigSchemaValidatorCache {
+    pub fn get_validator(
+        &self,
+        schema: &ConfigSchemaDO,
+    ) -> anyhow::Result<Arc<ConfigSchemaValidator>> {
+        let mut cache = self.cache.lock().unwrap();
+        if let Some((text, validator)) = cache.get(&schema.name) {
+            if text == &schema.schema {
+                return Ok(validator.clone());
+            }
+        }
+        let validator = Arc::new(ConfigSchemaValidator::compile(&schema.schema)?);
+        cache.insert(
+            schema.name.clone(),
+            (schema.schema.clone(), validator.clone()),
+        );
+        Ok(validator)
+    }
+
+    pub fn remove(&self, name: &str) {
+        self.cache.lock().unwrap().remove(name);
+    }
+}
+
+pub struct ConfigSchemaUtils;
+
+impl ConfigSchemaUtils {
+    pub async fn query_schemas(
+        table_manager: &Addr<TableManager>,
+    ) -> anyhow::Result<Vec<ConfigSchemaDO>> {
+        let req = TableManagerQueryReq::QueryPageList {
+            table_name: CONFIG_SCHEMA_TREE_NAME.clone(),
+            like_key: None,
+            offset: None,
+            limit: None,
+            is_rev: false,
+        };
+        match table_manager.send(req).await?? {
+            TableManagerResult::PageListResult(_, list) => {
+                let mut schemas = Vec::with_capacity(list.len());
+                for (_, v) in list {
+                    schemas.push(ConfigSchemaDO::from_bytes(&v)?);
+                }
+                Ok(schemas)
+            }
+            _ => Ok(vec![]),
+        }
+    }
+
+    pub async fn set_schema(
+        table_route: &Arc<TableRoute>,
+        mut schema: ConfigSchemaDO,
+    ) -> anyhow::Result<()> {
+        schema.check()?;
+        schema.gmt_modified = now_millis_i64();
+        let req = TableManagerReq::Set {
+            table_name: CONFIG_SCHEMA_TREE_NAME.clone(),
+            key: schema.name.as_bytes().to_owned(),
+            value: schema.to_bytes()?,
+            last_seq_id: None,
+        };
+        table_route.request(req).await?;
+        Ok(())
+    }
+
+    pub async fn remove_schema(table_route: &Arc<TableRoute>, name: &str) -> anyhow::Result<()> {
+        let req = TableManagerReq::Remove {
+            table_name: CONFIG_SCHEMA_TREE_NAME.clone(),
+            key: name.as_bytes().to_owned(),
+        };
+        table_route.request(req).await?;
+        SCHEMA_VALIDATOR_CACHE.remove(name);
+        Ok(())
+    }
+
+    ///
+    /// 未指定类型时按dataId后缀推断
+    pub fn get_config_type(
+        key: &ConfigKey,
+        config_type: Option<&Arc<String>>,
+    ) -> Option<ConfigType> {
+        match config_type {
+            Some(v) if !v.is_empty() => ConfigType::new_by_value(v).ok(),
+            _ => key
+                .data_id
+                .rsplit_once('.')
+                .and_then(|(_, ext)| ConfigType::new_by_value(ext).ok()),
+        }
+    }
+
+    ///
+    /// 按匹配的所有schema校验配置内容，返回不匹配项
+    pub fn validate_by_schemas(
+        schemas: &[ConfigSchemaDO],
+        key: &ConfigKey,
+        config_type: Option<&Arc<String>>,
+        content: &str,
+    ) -> Vec<ConfigSchemaError> {
+        let mut errors = vec![];
+        let matched: Vec<&ConfigSchemaDO> = schemas.iter().filter(|e| e.is_match(key)).collect();
+        if matched.is_empty() {
+            return errors;
+        }
+        let config_type = match Self::get_config_type(key, config_type) {
+            Some(v) => v,
+            None => return errors,
+        };
+        let documents = match ConfigSchemaValidator::parse_documents(config_type, content) {
+            Ok(v) => v,
+            Err(err) => {
+                errors.push(ConfigSchemaError {
+                    path: "/".to_owned(),
+                    message: format!("content is not valid {}, {}", config_type.get_value(), err),
+                    schema_name: matched[0].name.clone(),
+                });
+                return errors;
+            }
+        };
+        if documents.is_empty() {
+            return errors;
+        }
+        for schema in matched {
+            let validator = match SCHEMA_VALIDATOR_CACHE.get_validator(schema) {
+                Ok(v) => v,
+                Err(err) => {
+                    log::warn!("config schema {} compile error,{}", &schema.name, err);
+                    continue;
+                }
+            };
+            for mut err in validator.validate(&documents) {
+                err.schema_name = schema.name.clone();
+                errors.push(err);
+            }
+        }
+        errors
+    }
+
+    ///
+    /// 发布配置前校验，不匹配时返回ConfigError::SchemaInvalid;
+    /// content为空表示加密内容无法解密，存在匹配的schema时不允许发布
+    pub async fn validate_config(
+        table_manager: &Addr<TableManager>,
+        key: &ConfigKey,
+        config_type: Option<&Arc<String>>,
+        content: Option<&str>,
+    ) -> anyhow::Result<()> {
+        let schemas = Self::query_schemas(table_manager).await?;
+        let errors = match content {
+            Some(content) => Self::validate_by_schemas(&schemas, key, config_type, content),
+            None => schemas
+                .iter()
+                .filter(|e| e.is_match(key))
+                .map(|e| ConfigSchemaError {
+                    path: "/".to_owned(),
+                    message: "encrypted content can't be decrypted for validation".to_owned(),
+                    schema_name: e.name.clone(),
+                })
+                .collect(),
+        };
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            let messages = errors.iter().map(|e| e.to_message()).collect();
+            Err(ConfigError::SchemaInvalid(messages).into())
+        }
+    }
+}
+
+#[test]
+fn validate_config_schema() {
+    let schema = ConfigSchemaDO {
+        name: "app".to_owned(),
+        group: Some("DEFAULT_GROUP".to_owned()),
+        schema: r#"{
+            "type": "object",
+            "required": ["port"],
+            "properties": {
+                "port": {"type": "integer", "minimum": 1},
+                "hosts": {"type": "array", "items": {"type": "string"}}
+            }
+        }"#
+        .to_owned(),
+        enable: true,
+        ..Default::default()
+    };
+    assert!(schema.check().is_ok());
+    let schemas = vec![schema];
+    let key = ConfigKey::new("app.yaml", "DEFAULT_GROUP", "");
+    let errors =
+        ConfigSchemaUtils::validate_by_schemas(&schemas, &key, None, "port: 8080\nhosts: [a]");
+    assert!(errors.is_empty());
+
+    let errors =
+        ConfigSchemaUtils::validate_by_schemas(&schemas, &key, None, "port: 0\nhosts: [a, 1]");
+    let mut paths: Vec<&str> = errors.iter().map(|e| e.path.as_str()).collect();
+    paths.sort_unstable();
+    assert_eq!(paths, vec!["/hosts/1", "/port"]);
+
+    let json_type = Arc::new("json".to_owned());
+    let errors = ConfigSchemaUtils::validate_by_schemas(&schemas, &key, Some(&json_type), "{}");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].path, "/");
+    let errors =
+        ConfigSchemaUtils::validate_by_schemas(&schemas, &key, Some(&json_type), "port: 1");
+    assert!(errors[0].message.starts_with("content is not valid json"));
+
+    //不匹配的分组及非json、yaml类型不校验
+    let other = ConfigKey::new("app.yaml", "OTHER", "");
+    assert!(ConfigSchemaUtils::validate_by_schemas(&schemas, &other, None, "{}").is_empty());
+    let text = ConfigKey::new("app.txt", "DEFAULT_GROUP", "");
+    assert!(ConfigSchemaUtils::validate_by_schemas(&schemas, &text, None, "{}").is_empty());
+
+    let invalid = ConfigSchemaDO {
+        name: "invalid".to_owned(),
+        group: Some("DEFAULT_GROUP".to_owned()),
+        schema: r#"{"type": 1}"#.to_owned(),
+        ..Default::default()
+    };
+    assert!(invalid.check().is_err());
+
+    //schema内容不变时复用编译结果
+    let cache = ConfigSchemaValidatorCache::default();
+    let a = cache.get_validator(&schemas[0]).unwrap();
+    let b = cache.get_validator(&schemas[0]).unwrap();
+    assert!(Arc::ptr_eq(&a, &b));
+    let changed = ConfigSchemaDO {
+        schema: r#"{"type": "object"}"#.to_owned(),
+        ..schemas[0].clone()
+    };
+    let c = cache.get_validator(&changed).unwrap();
+    assert!(!Arc::ptr_eq(&a, &c));
+}
@@ -0,0 +1,319 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::core::ConfigKey;
+
+type Gram = [char; 3];
+
+const MAX_LINE_CHARS: usize = 256;
+const MAX_KEYWORD_LEN: usize = 256;
+///
+/// 正则编译后的大小上限，避免复杂正则占用过多内存及cpu
+const REGEX_SIZE_LIMIT: usize = 1 << 20;
+
+///
+/// 配置内容检索参数
+#[derive(Debug, Clone, Default)]
+pub struct ConfigSearchParam {
+    pub keyword: String,
+    pub regex: bool,
+    pub ignore_case: bool,
+    pub tenant: Option<Arc<String>>,
+    pub group: Option<Arc<String>>,
+    ///每个配置最多返回的命中行数
+    pub max_lines: usize,
+    pub offset: usize,
+    pub limit: usize,
+}
+
+impl ConfigSearchParam {
+    pub fn match_key(&self, key: &ConfigKey) -> bool {
+        if let Some(tenant) = &self.tenant {
+            if tenant != &key.tenant {
+                return false;
+            }
+        }
+        if let Some(group) = &self.group {
+            if !group.is_empty() && group != &key.group {
+                return false;
+            }
+        }
+        true
+    }
+
+    pub fn build_matcher(&self) -> anyhow::Result<ContentMatcher> {
+        if self.keyword.is_empty() {
+            return Err(anyhow::anyhow!("search keyword is empty"));
+        }
+        if self.keyword.len() > MAX_KEYWORD_LEN {
+            return Err(anyhow::anyhow!(
+                "search keyword length must not be greater than {}",
+                MAX_KEYWORD_LEN
+            ));
+        }
+        if self.regex {
+            let regex = regex::RegexBuilder::new(&self.keyword)
+                .case_insensitive(self.ignore_case)
+                .size_limit(REGEX_SIZE_LIMIT)
+                .dfa_size_limit(REGEX_SIZE_LIMIT)
+                .build()?;
+            Ok(ContentMatcher::Regex(regex))
+        } else if self.ignore_case {
+            Ok(ContentMatcher::IgnoreCase(self.keyword.to_lowercase()))
+        } else {
+            Ok(ContentMatcher::Text(self.keyword.clone()))
+        }
+    }
+}
+
+pub enum ContentMatcher {
+    Text(String),
+    IgnoreCase(String),
+    Regex(regex::Regex),
+}
+
+impl ContentMatcher {
+    pub fn is_match(&self, line: &str) -> bool {
+        match self {
+            Self::Text(v) => line.contains(v.as_str()),
+            Self::IgnoreCase(v) => line.to_lowercase().contains(v.as_str()),
+            Self::Regex(r) => r.is_match(line),
+        }
+    }
+
+    ///
+    /// 返回命中的行数及前max_lines条命中行
+    pub fn match_lines(
+        &self,
+        content: &str,
+        max_lines: usize,
+    ) -> (usize, Vec<ConfigSearchLineDto>) {
+        let mut count = 0;
+        let mut lines = vec![];
+        for (index, line) in content.lines().enumerate() {
+            if self.is_match(line) {
+                count += 1;
+                if lines.len() < max_lines {
+                    lines.push(ConfigSearchLineDto {
+                        line_number: index + 1,
+                        line: line.chars().take(MAX_LINE_CHARS).collect(),
+                    });
+                }
+            }
+        }
+        (count, lines)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigSearchLineDto {
+    pub line_number: usize,
+    pub line: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigSearchItemDto {
+    pub tenant: Arc<String>,
+    pub group: Arc<String>,
+    pub data_id: Arc<String>,
+    pub match_count: usize,
+    pub lines: Vec<ConfigSearchLineDto>,
+}
+
+///
+/// 在候选配置中检索内容，返回命中的配置数及当前页数据;
+/// 逐行匹配比较耗时，由调用方放在ConfigActor之外执行
+pub fn search_contents(
+    param: &ConfigSearchParam,
+    candidates: Vec<(ConfigKey, Arc<String>)>,
+) -> anyhow::Result<(usize, Vec<ConfigSearchItemDto>)> {
+    let matcher = param.build_matcher()?;
+    let max_lines = if param.max_lines == 0 {
+        10
+    } else {
+        param.max_lines
+    };
+    let mut size = 0;
+    let mut list = vec![];
+    for (key, content) in candidates {
+        let (match_count, lines) = matcher.match_lines(&content, max_lines);
+        if match_count == 0 {
+            continue;
+        }
+        if size >= param.offset && list.len() < param.limit {
+            list.push(ConfigSearchItemDto {
+                tenant: key.tenant,
+                group: key.group,
+                data_id: key.data_id,
+                match_count,
+                lines,
+            });
+        }
+        size += 1;
+    }
+    Ok((size, list))
+}
+
+///
+/// 配置内容的三元组倒排索引，用于子串检索时过滤候选配置;
+/// 索引统一转小写，命中结果需要再按原内容校验;
+/// 倒排表中只保存配置的内部id，避免每个三元组都复制一份ConfigKey
+#[derive(Debug, Default)]
+pub struct ConfigContentIndex {
+    gram_data: HashMap<Gram, HashSet<u64>>,
+    key_ids: HashMap<ConfigKey, u64>,
+    id_data: HashMap<u64, (ConfigKey, HashSet<Gram>)>,
+    next_id: u64,
+}
+
+impl ConfigContentIndex {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn insert(&mut self, key: &ConfigKey, content: &str) {
+        self.remove(key);
+        let id = self.next_id;
+        self.next_id += 1;
+        let grams = Self::build_grams(content);
+        for gram in &grams {
+            self.gram_data.entry(*gram).or_default().insert(id);
+        }
+        self.key_ids.insert(key.clone(), id);
+        self.id_data.insert(id, (key.clone(), grams));
+    }
+
+    pub fn remove(&mut self, key: &ConfigKey) {
+        let id = match self.key_ids.remove(key) {
+            Some(v) => v,
+            None => return,
+        };
+        if let Some((_, grams)) = self.id_data.remove(&id) {
+            for gram in &grams {
+                if let Some(set) = self.gram_data.get_mut(gram) {
+                    set.remove(&id);
+                    if set.is_empty() {
+                        self.gram_data.remove(gram);
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.key_ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.key_ids.is_empty()
+    }
+
+    ///
+    /// 返回可能包含关键字的配置；正则或关键字少于3个字符时返回全部已索引配置
+    pub fn candidates(&self, param: &ConfigSearchParam) -> Vec<ConfigKey> {
+        let grams = if param.regex {
+            HashSet::new()
+        } else {
+            Self::build_grams(&param.keyword)
+        };
+        let mut list: Vec<ConfigKey> = if grams.is_empty() {
+            self.key_ids
+                .keys()
+                .filter(|e| param.match_key(e))
+                .cloned()
+                .collect()
+        } else {
+            let mut sets = vec![];
+            for gram in &grams {
+                if let Some(set) = self.gram_data.get(gram) {
+                    sets.push(set);
+                } else {
+                    return vec![];
+                }
+            }
+            sets.sort_by_key(|e| e.len());
+            sets[0]
+                .iter()
+                .filter(|e| sets[1..].iter().all(|s| s.contains(*e)))
+                .filter_map(|e| self.id_data.get(e).map(|(key, _)| key))
+                .filter(|e| param.match_key(e))
+                .cloned()
+                .collect()
+        };
+        list.sort_by(|a, b| {
+            (&a.tenant, &a.group, &a.data_id).cmp(&(&b.tenant, &b.group, &b.data_id))
+        });
+        list
+    }
+
+    fn build_grams(content: &str) -> HashSet<Gram> {
+        let chars: Vec<char> = content.chars().flat_map(|c| c.to_lowercase()).collect();
+        chars.windows(3).map(|e| [e[0], e[1], e[2]]).collect()
+    }
+}
+
+#[test]
+fn content_index_search() {
+    let mut index = ConfigContentIndex::new();
+    let key1 = ConfigKey::new("1", "g1", "t1");
+    let key2 = ConfigKey::new("2", "g1", "t1");
+    let key3 = ConfigKey::new("3", "g2", "t2");
+    index.insert(&key1, "db.host=10.0.0.1\ndb.port=3306");
+    index.insert(&key2, "db.host=10.0.0.2\ndb.port=3306");
+    index.insert(&key3, "DB.HOST=10.0.0.1");
+
+    let mut param = ConfigSearchParam {
+        keyword: "10.0.0.1".to_owned(),
+        ..Default::default()
+    };
+    assert_eq!(index.candidates(&param), vec![key1.clone(), key3.clone()]);
+    param.tenant = Some(Arc::new("t1".to_owned()));
+    assert_eq!(index.candidates(&param), vec![key1.clone()]);
+
+    param.tenant = None;
+    param.keyword = "db.host".to_owned();
+    let matcher = param.build_matcher().unwrap();
+    let (count, lines) = matcher.match_lines("a=1\ndb.host=10.0.0.1\nDB.HOST=1", 10);
+    assert_eq!(count, 1);
+    assert_eq!(lines[0].line_number, 2);
+    param.ignore_case = true;
+    let matcher = param.build_matcher().unwrap();
+    let (count, _) = matcher.match_lines("a=1\ndb.host=10.0.0.1\nDB.HOST=1", 10);
+    assert_eq!(count, 2);
+
+    param.regex = true;
+    param.keyword = r"port=\d+".to_owned();
+    assert_eq!(index.candidates(&param).len(), 3);
+    let matcher = param.build_matcher().unwrap();
+    assert!(matcher.is_match("db.port=3306"));
+    let candidates = index
+        .candidates(&param)
+        .into_iter()
+        .map(|e| (e, Arc::new("db.port=3306".to_owned())))
+        .collect();
+    let (size, list) = search_contents(
+        &ConfigSearchParam {
+            limit: 2,
+            ..param.clone()
+        },
+        candidates,
+    )
+    .unwrap();
+    assert_eq!((size, list.len()), (3, 2));
+    param.keyword = "a".repeat(MAX_KEYWORD_LEN + 1);
+    assert!(param.build_matcher().is_err());
+    param.keyword = r"\w{1000}{1000}".to_owned();
+    assert!(param.build_matcher().is_err());
+
+    index.insert(&key1, "other");
+    index.remove(&key2);
+    param.regex = false;
+    param.keyword = "10.0.0".to_owned();
+    assert_eq!(index.candidates(&param), vec![key3]);
+}
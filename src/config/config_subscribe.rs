@@ -4,13 +4,22 @@ use std::{
 };
 
 use super::core::{ConfigKey, ListenerItem};
+use super::model::{ConfigClientInfo, ConfigListenerInfoDto, ConfigListenerQueryParam};
 use crate::grpc::bistream_manage::{BiStreamManage, BiStreamManageCmd};
 use actix::prelude::*;
 
+///
+/// http watch客户端接收变更配置的通道
+pub type WatchSenderType = tokio::sync::mpsc::UnboundedSender<ConfigKey>;
+
 #[derive(Default)]
 pub struct Subscriber {
     listener: HashMap<ConfigKey, HashSet<Arc<String>>>,
-    client_keys: HashMap<Arc<String>, HashSet<ConfigKey>>,
+    ///客户端监听的配置及最后上报的md5
+    client_keys: HashMap<Arc<String>, HashMap<ConfigKey, Arc<String>>>,
+    client_infos: HashMap<Arc<String>, ConfigClientInfo>,
+    ///http watch客户端，变更通过通道推送，不经过grpc连接
+    watchers: HashMap<Arc<String>, WatchSenderType>,
     conn_manage: Option<Addr<BiStreamManage>>,
 }
 
@@ -19,6 +28,8 @@ impl Subscriber {
         Self {
             listener: Default::default(),
             client_keys: Default::default(),
+            client_infos: Default::default(),
+            watchers: Default::default(),
             conn_manage: Default::default(),
         }
     }
@@ -27,7 +38,12 @@ impl Subscriber {
         self.conn_manage = Some(conn_manage);
     }
 
-    pub fn add_subscribe(&mut self, client_id: Arc<String>, items: Vec<ListenerItem>) {
+    pub fn add_subscribe(
+        &mut self,
+        client_id: Arc<String>,
+        client: ConfigClientInfo,
+        items: Vec<ListenerItem>,
+    ) {
         for item in &items {
             match self.listener.get_mut(&item.key) {
                 Some(set) => {
@@ -40,22 +56,27 @@ impl Subscriber {
                 }
             };
         }
+        self.client_infos.insert(client_id.clone(), client);
         match self.client_keys.get_mut(&client_id) {
-            Some(set) => {
+            Some(map) => {
                 for item in items {
-                    set.insert(item.key);
+                    map.insert(item.key, item.md5);
                 }
             }
             None => {
-                let mut set = HashSet::new();
+                let mut map = HashMap::new();
                 for item in items {
-                    set.insert(item.key);
+                    map.insert(item.key, item.md5);
                 }
-                self.client_keys.insert(client_id, set);
+                self.client_keys.insert(client_id, map);
             }
         }
     }
 
+    pub fn add_watcher(&mut self, client_id: Arc<String>, sender: WatchSenderType) {
+        self.watchers.insert(client_id, sender);
+    }
+
     pub fn remove_subscribe(&mut self, client_id: Arc<String>, items: Vec<ListenerItem>) {
         let mut remove_keys = vec![];
         for item in &items {
@@ -81,13 +102,16 @@ impl Subscriber {
         };
         if remove_empty_client {
             self.client_keys.remove(&client_id);
+            self.client_infos.remove(&client_id);
         }
     }
 
     pub fn remove_client_subscribe(&mut self, client_id: Arc<String>) {
-        if let Some(set) = self.client_keys.remove(&client_id) {
+        self.client_infos.remove(&client_id);
+        self.watchers.remove(&client_id);
+        if let Some(map) = self.client_keys.remove(&client_id) {
             let mut remove_keys = vec![];
-            for key in set {
+            for key in map.into_keys() {
                 if let Some(set) = self.listener.get_mut(&key) {
                     set.remove(&client_id);
                     if set.is_empty() {
@@ -101,10 +125,17 @@ impl Subscriber {
         }
     }
 
+    ///
+    /// 配置删除后移除grpc客户端的监听；http watch客户端保留监听，直到连接关闭
     pub fn remove_config_key(&mut self, key: ConfigKey) {
         if let Some(set) = self.listener.remove(&key) {
             let mut remove_keys = vec![];
+            let mut watch_set = HashSet::new();
             for client_id in set {
+                if self.watchers.contains_key(&client_id) {
+                    watch_set.insert(client_id);
+                    continue;
+                }
                 if let Some(set) = self.client_keys.get_mut(&client_id) {
                     set.remove(&key);
                     if set.is_empty() {
@@ -114,15 +145,162 @@ impl Subscriber {
             }
             for key in &remove_keys {
                 self.client_keys.remove(key);
+                self.client_infos.remove(key);
+            }
+            if !watch_set.is_empty() {
+                self.listener.insert(key, watch_set);
             }
         }
     }
 
-    pub fn notify(&self, key: ConfigKey) {
-        if let Some(conn_manage) = &self.conn_manage {
+    ///
+    /// 按配置或客户端查询grpc监听信息
+    pub fn query_listener(&self, param: &ConfigListenerQueryParam) -> Vec<ConfigListenerInfoDto> {
+        let mut list = vec![];
+        if let Some(key) = param.get_config_key() {
             if let Some(set) = self.listener.get(&key) {
-                conn_manage.do_send(BiStreamManageCmd::NotifyConfig(key, set.clone()));
+                for client_id in set {
+                    if let Some(info) = self.build_listener_info(client_id, &key, param) {
+                        list.push(info);
+                    }
+                }
             }
+            return list;
         }
+        for (client_id, map) in &self.client_keys {
+            for key in map.keys() {
+                if let Some(info) = self.build_listener_info(client_id, key, param) {
+                    list.push(info);
+                }
+            }
+        }
+        list
     }
+
+    fn build_listener_info(
+        &self,
+        client_id: &Arc<String>,
+        key: &ConfigKey,
+        param: &ConfigListenerQueryParam,
+    ) -> Option<ConfigListenerInfoDto> {
+        let client = self.client_infos.get(client_id)?;
+        if !param.match_client(&client.ip, Some(client_id)) {
+            return None;
+        }
+        let md5 = self
+            .client_keys
+            .get(client_id)
+            .and_then(|map| map.get(key))
+            .cloned()
+            .unwrap_or_default();
+        Some(ConfigListenerInfoDto::new(
+            key,
+            md5,
+            client,
+            Some(client_id.clone()),
+        ))
+    }
+
+    pub fn notify(&mut self, key: ConfigKey) {
+        let set = match self.listener.get(&key) {
+            Some(set) => set,
+            None => return,
+        };
+        let mut conn_set = HashSet::new();
+        let mut closed_watchers = vec![];
+        for client_id in set {
+            if let Some(sender) = self.watchers.get(client_id) {
+                if sender.send(key.clone()).is_err() {
+                    closed_watchers.push(client_id.clone());
+                }
+            } else {
+                conn_set.insert(client_id.clone());
+            }
+        }
+        if !conn_set.is_empty() {
+            if let Some(conn_manage) = &self.conn_manage {
+                conn_manage.do_send(BiStreamManageCmd::NotifyConfig(key, conn_set));
+            }
+        }
+        for client_id in closed_watchers {
+            self.remove_client_subscribe(client_id);
+        }
+    }
+}
+
+#[test]
+fn query_subscribe_listener() {
+    let key_a = ConfigKey::new("a", "DEFAULT_GROUP", "");
+    let key_b = ConfigKey::new("b", "DEFAULT_GROUP", "");
+    let mut subscriber = Subscriber::new();
+    subscriber.add_subscribe(
+        Arc::new("conn_1".to_owned()),
+        ConfigClientInfo::new("127.0.0.1".to_owned(), Default::default()),
+        vec![
+            ListenerItem::new(key_a.clone(), Arc::new("md5_a".to_owned())),
+            ListenerItem::new(key_b.clone(), Arc::new("md5_b".to_owned())),
+        ],
+    );
+    subscriber.add_subscribe(
+        Arc::new("conn_2".to_owned()),
+        ConfigClientInfo::new("127.0.0.2".to_owned(), Default::default()),
+        vec![ListenerItem::new(
+            key_a.clone(),
+            Arc::new("md5_a2".to_owned()),
+        )],
+    );
+    let by_key = ConfigListenerQueryParam {
+        key: Some(key_a.build_key()),
+        ..Default::default()
+    };
+    assert_eq!(subscriber.query_listener(&by_key).len(), 2);
+    let by_ip = ConfigListenerQueryParam {
+        client_ip: Some("127.0.0.2".to_owned()),
+        ..Default::default()
+    };
+    let list = subscriber.query_listener(&by_ip);
+    assert_eq!(list.len(), 1);
+    assert_eq!(list[0].md5.as_str(), "md5_a2");
+    let by_conn = ConfigListenerQueryParam {
+        conn_id: Some("conn_1".to_owned()),
+        ..Default::default()
+    };
+    assert_eq!(subscriber.query_listener(&by_conn).len(), 2);
+    subscriber.remove_client_subscribe(Arc::new("conn_1".to_owned()));
+    assert_eq!(subscriber.query_listener(&by_key).len(), 1);
+    assert!(subscriber.query_listener(&by_conn).is_empty());
+}
+
+#[test]
+fn notify_config_watcher() {
+    let key = ConfigKey::new("a", "DEFAULT_GROUP", "");
+    let watch_id = Arc::new("watch_1".to_owned());
+    let conn_id = Arc::new("conn_1".to_owned());
+    let mut subscriber = Subscriber::new();
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    for client_id in [watch_id.clone(), conn_id.clone()].iter() {
+        subscriber.add_subscribe(
+            client_id.clone(),
+            ConfigClientInfo::new("127.0.0.1".to_owned(), Default::default()),
+            vec![ListenerItem::new(key.clone(), Arc::new("md5".to_owned()))],
+        );
+    }
+    subscriber.add_watcher(watch_id.clone(), tx);
+    subscriber.notify(key.clone());
+    assert_eq!(rx.try_recv().unwrap(), key);
+
+    //配置删除后watch客户端保留监听
+    subscriber.remove_config_key(key.clone());
+    let by_key = ConfigListenerQueryParam {
+        key: Some(key.build_key()),
+        ..Default::default()
+    };
+    let list = subscriber.query_listener(&by_key);
+    assert_eq!(list.len(), 1);
+    assert_eq!(list[0].conn_id.as_ref(), Some(&watch_id));
+
+    //通道关闭后移除watch客户端
+    drop(rx);
+    subscriber.notify(key);
+    assert!(subscriber.query_listener(&by_key).is_empty());
 }
@@ -0,0 +1,295 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::core::ConfigInfoDto;
+
+///
+/// 导出包中的元数据文件名，兼容nacos的.metadata.yml
+pub const CONFIG_META_FILE_NAME: &str = ".meta.yml";
+pub const CONFIG_METADATA_FILE_NAME: &str = ".metadata.yml";
+
+///
+/// 导入时配置已存在的处理策略，与nacos保持一致
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConfigImportPolicy {
+    ///存在冲突时整体终止，不导入任何配置
+    Abort,
+    ///跳过已存在的配置
+    Skip,
+    ///覆盖已存在的配置
+    #[default]
+    Overwrite,
+}
+
+impl ConfigImportPolicy {
+    pub fn new_by_value(value: &str) -> anyhow::Result<Self> {
+        match value.to_uppercase().as_str() {
+            "ABORT" => Ok(Self::Abort),
+            "SKIP" => Ok(Self::Skip),
+            "OVERWRITE" => Ok(Self::Overwrite),
+            _ => Err(anyhow::anyhow!("invalid import policy : {}", value)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigMetaItem {
+    pub group: String,
+    pub data_id: String,
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub config_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub desc: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config_tags: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConfigMetadata {
+    #[serde(default)]
+    pub metadata: Vec<ConfigMetaItem>,
+}
+
+impl ConfigMetadata {
+    pub fn build(list: &[ConfigInfoDto]) -> Self {
+        let metadata = list
+            .iter()
+            .map(|item| ConfigMetaItem {
+                group: item.group.as_ref().to_owned(),
+                data_id: item.data_id.as_ref().to_owned(),
+                config_type: item.config_type.as_ref().map(|e| e.as_ref().to_owned()),
+                desc: item.desc.as_ref().map(|e| e.as_ref().to_owned()),
+                app_name: item.app_name.as_ref().map(|e| e.as_ref().to_owned()),
+                config_tags: item.config_tags.as_ref().map(|e| e.as_ref().to_owned()),
+            })
+            .collect();
+        Self { metadata }
+    }
+
+    pub fn to_yaml(&self) -> anyhow::Result<String> {
+        Ok(serde_yaml::to_string(self)?)
+    }
+
+    ///
+    /// 解析元数据文件，支持yaml格式及nacos旧版本`group.dataId.app=appName`格式
+    pub fn parse(content: &str) -> anyhow::Result<Self> {
+        if let Ok(v) = serde_yaml::from_str::<Self>(content) {
+            return Ok(v);
+        }
+        let mut metadata = vec![];
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some((key, value)) = line.split_once('=') {
+                if let Some(key) = key.strip_suffix(".app") {
+                    //旧格式key无法可靠拆分出group与dataId，匹配时按legacy_key比较
+                    metadata.push(ConfigMetaItem {
+                        group: String::new(),
+                        data_id: key.to_owned(),
+                        app_name: Some(value.to_owned()),
+                        ..Default::default()
+                    });
+                }
+            } else {
+                return Err(anyhow::anyhow!("invalid config metadata line: {}", line));
+            }
+        }
+        Ok(Self { metadata })
+    }
+
+    ///
+    /// nacos旧版本元数据key: group.dataId，dataId最后一个.替换为~
+    fn legacy_key(group: &str, data_id: &str) -> String {
+        let data_id = match data_id.rfind('.') {
+            Some(i) => format!("{}~{}", &data_id[..i], &data_id[i + 1..]),
+            None => data_id.to_owned(),
+        };
+        format!("{}.{}", group, data_id)
+    }
+
+    pub fn to_map(self) -> HashMap<(String, String), ConfigMetaItem> {
+        let mut map = HashMap::with_capacity(self.metadata.len());
+        for item in self.metadata {
+            map.insert((item.group.clone(), item.data_id.clone()), item);
+        }
+        map
+    }
+
+    pub fn find<'a>(
+        map: &'a HashMap<(String, String), ConfigMetaItem>,
+        group: &str,
+        data_id: &str,
+    ) -> Option<&'a ConfigMetaItem> {
+        map.get(&(group.to_owned(), data_id.to_owned()))
+            .or_else(|| map.get(&(String::new(), Self::legacy_key(group, data_id))))
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigImportItem {
+    pub group: String,
+    pub data_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl ConfigImportItem {
+    pub fn new(group: &str, data_id: &str, message: Option<String>) -> Self {
+        Self {
+            group: group.to_owned(),
+            data_id: data_id.to_owned(),
+            message,
+        }
+    }
+}
+
+///
+/// 导入结果，按条目返回成功、跳过及失败的配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigImportResult {
+    pub succ_count: usize,
+    pub skip_count: usize,
+    pub fail_count: usize,
+    pub succ_data: Vec<ConfigImportItem>,
+    pub skip_data: Vec<ConfigImportItem>,
+    pub fail_data: Vec<ConfigImportItem>,
+}
+
+impl ConfigImportResult {
+    pub fn add_succ(&mut self, item: ConfigImportItem) {
+        self.succ_count += 1;
+        self.succ_data.push(item);
+    }
+
+    pub fn add_skip(&mut self, item: ConfigImportItem) {
+        self.skip_count += 1;
+        self.skip_data.push(item);
+    }
+
+    pub fn add_fail(&mut self, item: ConfigImportItem) {
+        self.fail_count += 1;
+        self.fail_data.push(item);
+    }
+}
+
+///
+/// 复制配置时group、dataId的重写规则，按正则匹配替换，replacement支持$1等分组引用
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigRewriteRule {
+    ///重写字段: group或dataId
+    pub field: String,
+    pub pattern: String,
+    pub replacement: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ConfigRewriter {
+    group_rules: Vec<(regex::Regex, String)>,
+    data_id_rules: Vec<(regex::Regex, String)>,
+}
+
+impl ConfigRewriter {
+    pub fn new(rules: &[ConfigRewriteRule]) -> anyhow::Result<Self> {
+        let mut rewriter = Self::default();
+        for rule in rules {
+            let regex = regex::Regex::new(&rule.pattern)?;
+            match rule.field.as_str() {
+                "group" => rewriter
+                    .group_rules
+                    .push((regex, rule.replacement.to_owned())),
+                "dataId" | "data_id" => rewriter
+                    .data_id_rules
+                    .push((regex, rule.replacement.to_owned())),
+                _ => return Err(anyhow::anyhow!("invalid rewrite field : {}", &rule.field)),
+            }
+        }
+        Ok(rewriter)
+    }
+
+    ///
+    /// 按规则顺序依次重写，返回(group,dataId)
+    pub fn rewrite(&self, group: &str, data_id: &str) -> (String, String) {
+        (
+            Self::apply(&self.group_rules, group),
+            Self::apply(&self.data_id_rules, data_id),
+        )
+    }
+
+    fn apply(rules: &[(regex::Regex, String)], value: &str) -> String {
+        let mut value = value.to_owned();
+        for (regex, replacement) in rules {
+            value = regex.replace(&value, replacement.as_str()).into_owned();
+        }
+        value
+    }
+}
+
+#[test]
+fn parse_config_metadata() {
+    let list = vec![ConfigInfoDto {
+        group: std::sync::Arc::new("DEFAULT_GROUP".to_owned()),
+        data_id: std::sync::Arc::new("app.yaml".to_owned()),
+        config_type: Some(std::sync::Arc::new("yaml".to_owned())),
+        desc: Some(std::sync::Arc::new("app config".to_owned())),
+        config_tags: Some(std::sync::Arc::new("a,b".to_owned())),
+        ..Default::default()
+    }];
+    let yaml = ConfigMetadata::build(&list).to_yaml().unwrap();
+    let map = ConfigMetadata::parse(&yaml).unwrap().to_map();
+    let item = ConfigMetadata::find(&map, "DEFAULT_GROUP", "app.yaml").unwrap();
+    assert_eq!(item.config_type.as_deref(), Some("yaml"));
+    assert_eq!(item.config_tags.as_deref(), Some("a,b"));
+    assert!(item.app_name.is_none());
+
+    let map = ConfigMetadata::parse("DEFAULT_GROUP.app~yaml.app=demo\n")
+        .unwrap()
+        .to_map();
+    let item = ConfigMetadata::find(&map, "DEFAULT_GROUP", "app.yaml").unwrap();
+    assert_eq!(item.app_name.as_deref(), Some("demo"));
+    assert!(ConfigMetadata::find(&map, "DEFAULT_GROUP", "other").is_none());
+
+    assert_eq!(
+        ConfigImportPolicy::new_by_value("skip").unwrap(),
+        ConfigImportPolicy::Skip
+    );
+    assert!(ConfigImportPolicy::new_by_value("x").is_err());
+}
+
+#[test]
+fn rewrite_config_key() {
+    let rules = vec![
+        ConfigRewriteRule {
+            field: "group".to_owned(),
+            pattern: "^DEV_GROUP$".to_owned(),
+            replacement: "TEST_GROUP".to_owned(),
+        },
+        ConfigRewriteRule {
+            field: "dataId".to_owned(),
+            pattern: r"^(.+)-dev\.(\w+)$".to_owned(),
+            replacement: "$1-test.$2".to_owned(),
+        },
+    ];
+    let rewriter = ConfigRewriter::new(&rules).unwrap();
+    assert_eq!(
+        rewriter.rewrite("DEV_GROUP", "app-dev.yaml"),
+        ("TEST_GROUP".to_owned(), "app-test.yaml".to_owned())
+    );
+    assert_eq!(
+        rewriter.rewrite("DEV_GROUP_X", "app.yaml"),
+        ("DEV_GROUP_X".to_owned(), "app.yaml".to_owned())
+    );
+    let rules = vec![ConfigRewriteRule {
+        field: "tenant".to_owned(),
+        ..Default::default()
+    }];
+    assert!(ConfigRewriter::new(&rules).is_err());
+}
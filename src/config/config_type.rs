@@ -0,0 +1,174 @@
+use serde::Deserialize;
+
+///
+/// 配置内容类型，与nacos控制台支持的类型保持一致
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigType {
+    Text,
+    Json,
+    Xml,
+    Yaml,
+    Html,
+    Properties,
+    Toml,
+}
+
+impl ConfigType {
+    pub fn new_by_value(value: &str) -> anyhow::Result<Self> {
+        match value.to_lowercase().as_str() {
+            "text" | "txt" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            "xml" => Ok(Self::Xml),
+            "yaml" | "yml" => Ok(Self::Yaml),
+            "html" | "htm" => Ok(Self::Html),
+            "properties" => Ok(Self::Properties),
+            "toml" => Ok(Self::Toml),
+            _ => Err(anyhow::anyhow!("invalid config type : {}", value)),
+        }
+    }
+
+    pub fn get_value(&self) -> &'static str {
+        match self {
+            Self::Text => "text",
+            Self::Json => "json",
+            Self::Xml => "xml",
+            Self::Yaml => "yaml",
+            Self::Html => "html",
+            Self::Properties => "properties",
+            Self::Toml => "toml",
+        }
+    }
+
+    ///
+    /// 按类型校验配置内容格式，text与html不做校验
+    pub fn validate(&self, content: &str) -> anyhow::Result<()> {
+        let r = match self {
+            Self::Text | Self::Html => Ok(()),
+            Self::Json => Self::validate_json(content),
+            Self::Xml => Self::validate_xml(content),
+            Self::Yaml => Self::validate_yaml(content),
+            Self::Properties => Self::validate_properties(content),
+            Self::Toml => Self::validate_toml(content),
+        };
+        r.map_err(|e| anyhow::anyhow!("config content is not valid {}, {}", self.get_value(), e))
+    }
+
+    fn validate_json(content: &str) -> anyhow::Result<()> {
+        serde_json::from_str::<serde_json::Value>(content)?;
+        Ok(())
+    }
+
+    fn validate_yaml(content: &str) -> anyhow::Result<()> {
+        //支持多文档yaml
+        for document in serde_yaml::Deserializer::from_str(content) {
+            serde_yaml::Value::deserialize(document)?;
+        }
+        Ok(())
+    }
+
+    fn validate_toml(content: &str) -> anyhow::Result<()> {
+        toml::from_str::<toml::Table>(content)?;
+        Ok(())
+    }
+
+    fn validate_xml(content: &str) -> anyhow::Result<()> {
+        use quick_xml::events::Event;
+        let mut reader = quick_xml::Reader::from_str(content);
+        let mut depth = 0usize;
+        let mut has_root = false;
+        loop {
+            match reader.read_event() {
+                Ok(Event::Start(_)) => {
+                    if depth == 0 && has_root {
+                        return Err(anyhow::anyhow!("multiple root elements"));
+                    }
+                    depth += 1;
+                    has_root = true;
+                }
+                Ok(Event::Empty(_)) => {
+                    if depth == 0 && has_root {
+                        return Err(anyhow::anyhow!("multiple root elements"));
+                    }
+                    has_root = true;
+                }
+                Ok(Event::End(_)) => {
+                    if depth == 0 {
+                        return Err(anyhow::anyhow!(
+                            "unexpected end tag at position {}",
+                            reader.buffer_position()
+                        ));
+                    }
+                    depth -= 1;
+                }
+                Ok(Event::Eof) => break,
+                Ok(_) => {}
+                Err(err) => {
+                    return Err(anyhow::anyhow!(
+                        "error at position {}: {}",
+                        reader.buffer_position(),
+                        err
+                    ));
+                }
+            }
+        }
+        if depth > 0 {
+            return Err(anyhow::anyhow!("unclosed element"));
+        }
+        if !has_root {
+            return Err(anyhow::anyhow!("root element is empty"));
+        }
+        Ok(())
+    }
+
+    ///
+    /// properties格式比较宽松，只校验\uxxxx转义是否合法
+    fn validate_properties(content: &str) -> anyhow::Result<()> {
+        for (index, line) in content.lines().enumerate() {
+            let trimmed = line.trim_start();
+            if trimmed.starts_with('#') || trimmed.starts_with('!') {
+                continue;
+            }
+            let chars: Vec<char> = line.chars().collect();
+            let mut i = 0;
+            while i < chars.len() {
+                if chars[i] == '\\' {
+                    if i + 1 < chars.len() && chars[i + 1] == 'u' {
+                        let hex: String = chars.iter().skip(i + 2).take(4).collect();
+                        if hex.len() != 4 || u32::from_str_radix(&hex, 16).is_err() {
+                            return Err(anyhow::anyhow!(
+                                "malformed \\uxxxx encoding at line {}",
+                                index + 1
+                            ));
+                        }
+                        i += 6;
+                        continue;
+                    }
+                    i += 2;
+                    continue;
+                }
+                i += 1;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn validate_config_type() {
+    assert!(ConfigType::new_by_value("unknown").is_err());
+    assert_eq!(ConfigType::new_by_value("YML").unwrap(), ConfigType::Yaml);
+    assert!(ConfigType::Json.validate(r#"{"a":1}"#).is_ok());
+    assert!(ConfigType::Json.validate(r#"{"a":1"#).is_err());
+    assert!(ConfigType::Yaml.validate("a:\n  b: 1\n---\nc: 2\n").is_ok());
+    assert!(ConfigType::Yaml.validate("a: [1, 2\nb: 1").is_err());
+    assert!(ConfigType::Xml.validate("<a><b>1</b><c/></a>").is_ok());
+    assert!(ConfigType::Xml.validate("<a><b>1</a>").is_err());
+    assert!(ConfigType::Xml.validate("<a></a><b></b>").is_err());
+    assert!(ConfigType::Toml.validate("[server]\nport = 8080\n").is_ok());
+    assert!(ConfigType::Toml.validate("[server\nport = 8080\n").is_err());
+    assert!(ConfigType::Properties
+        .validate("a=1\nb=\\u4e2d\n# \\uzz")
+        .is_ok());
+    assert!(ConfigType::Properties.validate("a=\\u4e2").is_err());
+    assert!(ConfigType::Text.validate("{").is_ok());
+}
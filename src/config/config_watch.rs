@@ -0,0 +1,171 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
+
+use actix::Addr;
+use actix_web::web::Bytes;
+use futures_util::stream::Stream;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::UnboundedReceiver;
+use uuid::Uuid;
+
+use super::config_encryption::CONFIG_ENCRYPTION;
+use super::core::{ConfigActor, ConfigCmd, ConfigKey, ConfigResult, ListenerItem};
+use super::model::ConfigClientInfo;
+
+///
+/// 无变更时定时发送注释行，避免连接被代理断开
+const WATCH_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigWatchEvent {
+    pub data_id: Arc<String>,
+    pub group: Arc<String>,
+    pub tenant: Arc<String>,
+    ///配置删除时为空
+    pub md5: Arc<String>,
+    pub deleted: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<Arc<String>>,
+}
+
+impl ConfigWatchEvent {
+    pub fn to_sse_bytes(&self) -> Bytes {
+        let data = serde_json::to_string(self).unwrap_or_default();
+        Bytes::from(format!("event: change\ndata: {}\n\n", data))
+    }
+}
+
+///
+/// http watch客户端，以SSE格式持续推送监听配置的变更；
+/// 重连时客户端带上最后收到的md5，不一致的配置会在连接建立后立即推送
+pub struct ConfigWatcher {
+    client_id: Arc<String>,
+    client: ConfigClientInfo,
+    config_addr: Addr<ConfigActor>,
+    receiver: UnboundedReceiver<ConfigKey>,
+    pending: VecDeque<ConfigKey>,
+    ///已推送给客户端的md5
+    md5_map: HashMap<ConfigKey, Arc<String>>,
+    with_content: bool,
+    heartbeat: tokio::time::Interval,
+}
+
+impl ConfigWatcher {
+    pub async fn register(
+        config_addr: Addr<ConfigActor>,
+        items: Vec<ListenerItem>,
+        client: ConfigClientInfo,
+        with_content: bool,
+    ) -> anyhow::Result<Self> {
+        let client_id = Arc::new(format!("http_watch_{}", Uuid::new_v4().simple()));
+        let md5_map = items
+            .iter()
+            .map(|item| (item.key.clone(), item.md5.clone()))
+            .collect();
+        let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+        let cmd = ConfigCmd::Watch(items, client_id.clone(), client.clone(), sender);
+        let pending = match config_addr.send(cmd).await?? {
+            ConfigResult::ChangeKey(keys) => keys.into(),
+            _ => VecDeque::new(),
+        };
+        Ok(Self {
+            client_id,
+            client,
+            config_addr,
+            receiver,
+            pending,
+            md5_map,
+            with_content,
+            heartbeat: tokio::time::interval(WATCH_HEARTBEAT_INTERVAL),
+        })
+    }
+
+    pub fn into_stream(self) -> impl Stream<Item = Result<Bytes, actix_web::Error>> {
+        futures_util::stream::unfold(self, |mut watcher| async move {
+            watcher.next_event().await.map(|v| (Ok(v), watcher))
+        })
+    }
+
+    async fn next_event(&mut self) -> Option<Bytes> {
+        loop {
+            let key = match self.pending.pop_front() {
+                Some(key) => key,
+                None => {
+                    tokio::select! {
+                        v = self.receiver.recv() => v?,
+                        _ = self.heartbeat.tick() => {
+                            return Some(Bytes::from_static(b": heartbeat\n\n"));
+                        }
+                    }
+                }
+            };
+            match self.build_event(key).await {
+                Ok(Some(event)) => return Some(event.to_sse_bytes()),
+                Ok(None) => {}
+                Err(err) => {
+                    log::warn!("config watch {} query error,{}", &self.client_id, err);
+                    return None;
+                }
+            }
+        }
+    }
+
+    ///
+    /// md5与已推送的一致时不重复推送
+    async fn build_event(&mut self, key: ConfigKey) -> anyhow::Result<Option<ConfigWatchEvent>> {
+        let cmd = ConfigCmd::GetByClient(key.clone(), self.client.clone());
+        let (md5, content) = match self.config_addr.send(cmd).await?? {
+            ConfigResult::QueryData(data) => {
+                let content = if self.with_content {
+                    Some(CONFIG_ENCRYPTION.decrypt(
+                        &key.data_id,
+                        data.encrypted_data_key.as_ref(),
+                        data.content,
+                    )?)
+                } else {
+                    None
+                };
+                (data.md5, content)
+            }
+            _ => (Arc::new(String::new()), None),
+        };
+        if self.md5_map.get(&key) == Some(&md5) {
+            return Ok(None);
+        }
+        self.md5_map.insert(key.clone(), md5.clone());
+        Ok(Some(ConfigWatchEvent {
+            deleted: md5.is_empty(),
+            data_id: key.data_id,
+            group: key.group,
+            tenant: key.tenant,
+            md5,
+            content,
+        }))
+    }
+}
+
+impl Drop for ConfigWatcher {
+    fn drop(&mut self) {
+        self.config_addr
+            .do_send(ConfigCmd::RemoveSubscribeClient(self.client_id.clone()));
+    }
+}
+
+#[test]
+fn config_watch_event_format() {
+    let event = ConfigWatchEvent {
+        data_id: Arc::new("app.yaml".to_owned()),
+        group: Arc::new("DEFAULT_GROUP".to_owned()),
+        tenant: Arc::new("".to_owned()),
+        md5: Arc::new("md5".to_owned()),
+        deleted: false,
+        content: None,
+    };
+    let bytes = event.to_sse_bytes();
+    let text = std::str::from_utf8(&bytes).unwrap();
+    assert!(text.starts_with("event: change\ndata: {"));
+    assert!(text.ends_with("}\n\n"));
+    assert!(!text.contains("content"));
+}
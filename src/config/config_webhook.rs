@@ -0,0 +1,794 @@
+use std::cmp::max;
+use std::sync::Arc;
+use std::time::Duration;
+
+use actix::prelude::*;
+use bean_factory::{bean, Inject};
+use serde::{Deserialize, Serialize};
+
+use crate::common::byte_utils::id_to_bin;
+use crate::common::constant::{CONFIG_WEBHOOK_LOG_TREE_NAME, CONFIG_WEBHOOK_TREE_NAME};
+use crate::common::AppSysConfig;
+use crate::now_millis_i64;
+use crate::raft::cluster::model::RouteAddr;
+use crate::raft::cluster::route::RaftAddrRouter;
+use crate::raft::db::route::TableRoute;
+use crate::raft::db::table::{
+    TableManager, TableManagerQueryReq, TableManagerReq, TableManagerResult,
+};
+
+use super::config_diff::ConfigDiffUtils;
+use super::config_encryption::ConfigEncryptionManager;
+use super::core::ConfigKey;
+
+pub const CONFIG_EVENT_SET: &str = "SET";
+pub const CONFIG_EVENT_REMOVE: &str = "REMOVE";
+const WEBHOOK_KEY_SEPARATOR: char = '\x01';
+
+///
+/// 配置变更webhook订阅，存储在raft表中;
+/// 表key为 tenant\x01group\x01name，按配置的命名空间、分组前缀查询可能匹配的webhook
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigWebhookDO {
+    pub name: String,
+    pub url: String,
+    ///为空时匹配所有命名空间
+    #[serde(default)]
+    pub tenant: Option<String>,
+    ///为空时匹配所有分组
+    #[serde(default)]
+    pub group: Option<String>,
+    ///dataId匹配规则，支持*通配符，为空时匹配所有dataId
+    #[serde(default)]
+    pub data_id_pattern: Option<String>,
+    ///通知中是否附带内容diff，加密配置不附带
+    #[serde(default)]
+    pub with_diff: bool,
+    #[serde(default)]
+    pub enable: bool,
+    #[serde(default)]
+    pub gmt_modified: i64,
+}
+
+impl ConfigWebhookDO {
+    pub fn to_bytes(&self) -> anyhow::Result<Vec<u8>> {
+        Ok(serde_json::to_vec(self)?)
+    }
+
+    pub fn from_bytes(v: &[u8]) -> anyhow::Result<Self> {
+        Ok(serde_json::from_slice(v)?)
+    }
+
+    pub fn check(&self) -> anyhow::Result<()> {
+        if self.name.is_empty() {
+            return Err(anyhow::anyhow!("webhook name can't empty"));
+        }
+        let fields = [Some(&self.name), self.tenant.as_ref(), self.group.as_ref()];
+        if fields
+            .iter()
+            .flatten()
+            .any(|e| e.contains(WEBHOOK_KEY_SEPARATOR))
+        {
+            return Err(anyhow::anyhow!("webhook name,tenant or group is invalid"));
+        }
+        if !self.url.starts_with("http://") && !self.url.starts_with("https://") {
+            return Err(anyhow::anyhow!("invalid webhook url : {}", &self.url));
+        }
+        Ok(())
+    }
+
+    pub fn build_key(&self) -> Vec<u8> {
+        let mut key = Self::build_key_prefix(
+            self.tenant.as_deref().unwrap_or_default(),
+            self.group.as_deref().unwrap_or_default(),
+        );
+        key.push_str(&self.name);
+        key.into_bytes()
+    }
+
+    fn build_key_prefix(tenant: &str, group: &str) -> String {
+        format!(
+            "{}{}{}{}",
+            tenant, WEBHOOK_KEY_SEPARATOR, group, WEBHOOK_KEY_SEPARATOR
+        )
+    }
+
+    ///
+    /// 空的tenant、group匹配全部，可能匹配配置的webhook只在这几个前缀下
+    pub fn build_match_prefixes(key: &ConfigKey) -> Vec<String> {
+        let mut prefixes = vec![];
+        for tenant in [key.tenant.as_str(), ""] {
+            for group in [key.group.as_str(), ""] {
+                let prefix = Self::build_key_prefix(tenant, group);
+                if !prefixes.contains(&prefix) {
+                    prefixes.push(prefix);
+                }
+            }
+        }
+        prefixes
+    }
+
+    pub fn is_match(&self, key: &ConfigKey) -> bool {
+        if !self.enable {
+            return false;
+        }
+        if let Some(tenant) = &self.tenant {
+            if !tenant.is_empty() && tenant != key.tenant.as_str() {
+                return false;
+            }
+        }
+        if let Some(group) = &self.group {
+            if !group.is_empty() && group != key.group.as_str() {
+                return false;
+            }
+        }
+        match &self.data_id_pattern {
+            Some(pattern) if !pattern.is_empty() => wildcard_match(pattern, &key.data_id),
+            _ => true,
+        }
+    }
+}
+
+///
+/// *匹配任意个字符
+pub(crate) fn wildcard_match(pattern: &str, value: &str) -> bool {
+    let p = pattern.as_bytes();
+    let v = value.as_bytes();
+    let (mut pi, mut vi) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while vi < v.len() {
+        if pi < p.len() && p[pi] == b'*' {
+            star = Some((pi, vi));
+            pi += 1;
+        } else if pi < p.len() && p[pi] == v[vi] {
+            pi += 1;
+            vi += 1;
+        } else if let Some((star_pi, star_vi)) = star {
+            pi = star_pi + 1;
+            vi = star_vi + 1;
+            star = Some((star_pi, star_vi + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|c| *c == b'*')
+}
+
+///
+/// 配置变更事件，在raft日志应用后由ConfigActor产生
+#[derive(Debug, Clone)]
+pub struct ConfigChangeEvent {
+    pub key: ConfigKey,
+    pub event_type: &'static str,
+    pub op_user: Option<Arc<String>>,
+    pub op_time: i64,
+    pub old_md5: Option<Arc<String>>,
+    pub new_md5: Option<Arc<String>>,
+    pub old_content: Option<Arc<String>>,
+    pub new_content: Option<Arc<String>>,
+}
+
+impl ConfigChangeEvent {
+    fn build_payload(&self, with_diff: bool) -> ConfigWebhookPayload {
+        let diff = if with_diff
+            && ConfigEncryptionManager::parse_algorithm_name(&self.key.data_id).is_none()
+        {
+            let empty = String::new();
+            let old_content = self
+                .old_content
+                .as_ref()
+                .map(|e| e.as_str())
+                .unwrap_or(&empty);
+            let new_content = self
+                .new_content
+                .as_ref()
+                .map(|e| e.as_str())
+                .unwrap_or(&empty);
+            let (diff, _) = ConfigDiffUtils::unified_diff(
+                self.old_md5
+                    .as_ref()
+                    .map(|e| e.as_str())
+                    .unwrap_or_default(),
+                old_content,
+                self.new_md5
+                    .as_ref()
+                    .map(|e| e.as_str())
+                    .unwrap_or_default(),
+                new_content,
+                3,
+            );
+            Some(diff)
+        } else {
+            None
+        };
+        ConfigWebhookPayload {
+            tenant: self.key.tenant.clone(),
+            group: self.key.group.clone(),
+            data_id: self.key.data_id.clone(),
+            event_type: self.event_type.to_owned(),
+            op_user: self.op_user.clone(),
+            op_time: self.op_time,
+            old_md5: self.old_md5.clone(),
+            new_md5: self.new_md5.clone(),
+            diff,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigWebhookPayload {
+    pub tenant: Arc<String>,
+    pub group: Arc<String>,
+    pub data_id: Arc<String>,
+    ///SET或REMOVE
+    pub event_type: String,
+    pub op_user: Option<Arc<String>>,
+    pub op_time: i64,
+    pub old_md5: Option<Arc<String>>,
+    pub new_md5: Option<Arc<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diff: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigWebhookDeliveryLog {
+    ///投递完成时间(毫秒)*1000起始的递增id，同时作为表key，按投递时间有序
+    pub id: u64,
+    pub webhook_name: String,
+    pub url: String,
+    pub tenant: Arc<String>,
+    pub group: Arc<String>,
+    pub data_id: Arc<String>,
+    pub event_type: String,
+    pub success: bool,
+    ///请求次数，包含重试
+    pub attempts: u32,
+    pub status_code: Option<u16>,
+    pub error: Option<String>,
+    pub create_time: i64,
+    pub finish_time: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigWebhookLogQueryParam {
+    pub webhook_name: Option<String>,
+    pub data_id: Option<String>,
+    pub success: Option<bool>,
+    pub offset: usize,
+    pub limit: usize,
+}
+
+impl ConfigWebhookDeliveryLog {
+    pub fn to_bytes(&self) -> anyhow::Result<Vec<u8>> {
+        Ok(serde_json::to_vec(self)?)
+    }
+
+    pub fn from_bytes(v: &[u8]) -> anyhow::Result<Self> {
+        Ok(serde_json::from_slice(v)?)
+    }
+}
+
+impl ConfigWebhookLogQueryParam {
+    fn is_match(&self, log: &ConfigWebhookDeliveryLog) -> bool {
+        if let Some(name) = &self.webhook_name {
+            if !name.is_empty() && name != &log.webhook_name {
+                return false;
+            }
+        }
+        if let Some(data_id) = &self.data_id {
+            if !data_id.is_empty() && data_id != log.data_id.as_str() {
+                return false;
+            }
+        }
+        match self.success {
+            Some(success) => success == log.success,
+            None => true,
+        }
+    }
+}
+
+///
+/// webhook投递，失败时按1s、2s、4s...退避重试
+pub struct ConfigWebhookSender {
+    client: reqwest::Client,
+    max_retry: u32,
+    backoff: Duration,
+}
+
+impl ConfigWebhookSender {
+    pub fn new(timeout: Duration, max_retry: u32, backoff: Duration) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .unwrap_or_default();
+        Self {
+            client,
+            max_retry,
+            backoff,
+        }
+    }
+
+    pub async fn deliver(
+        &self,
+        webhook: &ConfigWebhookDO,
+        payload: &ConfigWebhookPayload,
+    ) -> ConfigWebhookDeliveryLog {
+        let mut log = ConfigWebhookDeliveryLog {
+            webhook_name: webhook.name.to_owned(),
+            url: webhook.url.to_owned(),
+            tenant: payload.tenant.clone(),
+            group: payload.group.clone(),
+            data_id: payload.data_id.clone(),
+            event_type: payload.event_type.to_owned(),
+            create_time: now_millis_i64(),
+            ..Default::default()
+        };
+        let mut backoff = self.backoff;
+        loop {
+            log.attempts += 1;
+            match self.client.post(&webhook.url).json(payload).send().await {
+                Ok(resp) => {
+                    log.status_code = Some(resp.status().as_u16());
+                    if resp.status().is_success() {
+                        log.success = true;
+                        log.error = None;
+                        break;
+                    }
+                    log.error = Some(format!("response status {}", resp.status()));
+                }
+                Err(err) => {
+                    log.status_code = None;
+                    log.error = Some(err.to_string());
+                }
+            }
+            if log.attempts > self.max_retry {
+                break;
+            }
+            tokio::time::sleep(backoff).await;
+            backoff *= 2;
+        }
+        log.finish_time = now_millis_i64();
+        log
+    }
+}
+
+#[bean(inject)]
+pub struct ConfigWebhookManager {
+    raft_addr_route: Option<Arc<RaftAddrRouter>>,
+    raft_table_route: Option<Arc<TableRoute>>,
+    table_manager: Option<Addr<TableManager>>,
+    sender: Arc<ConfigWebhookSender>,
+    ///投递记录写入raft表，表中只保留最近log_size条，为0时不记录
+    log_size: usize,
+    last_log_id: u64,
+}
+
+impl ConfigWebhookManager {
+    pub fn new(sys_config: &AppSysConfig) -> Self {
+        let sender = ConfigWebhookSender::new(
+            Duration::from_millis(sys_config.config_webhook_timeout_ms),
+            sys_config.config_webhook_max_retry,
+            Duration::from_secs(1),
+        );
+        Self {
+            raft_addr_route: None,
+            raft_table_route: None,
+            table_manager: None,
+            sender: Arc::new(sender),
+            log_size: sys_config.config_webhook_log_size,
+            last_log_id: 0,
+        }
+    }
+
+    async fn query_webhook_items(
+        table_manager: &Option<Addr<TableManager>>,
+        req: TableManagerQueryReq,
+    ) -> anyhow::Result<Vec<(Vec<u8>, ConfigWebhookDO)>> {
+        let table_manager = match table_manager {
+            Some(v) => v,
+            None => return Ok(vec![]),
+        };
+        match table_manager.send(req).await?? {
+            TableManagerResult::PageListResult(_, list) => {
+                let mut webhooks = Vec::with_capacity(list.len());
+                for (k, v) in list {
+                    webhooks.push((k, ConfigWebhookDO::from_bytes(&v)?));
+                }
+                Ok(webhooks)
+            }
+            _ => Ok(vec![]),
+        }
+    }
+
+    async fn query_webhooks(
+        table_manager: &Option<Addr<TableManager>>,
+    ) -> anyhow::Result<Vec<ConfigWebhookDO>> {
+        let req = TableManagerQueryReq::QueryPageList {
+            table_name: CONFIG_WEBHOOK_TREE_NAME.clone(),
+            like_key: None,
+            offset: None,
+            limit: None,
+            is_rev: false,
+        };
+        let items = Self::query_webhook_items(table_manager, req).await?;
+        Ok(items.into_iter().map(|(_, v)| v).collect())
+    }
+
+    ///
+    /// 按配置的命名空间、分组前缀查询可能匹配的webhook，不扫描整个表
+    async fn query_match_webhooks(
+        table_manager: &Option<Addr<TableManager>>,
+        key: &ConfigKey,
+    ) -> anyhow::Result<Vec<ConfigWebhookDO>> {
+        let mut webhooks = vec![];
+        for prefix in ConfigWebhookDO::build_match_prefixes(key) {
+            let req = TableManagerQueryReq::QueryPrefixPageList {
+                table_name: CONFIG_WEBHOOK_TREE_NAME.clone(),
+                prefix: prefix.into_bytes(),
+                offset: None,
+                limit: None,
+                is_rev: false,
+            };
+            for (_, webhook) in Self::query_webhook_items(table_manager, req).await? {
+                if webhook.is_match(key) {
+                    webhooks.push(webhook);
+                }
+            }
+        }
+        Ok(webhooks)
+    }
+
+    ///
+    /// 按名称查找webhook的表key，只在维护webhook时调用
+    async fn find_webhook_key(
+        table_manager: &Option<Addr<TableManager>>,
+        name: &str,
+    ) -> anyhow::Result<Option<Vec<u8>>> {
+        let req = TableManagerQueryReq::QueryPageList {
+            table_name: CONFIG_WEBHOOK_TREE_NAME.clone(),
+            like_key: None,
+            offset: None,
+            limit: None,
+            is_rev: false,
+        };
+        Ok(Self::query_webhook_items(table_manager, req)
+            .await?
+            .into_iter()
+            .find(|(_, v)| v.name == name)
+            .map(|(k, _)| k))
+    }
+
+    ///
+    /// 只由主节点投递，避免每个节点应用日志后重复通知
+    async fn notify(
+        event: ConfigChangeEvent,
+        raft_addr_route: Option<Arc<RaftAddrRouter>>,
+        table_manager: Option<Addr<TableManager>>,
+        sender: Arc<ConfigWebhookSender>,
+        self_addr: Addr<Self>,
+    ) -> anyhow::Result<()> {
+        match &raft_addr_route {
+            Some(route) => {
+                if !matches!(route.get_route_addr().await?, RouteAddr::Local) {
+                    return Ok(());
+                }
+            }
+            None => return Ok(()),
+        }
+        for webhook in Self::query_match_webhooks(&table_manager, &event.key).await? {
+            let payload = event.build_payload(webhook.with_diff);
+            let sender = sender.clone();
+            let self_addr = self_addr.clone();
+            actix::spawn(async move {
+                let log = sender.deliver(&webhook, &payload).await;
+                if !log.success {
+                    log::warn!(
+                        "config webhook {} deliver failed,{:?}",
+                        &log.webhook_name,
+                        &log.error
+                    );
+                }
+                self_addr.do_send(ConfigWebhookCmd::AddDeliveryLog(log));
+            });
+        }
+        Ok(())
+    }
+
+    fn route_none_err() -> anyhow::Error {
+        anyhow::anyhow!("raft table route is none, webhook can't be saved")
+    }
+
+    ///
+    /// 以投递完成时间为前缀生成id，主节点切换后新记录仍排在旧记录之后
+    fn next_log_id(&mut self, finish_time: i64) -> u64 {
+        self.last_log_id = max(self.last_log_id + 1, finish_time.max(0) as u64 * 1000);
+        self.last_log_id
+    }
+
+    async fn save_delivery_log(
+        raft_table_route: Option<Arc<TableRoute>>,
+        log: ConfigWebhookDeliveryLog,
+    ) -> anyhow::Result<()> {
+        let raft_table_route = raft_table_route.ok_or_else(Self::route_none_err)?;
+        let req = TableManagerReq::Set {
+            table_name: CONFIG_WEBHOOK_LOG_TREE_NAME.clone(),
+            key: id_to_bin(log.id),
+            value: log.to_bytes()?,
+            last_seq_id: None,
+        };
+        raft_table_route.request(req).await
+    }
+
+    ///
+    /// 按投递时间倒序分页查询
+    async fn query_delivery_log(
+        table_manager: &Option<Addr<TableManager>>,
+        param: &ConfigWebhookLogQueryParam,
+    ) -> anyhow::Result<(usize, Vec<ConfigWebhookDeliveryLog>)> {
+        let table_manager = match table_manager {
+            Some(v) => v,
+            None => return Ok((0, vec![])),
+        };
+        let req = TableManagerQueryReq::QueryPageList {
+            table_name: CONFIG_WEBHOOK_LOG_TREE_NAME.clone(),
+            like_key: None,
+            offset: None,
+            limit: None,
+            is_rev: true,
+        };
+        let mut size = 0;
+        let mut list = vec![];
+        if let TableManagerResult::PageListResult(_, items) = table_manager.send(req).await?? {
+            for (_, v) in items {
+                let log = ConfigWebhookDeliveryLog::from_bytes(&v)?;
+                if !param.is_match(&log) {
+                    continue;
+                }
+                if size >= param.offset && list.len() < param.limit {
+                    list.push(log);
+                }
+                size += 1;
+            }
+        }
+        Ok((size, list))
+    }
+}
+
+impl Actor for ConfigWebhookManager {
+    type Context = Context<Self>;
+
+    fn started(&mut self, _ctx: &mut Self::Context) {
+        log::info!("ConfigWebhookManager started")
+    }
+}
+
+impl Inject for ConfigWebhookManager {
+    type Context = Context<Self>;
+
+    fn inject(
+        &mut self,
+        factory_data: bean_factory::FactoryData,
+        _factory: bean_factory::BeanFactory,
+        _ctx: &mut Self::Context,
+    ) {
+        self.raft_addr_route = factory_data.get_bean();
+        self.raft_table_route = factory_data.get_bean();
+        self.table_manager = factory_data.get_actor();
+    }
+}
+
+#[derive(Message)]
+#[rtype(result = "anyhow::Result<ConfigWebhookResult>")]
+pub enum ConfigWebhookCmd {
+    Notify(Box<ConfigChangeEvent>),
+    AddDeliveryLog(ConfigWebhookDeliveryLog),
+    Set(ConfigWebhookDO),
+    Remove(String),
+    QueryList,
+    QueryDeliveryLog(ConfigWebhookLogQueryParam),
+}
+
+pub enum ConfigWebhookResult {
+    None,
+    WebhookList(Vec<ConfigWebhookDO>),
+    DeliveryLogPage(usize, Vec<ConfigWebhookDeliveryLog>),
+}
+
+impl Handler<ConfigWebhookCmd> for ConfigWebhookManager {
+    type Result = ResponseActFuture<Self, anyhow::Result<ConfigWebhookResult>>;
+
+    fn handle(&mut self, mut msg: ConfigWebhookCmd, ctx: &mut Self::Context) -> Self::Result {
+        let raft_addr_route = self.raft_addr_route.clone();
+        let raft_table_route = self.raft_table_route.clone();
+        let table_manager = self.table_manager.clone();
+        let sender = self.sender.clone();
+        let self_addr = ctx.address();
+        if let ConfigWebhookCmd::AddDeliveryLog(log) = &mut msg {
+            if self.log_size == 0 {
+                return Box::pin(async { Ok(ConfigWebhookResult::None) }.into_actor(self));
+            }
+            log.id = self.next_log_id(log.finish_time);
+        }
+        let fut = async move {
+            match msg {
+                ConfigWebhookCmd::Notify(event) => {
+                    Self::notify(*event, raft_addr_route, table_manager, sender, self_addr).await?;
+                    Ok(ConfigWebhookResult::None)
+                }
+                ConfigWebhookCmd::Set(mut webhook) => {
+                    webhook.check()?;
+                    let raft_table_route = raft_table_route.ok_or_else(Self::route_none_err)?;
+                    webhook.gmt_modified = now_millis_i64();
+                    let key = webhook.build_key();
+                    let old_key = Self::find_webhook_key(&table_manager, &webhook.name).await?;
+                    let req = TableManagerReq::Set {
+                        table_name: CONFIG_WEBHOOK_TREE_NAME.clone(),
+                        key: key.clone(),
+                        value: webhook.to_bytes()?,
+                        last_seq_id: None,
+                    };
+                    raft_table_route.request(req).await?;
+                    //tenant或group变更后key随之变更，移除旧记录
+                    if let Some(old_key) = old_key.filter(|e| e != &key) {
+                        let req = TableManagerReq::Remove {
+                            table_name: CONFIG_WEBHOOK_TREE_NAME.clone(),
+                            key: old_key,
+                        };
+                        raft_table_route.request(req).await?;
+                    }
+                    Ok(ConfigWebhookResult::None)
+                }
+                ConfigWebhookCmd::Remove(name) => {
+                    let raft_table_route = raft_table_route.ok_or_else(Self::route_none_err)?;
+                    if let Some(key) = Self::find_webhook_key(&table_manager, &name).await? {
+                        let req = TableManagerReq::Remove {
+                            table_name: CONFIG_WEBHOOK_TREE_NAME.clone(),
+                            key,
+                        };
+                        raft_table_route.request(req).await?;
+                    }
+                    Ok(ConfigWebhookResult::None)
+                }
+                ConfigWebhookCmd::QueryList => Ok(ConfigWebhookResult::WebhookList(
+                    Self::query_webhooks(&table_manager).await?,
+                )),
+                ConfigWebhookCmd::AddDeliveryLog(log) => {
+                    if let Err(err) = Self::save_delivery_log(raft_table_route, log).await {
+                        log::warn!("save config webhook delivery log error,{}", err);
+                    }
+                    Ok(ConfigWebhookResult::None)
+                }
+                ConfigWebhookCmd::QueryDeliveryLog(param) => {
+                    let (size, list) = Self::query_delivery_log(&table_manager, &param).await?;
+                    Ok(ConfigWebhookResult::DeliveryLogPage(size, list))
+                }
+            }
+        }
+        .into_actor(self)
+        .map(|r, _act, _ctx| r);
+        Box::pin(fut)
+    }
+}
+
+#[test]
+fn webhook_match_config() {
+    let webhook = ConfigWebhookDO {
+        name: "ci".to_owned(),
+        url: "http://127.0.0.1/hook".to_owned(),
+        tenant: Some("prod".to_owned()),
+        data_id_pattern: Some("app-*.yaml".to_owned()),
+        enable: true,
+        ..Default::default()
+    };
+    assert!(webhook.check().is_ok());
+    assert!(webhook.is_match(&ConfigKey::new("app-order.yaml", "DEFAULT_GROUP", "prod")));
+    assert!(!webhook.is_match(&ConfigKey::new("app-order.yaml", "DEFAULT_GROUP", "dev")));
+    assert!(!webhook.is_match(&ConfigKey::new("app-order.yml", "DEFAULT_GROUP", "prod")));
+    assert_eq!(webhook.build_key(), "prod\x01\x01ci".as_bytes().to_owned());
+    let prefixes =
+        ConfigWebhookDO::build_match_prefixes(&ConfigKey::new("app.yaml", "DEFAULT_GROUP", "prod"));
+    assert_eq!(prefixes.len(), 4);
+    assert!(prefixes.contains(&"prod\x01\x01".to_owned()));
+    let prefixes = ConfigWebhookDO::build_match_prefixes(&ConfigKey::new("app.yaml", "", ""));
+    assert_eq!(prefixes, vec!["\x01\x01".to_owned()]);
+    assert!(wildcard_match("*", ""));
+    assert!(wildcard_match("a*b*c", "a-b-b-c"));
+    assert!(!wildcard_match("a*b", "a-c"));
+}
+
+#[test]
+fn webhook_delivery_log_id() {
+    let mut manager = ConfigWebhookManager::new(&crate::common::APP_SYS_CONFIG);
+    let a = manager.next_log_id(1000);
+    let b = manager.next_log_id(1000);
+    //时间回退时id仍然递增
+    let c = manager.next_log_id(999);
+    let d = manager.next_log_id(2000);
+    assert_eq!(a, 1_000_000);
+    assert!(a < b && b < c && c < d);
+    assert_eq!(d, 2_000_000);
+    assert!(id_to_bin(c) < id_to_bin(d));
+    let log = ConfigWebhookDeliveryLog {
+        id: d,
+        webhook_name: "ci".to_owned(),
+        ..Default::default()
+    };
+    let v = ConfigWebhookDeliveryLog::from_bytes(&log.to_bytes().unwrap()).unwrap();
+    assert_eq!(v.id, d);
+    assert_eq!(v.webhook_name, "ci");
+}
+
+#[cfg(test)]
+async fn read_http_request(stream: &mut tokio::net::TcpStream) -> String {
+    use tokio::io::AsyncReadExt;
+    let mut data = vec![];
+    let mut buf = vec![0u8; 4096];
+    loop {
+        let n = stream.read(&mut buf).await.unwrap();
+        data.extend_from_slice(&buf[..n]);
+        let text = String::from_utf8_lossy(&data).to_string();
+        if let Some(index) = text.find("\r\n\r\n") {
+            let content_length = text[..index]
+                .lines()
+                .filter_map(|line| line.split_once(':'))
+                .find(|(k, _)| k.eq_ignore_ascii_case("content-length"))
+                .map(|(_, v)| v.trim().parse::<usize>().unwrap())
+                .unwrap_or(0);
+            if data.len() >= index + 4 + content_length {
+                return text;
+            }
+        }
+        if n == 0 {
+            return text;
+        }
+    }
+}
+
+#[actix_rt::test]
+async fn webhook_deliver_with_retry() {
+    use tokio::io::AsyncWriteExt;
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/hook", listener.local_addr().unwrap());
+    let receiver = tokio::spawn(async move {
+        let mut bodies = vec![];
+        //第一次返回500，第二次返回200
+        for status in ["500 Internal Server Error", "200 OK"].iter() {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            bodies.push(read_http_request(&mut stream).await);
+            let resp = format!(
+                "HTTP/1.1 {}\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+                status
+            );
+            stream.write_all(resp.as_bytes()).await.unwrap();
+        }
+        bodies
+    });
+    let webhook = ConfigWebhookDO {
+        name: "ci".to_owned(),
+        url,
+        enable: true,
+        ..Default::default()
+    };
+    let event = ConfigChangeEvent {
+        key: ConfigKey::new("app.yaml", "DEFAULT_GROUP", ""),
+        event_type: CONFIG_EVENT_SET,
+        op_user: Some(Arc::new("admin".to_owned())),
+        op_time: 1,
+        old_md5: Some(Arc::new("m1".to_owned())),
+        new_md5: Some(Arc::new("m2".to_owned())),
+        old_content: Some(Arc::new("a=1\n".to_owned())),
+        new_content: Some(Arc::new("a=2\n".to_owned())),
+    };
+    let sender = ConfigWebhookSender::new(Duration::from_secs(3), 2, Duration::from_millis(10));
+    let log = sender.deliver(&webhook, &event.build_payload(true)).await;
+    assert!(log.success);
+    assert_eq!(log.attempts, 2);
+    assert_eq!(log.status_code, Some(200));
+    let bodies = receiver.await.unwrap();
+    assert!(bodies[1].contains("\"newMd5\":\"m2\""));
+    assert!(bodies[1].contains("+a=2"));
+}
@@ -9,21 +9,38 @@ use std::sync::Arc;
 use std::sync::Weak;
 use std::time::Duration;
 
-use crate::raft::store::ClientRequest;
+use crate::raft::cluster::model::SetConfigReq;
+use crate::raft::store::{ClientRequest, ClientResponse};
 use crate::raft::NacosRaft;
 use crate::utils::get_md5;
 use serde::{Deserialize, Serialize};
 
 use crate::common::byte_utils::id_to_bin;
-use crate::common::constant::{CONFIG_TREE_NAME, SEQUENCE_TREE_NAME, SEQ_KEY_CONFIG};
+use crate::common::constant::{
+    CONFIG_HISTORY_TREE_NAME, CONFIG_RECYCLE_TREE_NAME, CONFIG_TREE_NAME, SEQUENCE_TREE_NAME,
+    SEQ_KEY_CONFIG,
+};
 use crate::common::sequence_utils::SimpleSequence;
 use actix::prelude::*;
 
-use super::config_subscribe::Subscriber;
-use super::dal::ConfigHistoryParam;
-use crate::config::config_index::{ConfigQueryParam, TenantIndex};
-use crate::config::model::{ConfigRaftCmd, ConfigRaftResult, ConfigValueDO, HistoryItem};
+use super::config_history::ConfigHistoryUtils;
+use super::config_include::{ConfigIncludeGraph, ConfigIncludeGraphParam, ConfigIncludeIndex};
+use super::config_recycle::{ConfigRecycleDO, ConfigRecycleUtils};
+use super::config_subscribe::{Subscriber, WatchSenderType};
+use super::config_webhook::{
+    ConfigChangeEvent, ConfigWebhookCmd, ConfigWebhookManager, CONFIG_EVENT_REMOVE,
+    CONFIG_EVENT_SET,
+};
+use crate::config::config_encryption::CONFIG_ENCRYPTION;
+use crate::config::config_index::{ConfigIndexMeta, ConfigQueryParam, TenantIndex};
+use crate::config::config_search::{ConfigContentIndex, ConfigSearchParam};
+use crate::config::model::{
+    ConfigBetaInfoDto, ConfigBetaRule, ConfigBetaValue, ConfigClientInfo, ConfigError,
+    ConfigHistoryItemDO, ConfigListenerInfoDto, ConfigListenerQueryParam, ConfigRaftCmd,
+    ConfigRaftResult, ConfigValueDO, HistoryItem, CAS_MD5_NOT_EXIST,
+};
 use crate::now_millis_i64;
+use crate::raft::db::table::{TableManager, TableManagerReq};
 use crate::raft::filestore::model::SnapshotRecordDto;
 use crate::raft::filestore::raftsnapshot::{SnapshotWriterActor, SnapshotWriterRequest};
 
@@ -84,7 +101,15 @@ pub struct ConfigValue {
     pub(crate) content: Arc<String>,
     pub(crate) md5: Arc<String>,
     pub(crate) tmp: bool,
-    pub(crate) histories: Vec<HistoryItem>,
+    ///最后一条历史记录，完整历史记录保存在历史记录表中
+    pub(crate) last_history: Option<HistoryItem>,
+    pub(crate) beta: Option<ConfigBetaValue>,
+    pub(crate) config_type: Option<Arc<String>>,
+    pub(crate) desc: Option<Arc<String>>,
+    pub(crate) config_tags: Option<Arc<String>>,
+    pub(crate) app_name: Option<Arc<String>>,
+    ///加密配置的数据密钥，为空表示内容未加密
+    pub(crate) encrypted_data_key: Option<Arc<String>>,
 }
 
 impl ConfigValue {
@@ -94,7 +119,13 @@ impl ConfigValue {
             content,
             md5: Arc::new(md5),
             tmp: false,
-            histories: vec![],
+            last_history: None,
+            beta: None,
+            config_type: None,
+            desc: None,
+            config_tags: None,
+            app_name: None,
+            encrypted_data_key: None,
         }
     }
 
@@ -104,6 +135,7 @@ impl ConfigValue {
         op_time: i64,
         md5: Option<Arc<String>>,
         op_user: Option<Arc<String>>,
+        encrypted_data_key: Option<Arc<String>>,
     ) -> Self {
         let md5 = if let Some(v) = md5 {
             v
@@ -114,12 +146,19 @@ impl ConfigValue {
             content: content.clone(),
             md5,
             tmp: false,
-            histories: vec![HistoryItem {
+            last_history: Some(HistoryItem {
                 id: history_id,
                 content,
                 modified_time: op_time,
                 op_user,
-            }],
+                encrypted_data_key: encrypted_data_key.clone(),
+            }),
+            beta: None,
+            config_type: None,
+            desc: None,
+            config_tags: None,
+            app_name: None,
+            encrypted_data_key,
         }
     }
 
@@ -130,6 +169,7 @@ impl ConfigValue {
         op_time: i64,
         md5: Option<Arc<String>>,
         op_user: Option<Arc<String>>,
+        encrypted_data_key: Option<Arc<String>>,
     ) {
         let md5 = if let Some(v) = md5 {
             v
@@ -139,16 +179,144 @@ impl ConfigValue {
         self.md5 = md5;
         self.content = content.clone();
         self.tmp = false;
+        self.encrypted_data_key = encrypted_data_key.clone();
         let item = HistoryItem {
             id: history_id,
             content,
             modified_time: op_time,
             op_user,
+            encrypted_data_key,
         };
-        if self.histories.len() >= 100 {
-            self.histories.remove(0);
+        self.last_history = Some(item);
+    }
+
+    ///
+    /// 更新配置元数据，None表示不变更，空字符串表示清除
+    pub fn update_meta(
+        &mut self,
+        config_type: Option<Arc<String>>,
+        desc: Option<Arc<String>>,
+        config_tags: Option<Arc<String>>,
+        app_name: Option<Arc<String>>,
+    ) {
+        Self::merge_meta_value(&mut self.config_type, config_type);
+        Self::merge_meta_value(&mut self.desc, desc);
+        let config_tags = config_tags.map(|v| {
+            let tags: Vec<String> = ConfigIndexMeta::split_tags(&v)
+                .into_iter()
+                .map(|e| e.as_ref().to_owned())
+                .collect();
+            Arc::new(tags.join(","))
+        });
+        Self::merge_meta_value(&mut self.config_tags, config_tags);
+        Self::merge_meta_value(&mut self.app_name, app_name);
+    }
+
+    fn merge_meta_value(old: &mut Option<Arc<String>>, new: Option<Arc<String>>) {
+        if let Some(v) = new {
+            if v.is_empty() {
+                *old = None;
+            } else {
+                *old = Some(v);
+            }
+        }
+    }
+
+    ///
+    /// 加密配置返回解密后的内容，解密失败时返回原内容
+    pub fn get_plain_content(&self, key: &ConfigKey) -> Arc<String> {
+        match CONFIG_ENCRYPTION.decrypt(
+            &key.data_id,
+            self.encrypted_data_key.as_ref(),
+            self.content.clone(),
+        ) {
+            Ok(v) => v,
+            Err(err) => {
+                log::warn!("config decrypt error,{:?},{}", key, err);
+                self.content.clone()
+            }
+        }
+    }
+
+    ///
+    /// 加密配置的md5按明文计算，与客户端读取到的内容保持一致
+    pub fn get_plain_md5(
+        key: &ConfigKey,
+        content: &Arc<String>,
+        encrypted_data_key: Option<&Arc<String>>,
+    ) -> String {
+        if encrypted_data_key.is_none() {
+            return get_md5(content);
+        }
+        match CONFIG_ENCRYPTION.decrypt(&key.data_id, encrypted_data_key, content.clone()) {
+            Ok(v) => get_md5(&v),
+            Err(err) => {
+                log::warn!("config decrypt error,{:?},{}", key, err);
+                get_md5(content)
+            }
+        }
+    }
+
+    pub fn build_index_meta(&self) -> ConfigIndexMeta {
+        ConfigIndexMeta::new(self.app_name.clone(), self.config_tags.as_ref())
+    }
+
+    ///
+    /// 返回客户端命中的灰度内容
+    pub fn match_beta(&self, client: &ConfigClientInfo) -> Option<&ConfigBetaValue> {
+        if let Some(beta) = &self.beta {
+            if beta.rule.is_match(client) {
+                return Some(beta);
+            }
+        }
+        None
+    }
+
+    pub fn to_query_data(&self) -> ConfigQueryData {
+        ConfigQueryData {
+            content: self.content.clone(),
+            md5: self.md5.clone(),
+            config_type: self.config_type.clone(),
+            beta: false,
+            encrypted_data_key: self.encrypted_data_key.clone(),
+            last_modified: self
+                .last_history
+                .as_ref()
+                .map(|e| e.modified_time)
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn get_client_md5(&self, client: &ConfigClientInfo) -> &Arc<String> {
+        if let Some(beta) = self.match_beta(client) {
+            &beta.md5
+        } else {
+            &self.md5
+        }
+    }
+
+    ///
+    /// 灰度内容转正式发布，灰度信息同时移除
+    pub fn publish_beta(
+        &mut self,
+        history_id: u64,
+        op_time: i64,
+        op_user: Option<Arc<String>>,
+    ) -> bool {
+        if let Some(beta) = self.beta.take() {
+            let op_user = op_user.or(beta.op_user);
+            self.update_value(
+                beta.content,
+                history_id,
+                op_time,
+                Some(beta.md5),
+                op_user,
+                None,
+            );
+            true
+        } else {
+            false
         }
-        self.histories.push(item);
     }
 }
 
@@ -160,6 +328,23 @@ pub struct ConfigInfoDto {
     pub data_id: Arc<String>,
     pub content: Option<Arc<String>>,
     pub md5: Option<Arc<String>>,
+    #[serde(rename = "type")]
+    pub config_type: Option<Arc<String>>,
+    pub desc: Option<Arc<String>>,
+    pub config_tags: Option<Arc<String>>,
+    pub app_name: Option<Arc<String>>,
+}
+
+///
+/// 按客户端查询到的配置内容
+#[derive(Debug, Clone, Default)]
+pub struct ConfigQueryData {
+    pub content: Arc<String>,
+    pub md5: Arc<String>,
+    pub config_type: Option<Arc<String>>,
+    pub beta: bool,
+    pub last_modified: i64,
+    pub encrypted_data_key: Option<Arc<String>>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Default, Clone)]
@@ -171,6 +356,7 @@ pub struct ConfigHistoryInfoDto {
     pub data_id: Option<String>,
     pub content: Option<String>,
     pub modified_time: Option<i64>, //给历史记录使用
+    pub op_user: Option<String>,
 }
 
 #[derive(Debug)]
@@ -266,11 +452,19 @@ pub enum ListenerResult {
 type ListenerSenderType = tokio::sync::oneshot::Sender<ListenerResult>;
 //type ListenerReceiverType = tokio::sync::oneshot::Receiver<ListenerResult>;
 
+///
+/// http长轮询客户端信息，用于查询监听
+struct HttpListenerClient {
+    client: ConfigClientInfo,
+    items: Vec<ListenerItem>,
+}
+
 struct ConfigListener {
     version: u64,
     listener: HashMap<ConfigKey, Vec<u64>>,
     time_listener: BTreeMap<i64, Vec<OnceListener>>,
     sender_map: HashMap<u64, ListenerSenderType>,
+    client_map: HashMap<u64, HttpListenerClient>,
 }
 
 impl ConfigListener {
@@ -280,10 +474,17 @@ impl ConfigListener {
             listener: Default::default(),
             time_listener: Default::default(),
             sender_map: Default::default(),
+            client_map: Default::default(),
         }
     }
 
-    fn add(&mut self, items: Vec<ListenerItem>, sender: ListenerSenderType, time: i64) {
+    fn add(
+        &mut self,
+        items: Vec<ListenerItem>,
+        sender: ListenerSenderType,
+        time: i64,
+        client: ConfigClientInfo,
+    ) {
         self.version += 1;
         for item in &items {
             let key = item.key.clone();
@@ -297,6 +498,8 @@ impl ConfigListener {
             };
         }
         self.sender_map.insert(self.version, sender);
+        self.client_map
+            .insert(self.version, HttpListenerClient { client, items });
         let once_listener = OnceListener {
             version: self.version,
             //time,
@@ -318,6 +521,7 @@ impl ConfigListener {
                 if let Some(sender) = self.sender_map.remove(&v) {
                     sender.send(ListenerResult::DATA(vec![key.clone()])).ok();
                 }
+                self.client_map.remove(&v);
             }
         }
     }
@@ -333,6 +537,7 @@ impl ConfigListener {
                     if let Some(sender) = self.sender_map.remove(&v) {
                         sender.send(ListenerResult::NULL).ok();
                     }
+                    self.client_map.remove(&v);
                 }
             } else {
                 break;
@@ -342,6 +547,37 @@ impl ConfigListener {
             self.time_listener.remove(&key);
         }
     }
+
+    ///
+    /// 按配置或客户端ip查询http长轮询监听信息
+    fn query_listener(&self, param: &ConfigListenerQueryParam) -> Vec<ConfigListenerInfoDto> {
+        let mut list = vec![];
+        if param
+            .conn_id
+            .as_ref()
+            .map(|e| !e.is_empty())
+            .unwrap_or(false)
+        {
+            return list;
+        }
+        let config_key = param.get_config_key();
+        for client in self.client_map.values() {
+            if !param.match_client(&client.client.ip, None) {
+                continue;
+            }
+            for item in &client.items {
+                if config_key.is_none() || config_key.as_ref() == Some(&item.key) {
+                    list.push(ConfigListenerInfoDto::new(
+                        &item.key,
+                        item.md5.clone(),
+                        &client.client,
+                        None,
+                    ));
+                }
+            }
+        }
+        list
+    }
 }
 
 #[bean(inject)]
@@ -350,7 +586,11 @@ pub struct ConfigActor {
     listener: ConfigListener,
     subscriber: Subscriber,
     tenant_index: TenantIndex,
+    content_index: ConfigContentIndex,
+    include_index: ConfigIncludeIndex,
     raft: Option<Weak<NacosRaft>>,
+    table_manager: Option<Addr<TableManager>>,
+    webhook_manager: Option<Addr<ConfigWebhookManager>>,
     sequence: SimpleSequence,
 }
 
@@ -365,6 +605,8 @@ impl Inject for ConfigActor {
     ) {
         let raft: Option<Arc<NacosRaft>> = factory_data.get_bean();
         self.raft = raft.map(|e| Arc::downgrade(&e));
+        self.table_manager = factory_data.get_actor();
+        self.webhook_manager = factory_data.get_actor();
         if let Some(conn_manage) = factory_data.get_actor() {
             self.subscriber.set_conn_manage(conn_manage);
         }
@@ -385,28 +627,66 @@ impl ConfigActor {
             subscriber: Subscriber::new(),
             listener: ConfigListener::new(),
             tenant_index: TenantIndex::new(),
+            content_index: ConfigContentIndex::new(),
+            include_index: ConfigIncludeIndex::new(),
             raft: None,
+            table_manager: None,
+            webhook_manager: None,
             sequence: SimpleSequence::new(0, 100),
         }
     }
 
-    fn set_tmp_config(&mut self, key: ConfigKey, val: Arc<String>) {
+    fn set_tmp_config(
+        &mut self,
+        key: ConfigKey,
+        val: Arc<String>,
+        encrypted_data_key: Option<Arc<String>>,
+    ) {
+        let md5 = ConfigValue::get_plain_md5(&key, &val, encrypted_data_key.as_ref());
         if let Some(v) = self.cache.get_mut(&key) {
             v.tmp = true;
-            v.md5 = Arc::new(get_md5(&val));
+            v.md5 = Arc::new(md5);
             v.content = val;
+            v.encrypted_data_key = encrypted_data_key;
         } else {
             let mut config_val = ConfigValue::new(val);
+            config_val.md5 = Arc::new(md5);
             config_val.tmp = true;
-            self.cache.insert(key, config_val);
+            config_val.encrypted_data_key = encrypted_data_key;
+            self.cache.insert(key.clone(), config_val);
         }
+        self.refresh_include(&key);
     }
 
-    fn inner_set_config(&mut self, key: ConfigKey, value: ConfigValue) {
+    fn inner_set_config(&mut self, key: ConfigKey, mut value: ConfigValue) {
+        if value.encrypted_data_key.is_some() {
+            value.md5 = Arc::new(ConfigValue::get_plain_md5(
+                &key,
+                &value.content,
+                value.encrypted_data_key.as_ref(),
+            ));
+        }
         self.tenant_index.insert_config(key.clone());
-        self.cache.insert(key, value);
+        self.tenant_index
+            .update_config_meta(&key, value.build_index_meta());
+        self.cache.insert(key.clone(), value);
+        self.update_content_index(&key);
+        self.refresh_include(&key);
     }
 
+    ///
+    /// 加密配置不建立内容索引
+    fn update_content_index(&mut self, key: &ConfigKey) {
+        if let Some(v) = self.cache.get(key) {
+            if v.encrypted_data_key.is_none() {
+                self.content_index.insert(key, &v.content);
+                return;
+            }
+        }
+        self.content_index.remove(key);
+    }
+
+    #[allow(clippy::too_many_arguments)]
     fn set_config(
         &mut self,
         key: ConfigKey,
@@ -415,30 +695,291 @@ impl ConfigActor {
         _history_table_id: Option<u64>,
         op_time: i64,
         op_user: Option<Arc<String>>,
+        encrypted_data_key: Option<Arc<String>>,
     ) -> anyhow::Result<ConfigResult> {
+        //加密配置每次发布的数据密钥不同，按明文md5判断内容是否变更
+        let md5 = ConfigValue::get_plain_md5(&key, &val, encrypted_data_key.as_ref());
         if let Some(v) = self.cache.get_mut(&key) {
-            let md5 = get_md5(val.as_str());
             if !v.tmp && v.md5.as_str() == md5 {
                 return Ok(ConfigResult::NULL);
             }
-            if v.histories.is_empty() {
+            if v.last_history.is_none() {
                 self.tenant_index.insert_config(key.clone());
             }
-            v.update_value(val, history_id, op_time, Some(Arc::new(md5)), op_user);
+            v.update_value(
+                val,
+                history_id,
+                op_time,
+                Some(Arc::new(md5)),
+                op_user,
+                encrypted_data_key,
+            );
         } else {
-            let v = ConfigValue::init(val, history_id, op_time, None, op_user);
+            let v = ConfigValue::init(
+                val,
+                history_id,
+                op_time,
+                Some(Arc::new(md5)),
+                op_user,
+                encrypted_data_key,
+            );
             self.cache.insert(key.clone(), v);
             self.tenant_index.insert_config(key.clone());
         }
+        self.update_content_index(&key);
+        self.refresh_include(&key);
+        self.save_history(&key);
         self.listener.notify(key.clone());
         self.subscriber.notify(key);
         Ok(ConfigResult::NULL)
     }
 
+    ///
+    /// 最后一条历史记录写入历史记录表，由历史记录表按保留策略裁剪;
+    /// 由各节点应用raft日志时写入本节点的历史记录表，写入内容只取决于日志，各节点一致
+    fn save_history(&self, key: &ConfigKey) {
+        if let (Some(table_manager), Some(v)) = (&self.table_manager, self.cache.get(key)) {
+            if let Some(item) = &v.last_history {
+                let value_do: ConfigHistoryItemDO = item.clone().into();
+                match value_do.to_bytes() {
+                    Ok(value) => {
+                        table_manager.do_send(TableManagerReq::Set {
+                            table_name: CONFIG_HISTORY_TREE_NAME.clone(),
+                            key: ConfigHistoryUtils::build_key(key, item.id),
+                            value,
+                            last_seq_id: None,
+                        });
+                    }
+                    Err(err) => log::warn!("config history encode error,{:?},{}", key, err),
+                }
+            }
+        }
+    }
+
+    ///
+    /// 删除前将配置写入回收站表，由各节点应用删除日志时写入
+    fn save_recycle(&self, key: &ConfigKey, op_user: Option<Arc<String>>, op_time: i64) {
+        if let (Some(table_manager), Some(v)) = (&self.table_manager, self.cache.get(key)) {
+            let item = ConfigRecycleDO::new(key, v, op_user, op_time);
+            match item.to_bytes() {
+                Ok(value) => {
+                    table_manager.do_send(TableManagerReq::Set {
+                        table_name: CONFIG_RECYCLE_TREE_NAME.clone(),
+                        key: ConfigRecycleUtils::build_key(key, op_time),
+                        value,
+                        last_seq_id: None,
+                    });
+                }
+                Err(err) => log::warn!("config recycle encode error,{:?},{}", key, err),
+            }
+        }
+    }
+
+    ///
+    /// cas_md5为空或配置不存在时不做校验，cas_md5为CAS_MD5_NOT_EXIST时要求配置不存在;
+    /// 校验引用展开前原始内容的md5，与写入内容、历史记录及版本差异比较保持一致;
+    /// 临时值只存在于转发请求的节点，需要用最后一次提交的内容校验，保证各节点结果一致
+    fn check_cas_md5(&self, key: &ConfigKey, cas_md5: Option<&Arc<String>>) -> bool {
+        let cas_md5 = match cas_md5 {
+            Some(v) if !v.is_empty() => v,
+            _ => return true,
+        };
+        if cas_md5.as_str() == CAS_MD5_NOT_EXIST {
+            return match self.cache.get(key) {
+                Some(v) => v.tmp && v.last_history.is_none(),
+                None => true,
+            };
+        }
+        if let Some(v) = self.cache.get(key) {
+            if !v.tmp {
+                return v.md5.as_str() == cas_md5.as_str();
+            }
+            if let Some(item) = &v.last_history {
+                return ConfigValue::get_plain_md5(
+                    key,
+                    &item.content,
+                    item.encrypted_data_key.as_ref(),
+                ) == *cas_md5.as_str();
+            }
+        }
+        true
+    }
+
+    ///
+    /// 返回最后一次提交的(md5,内容)，临时值以最后一条历史记录为准
+    fn get_committed_value(&self, key: &ConfigKey) -> (Option<Arc<String>>, Option<Arc<String>>) {
+        match self.cache.get(key) {
+            Some(v) if !v.tmp => (Some(v.md5.clone()), Some(v.get_plain_content(key))),
+            Some(v) => match &v.last_history {
+                Some(item) => {
+                    let content = CONFIG_ENCRYPTION
+                        .decrypt(
+                            &key.data_id,
+                            item.encrypted_data_key.as_ref(),
+                            item.content.clone(),
+                        )
+                        .unwrap_or_else(|_| item.content.clone());
+                    (Some(Arc::new(get_md5(&content))), Some(content))
+                }
+                None => (None, None),
+            },
+            None => (None, None),
+        }
+    }
+
+    ///
+    /// 配置内容变更后通知webhook，由webhook管理器判断是否为主节点
+    fn notify_webhook(
+        &self,
+        key: ConfigKey,
+        event_type: &'static str,
+        old_value: (Option<Arc<String>>, Option<Arc<String>>),
+        op_user: Option<Arc<String>>,
+        op_time: i64,
+    ) {
+        let webhook_manager = match &self.webhook_manager {
+            Some(v) => v,
+            None => return,
+        };
+        let (new_md5, new_content) = self.get_committed_value(&key);
+        let (old_md5, old_content) = old_value;
+        if old_md5 == new_md5 {
+            return;
+        }
+        let event = ConfigChangeEvent {
+            key,
+            event_type,
+            op_user,
+            op_time,
+            old_md5,
+            new_md5,
+            old_content,
+            new_content,
+        };
+        webhook_manager.do_send(ConfigWebhookCmd::Notify(Box::new(event)));
+    }
+
+    fn update_config_meta(
+        &mut self,
+        key: &ConfigKey,
+        config_type: Option<Arc<String>>,
+        desc: Option<Arc<String>>,
+        config_tags: Option<Arc<String>>,
+        app_name: Option<Arc<String>>,
+    ) {
+        if let Some(v) = self.cache.get_mut(key) {
+            v.update_meta(config_type, desc, config_tags, app_name);
+            self.tenant_index
+                .update_config_meta(key, v.build_index_meta());
+        }
+    }
+
+    fn set_beta_config(
+        &mut self,
+        key: ConfigKey,
+        val: Arc<String>,
+        rule: ConfigBetaRule,
+        op_time: i64,
+        op_user: Option<Arc<String>>,
+    ) -> anyhow::Result<()> {
+        if let Some(v) = self.cache.get_mut(&key) {
+            v.beta = Some(ConfigBetaValue::new(val, rule, op_time, op_user));
+            self.listener.notify(key.clone());
+            self.subscriber.notify(key);
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!("config not exist"))
+        }
+    }
+
+    fn remove_beta_config(&mut self, key: ConfigKey) -> anyhow::Result<()> {
+        if let Some(v) = self.cache.get_mut(&key) {
+            if v.beta.take().is_some() {
+                self.listener.notify(key.clone());
+                self.subscriber.notify(key);
+            }
+        }
+        Ok(())
+    }
+
+    fn publish_beta_config(
+        &mut self,
+        key: ConfigKey,
+        history_id: u64,
+        op_time: i64,
+        op_user: Option<Arc<String>>,
+    ) -> anyhow::Result<()> {
+        if let Some(v) = self.cache.get_mut(&key) {
+            if v.publish_beta(history_id, op_time, op_user) {
+                self.update_content_index(&key);
+                self.refresh_include(&key);
+                self.save_history(&key);
+                self.listener.notify(key.clone());
+                self.subscriber.notify(key);
+            }
+        }
+        Ok(())
+    }
+
+    ///
+    /// 配置内容变更后更新引用关系，并通知引用展开结果变化的配置；加密配置不参与引用展开
+    fn refresh_include(&mut self, key: &ConfigKey) {
+        let cache = &self.cache;
+        let lookup = |k: &ConfigKey| {
+            cache
+                .get(k)
+                .filter(|v| v.encrypted_data_key.is_none())
+                .map(|v| v.content.clone())
+        };
+        let content = lookup(key);
+        self.include_index
+            .update_refs(key, content.as_ref().map(|e| e.as_str()));
+        let changes = self.include_index.refresh(key, lookup);
+        for item in changes {
+            self.listener.notify(item.clone());
+            self.subscriber.notify(item);
+        }
+    }
+
+    ///
+    /// 客户端读取到的md5，命中灰度时为灰度md5，存在引用时为展开后内容的md5
+    fn get_client_md5<'a>(
+        &'a self,
+        key: &ConfigKey,
+        v: &'a ConfigValue,
+        client: &ConfigClientInfo,
+    ) -> &'a Arc<String> {
+        if let Some(beta) = v.match_beta(client) {
+            return &beta.md5;
+        }
+        match self.include_index.get_resolved(key) {
+            Some(resolved) => &resolved.md5,
+            None => &v.md5,
+        }
+    }
+
+    ///
+    /// 返回与客户端md5不一致的配置
+    fn get_change_keys(&self, items: &[ListenerItem], client: &ConfigClientInfo) -> Vec<ConfigKey> {
+        let mut changes = vec![];
+        for item in items {
+            if let Some(v) = self.cache.get(&item.key) {
+                if self.get_client_md5(&item.key, v, client) != &item.md5 {
+                    changes.push(item.key.clone());
+                }
+            } else if !item.md5.is_empty() {
+                changes.push(item.key.clone());
+            }
+        }
+        changes
+    }
+
     fn del_config(&mut self, key: ConfigKey) -> anyhow::Result<()> {
         self.cache.remove(&key);
         //self.config_db.del_config(&key).ok();
         self.tenant_index.remove_config(&key);
+        self.content_index.remove(&key);
+        self.refresh_include(&key);
         self.listener.notify(key.clone());
         self.subscriber.notify(key.clone());
         self.subscriber.remove_config_key(key);
@@ -468,12 +1009,22 @@ impl ConfigActor {
         if let Some(weak_raft) = raft {
             if let Some(raft) = weak_raft.upgrade() {
                 //TODO换成feature,非wait的方式
-                raft.client_write(ClientWriteRequest::new(req)).await?;
+                let resp = raft.client_write(ClientWriteRequest::new(req)).await?;
+                Self::check_client_response(&resp.data)?;
             }
         }
         Ok(())
     }
 
+    ///
+    /// raft日志应用时cas校验失败返回ConfigError::CasConflict
+    fn check_client_response(resp: &ClientResponse) -> anyhow::Result<()> {
+        if let ClientResponse::CasConflict = resp {
+            return Err(ConfigError::CasConflict.into());
+        }
+        Ok(())
+    }
+
     pub fn get_config_info_page(&self, param: &ConfigQueryParam) -> (usize, Vec<ConfigInfoDto>) {
         let (size, list) = self.tenant_index.query_config_page(param);
         let mut info_list = Vec::with_capacity(size);
@@ -483,12 +1034,16 @@ impl ConfigActor {
                     tenant: item.tenant.clone(),
                     group: item.group.clone(),
                     data_id: item.data_id.clone(),
+                    config_type: value.config_type.clone(),
+                    desc: value.desc.clone(),
+                    config_tags: value.config_tags.clone(),
+                    app_name: value.app_name.clone(),
                     //md5:Some(value.md5.clone()),
                     //content:Some(value.content.clone()),
                     ..Default::default()
                 };
                 if param.query_context {
-                    info.content = Some(value.content.clone());
+                    info.content = Some(value.get_plain_content(item));
                     info.md5 = Some(value.md5.clone());
                 }
                 info_list.push(info);
@@ -497,6 +1052,19 @@ impl ConfigActor {
         (size, info_list)
     }
 
+    ///
+    /// 返回可能命中检索条件的配置内容，逐行匹配由调用方在ConfigActor之外执行
+    pub fn search_candidates(&self, param: &ConfigSearchParam) -> Vec<(ConfigKey, Arc<String>)> {
+        self.content_index
+            .candidates(param)
+            .into_iter()
+            .filter_map(|key| {
+                let content = self.cache.get(&key)?.content.clone();
+                Some((key, content))
+            })
+            .collect()
+    }
+
     /*
     pub(crate) fn get_history_info_page_old(
         &self,
@@ -519,33 +1087,23 @@ impl ConfigActor {
      */
 
     ///
-    /// 从内存列表中直接查询历史记录
-    pub(crate) fn get_history_info_page(
-        &self,
-        param: &ConfigHistoryParam,
-    ) -> (usize, Vec<ConfigHistoryInfoDto>) {
-        if let (Some(t), Some(g), Some(id)) = (&param.tenant, &param.group, &param.data_id) {
-            let key = ConfigKey::new(id, g, t);
+    /// 查询本节点的配置监听信息(grpc订阅与http长轮询)，并标记md5过期的监听
+    pub fn query_listener(&self, param: &ConfigListenerQueryParam) -> Vec<ConfigListenerInfoDto> {
+        let mut list = self.subscriber.query_listener(param);
+        list.extend(self.listener.query_listener(param));
+        for item in list.iter_mut() {
+            let key = item.get_config_key();
             if let Some(v) = self.cache.get(&key) {
-                let mut ret = vec![];
-                let iter = v.histories.iter().rev();
-                if let Some(offset) = param.offset {
-                    let n_i = iter.skip(offset as usize);
-                    if let Some(limit) = param.limit {
-                        let t = n_i.take(limit as usize);
-                        for item in t {
-                            ret.push(item.to_dto(&key));
-                        }
-                    } else {
-                        for item in n_i {
-                            ret.push(item.to_dto(&key));
-                        }
-                    }
-                }
-                return (v.histories.len(), ret);
-            };
-        };
-        (0, vec![])
+                let server_md5 = self
+                    .get_client_md5(&key, v, &item.get_client_info())
+                    .clone();
+                item.stale = server_md5 != item.md5;
+                item.server_md5 = Some(server_md5);
+            } else {
+                item.stale = !item.md5.is_empty();
+            }
+        }
+        list
     }
 
     ///
@@ -554,12 +1112,11 @@ impl ConfigActor {
     fn build_snapshot(&self, writer: Addr<SnapshotWriterActor>) -> anyhow::Result<()> {
         for (key, value) in &self.cache {
             let value_db: ConfigValueDO = value.clone().into();
-            let record = SnapshotRecordDto {
-                tree: CONFIG_TREE_NAME.clone(),
-                key: key.build_key().as_bytes().to_vec(),
-                value: value_db.to_bytes()?,
-                op_type: 0,
-            };
+            let record = SnapshotRecordDto::new_with_compress(
+                CONFIG_TREE_NAME.clone(),
+                key.build_key().as_bytes().to_vec(),
+                value_db.to_bytes()?,
+            )?;
             writer.do_send(SnapshotWriterRequest::Record(record));
         }
         let seq_record = SnapshotRecordDto {
@@ -585,14 +1142,29 @@ impl ConfigActor {
 pub enum ConfigCmd {
     //ADD(ConfigKey, Arc<String>),
     //DELETE(ConfigKey),
-    SetTmpValue(ConfigKey, Arc<String>),
+    SetTmpValue(ConfigKey, Arc<String>, Option<Arc<String>>),
     InnerSet(ConfigKey, ConfigValue),
     InnerSetLastId(u64),
     GET(ConfigKey),
+    GetByClient(ConfigKey, ConfigClientInfo),
+    ///返回引用展开前的原始内容，不匹配灰度
+    GetRaw(ConfigKey),
+    ///发布前检查配置引用是否形成循环
+    CheckInclude(ConfigKey, Arc<String>),
+    GetBeta(ConfigKey),
     QueryPageInfo(Box<ConfigQueryParam>),
-    QueryHistoryPageInfo(Box<ConfigHistoryParam>),
-    LISTENER(Vec<ListenerItem>, ListenerSenderType, i64),
-    Subscribe(Vec<ListenerItem>, Arc<String>),
+    SearchContent(Box<ConfigSearchParam>),
+    QueryListener(Box<ConfigListenerQueryParam>),
+    QueryIncludeGraph(Box<ConfigIncludeGraphParam>),
+    LISTENER(Vec<ListenerItem>, ListenerSenderType, i64, ConfigClientInfo),
+    Subscribe(Vec<ListenerItem>, Arc<String>, ConfigClientInfo),
+    ///http watch订阅，返回与客户端md5不一致的配置
+    Watch(
+        Vec<ListenerItem>,
+        Arc<String>,
+        ConfigClientInfo,
+        WatchSenderType,
+    ),
     RemoveSubscribe(Vec<ListenerItem>, Arc<String>),
     RemoveSubscribeClient(Arc<String>),
     BuildSnapshot(Addr<SnapshotWriterActor>),
@@ -601,16 +1173,23 @@ pub enum ConfigCmd {
 #[derive(Message)]
 #[rtype(result = "anyhow::Result<ConfigResult>")]
 pub enum ConfigAsyncCmd {
-    Add(ConfigKey, Arc<String>, Option<Arc<String>>),
-    Delete(ConfigKey),
+    Add(SetConfigReq),
+    Delete(ConfigKey, Option<Arc<String>>),
+    AddBeta(ConfigKey, Arc<String>, ConfigBetaRule, Option<Arc<String>>),
+    RemoveBeta(ConfigKey),
+    PublishBeta(ConfigKey, Option<Arc<String>>),
 }
 
 pub enum ConfigResult {
     DATA(Arc<String>, Arc<String>),
+    QueryData(Box<ConfigQueryData>),
+    BetaInfo(Box<ConfigBetaInfoDto>),
     NULL,
     ChangeKey(Vec<ConfigKey>),
     ConfigInfoPage(usize, Vec<ConfigInfoDto>),
-    ConfigHistoryInfoPage(usize, Vec<ConfigHistoryInfoDto>),
+    ConfigSearchCandidates(Vec<(ConfigKey, Arc<String>)>),
+    ListenerInfoList(Vec<ConfigListenerInfoDto>),
+    IncludeGraph(Box<ConfigIncludeGraph>),
 }
 
 impl Actor for ConfigActor {
@@ -633,8 +1212,8 @@ impl Handler<ConfigCmd> for ConfigActor {
 
     fn handle(&mut self, msg: ConfigCmd, _ctx: &mut Context<Self>) -> Self::Result {
         match msg {
-            ConfigCmd::SetTmpValue(key, value) => {
-                self.set_tmp_config(key, value);
+            ConfigCmd::SetTmpValue(key, value, encrypted_data_key) => {
+                self.set_tmp_config(key, value, encrypted_data_key);
             }
             ConfigCmd::InnerSet(key, value) => {
                 self.inner_set_config(key, value);
@@ -647,41 +1226,67 @@ impl Handler<ConfigCmd> for ConfigActor {
                     return Ok(ConfigResult::DATA(v.content.clone(), v.md5.clone()));
                 }
             }
-            ConfigCmd::LISTENER(items, sender, time) => {
-                let mut changes = vec![];
-                for item in &items {
-                    if let Some(v) = self.cache.get(&item.key) {
-                        if v.md5 != item.md5 {
-                            changes.push(item.key.clone());
-                        }
-                    } else if !item.md5.is_empty() {
-                        changes.push(item.key.clone());
+            ConfigCmd::CheckInclude(key, content) => {
+                if let Some(path) = self.include_index.find_cycle(&key, &content) {
+                    let path = path
+                        .iter()
+                        .map(|e| format!("{}/{}", &e.group, &e.data_id))
+                        .collect();
+                    return Err(ConfigError::IncludeCycle(path).into());
+                }
+            }
+            ConfigCmd::GetRaw(key) => {
+                if let Some(v) = self.cache.get(&key) {
+                    return Ok(ConfigResult::QueryData(Box::new(v.to_query_data())));
+                }
+            }
+            ConfigCmd::GetByClient(key, client) => {
+                if let Some(v) = self.cache.get(&key) {
+                    let mut data = v.to_query_data();
+                    if let Some(beta) = v.match_beta(&client) {
+                        data.content = beta.content.clone();
+                        data.md5 = beta.md5.clone();
+                        data.beta = true;
+                        data.encrypted_data_key = None;
+                        data.last_modified = beta.modified_time;
+                    } else if let Some(resolved) = self.include_index.get_resolved(&key) {
+                        data.content = resolved.content.clone();
+                        data.md5 = resolved.md5.clone();
                     }
+                    return Ok(ConfigResult::QueryData(Box::new(data)));
                 }
+            }
+            ConfigCmd::GetBeta(key) => {
+                if let Some(v) = self.cache.get(&key) {
+                    if let Some(beta) = &v.beta {
+                        return Ok(ConfigResult::BetaInfo(Box::new(beta.to_dto(&key))));
+                    }
+                }
+            }
+            ConfigCmd::LISTENER(items, sender, time, client) => {
+                let changes = self.get_change_keys(&items, &client);
                 if !changes.is_empty() || time <= 0 {
                     sender.send(ListenerResult::DATA(changes)).ok();
                     return Ok(ConfigResult::NULL);
                 } else {
-                    self.listener.add(items, sender, time);
+                    self.listener.add(items, sender, time, client);
                     return Ok(ConfigResult::NULL);
                 }
             }
-            ConfigCmd::Subscribe(items, client_id) => {
-                let mut changes = vec![];
-                for item in &items {
-                    if let Some(v) = self.cache.get(&item.key) {
-                        if v.md5 != item.md5 {
-                            changes.push(item.key.clone());
-                        }
-                    } else if !item.md5.is_empty() {
-                        changes.push(item.key.clone());
-                    }
-                }
-                self.subscriber.add_subscribe(client_id, items);
+            ConfigCmd::Subscribe(items, client_id, client) => {
+                let changes = self.get_change_keys(&items, &client);
+                self.subscriber.add_subscribe(client_id, client, items);
                 if !changes.is_empty() {
                     return Ok(ConfigResult::ChangeKey(changes));
                 }
             }
+            ConfigCmd::Watch(items, client_id, client, sender) => {
+                let changes = self.get_change_keys(&items, &client);
+                self.subscriber
+                    .add_subscribe(client_id.clone(), client, items);
+                self.subscriber.add_watcher(client_id, sender);
+                return Ok(ConfigResult::ChangeKey(changes));
+            }
             ConfigCmd::RemoveSubscribe(items, client_id) => {
                 self.subscriber.remove_subscribe(client_id, items);
             }
@@ -692,9 +1297,20 @@ impl Handler<ConfigCmd> for ConfigActor {
                 let (size, list) = self.get_config_info_page(config_query_param.as_ref());
                 return Ok(ConfigResult::ConfigInfoPage(size, list));
             }
-            ConfigCmd::QueryHistoryPageInfo(query_param) => {
-                let (size, list) = self.get_history_info_page(query_param.as_ref());
-                return Ok(ConfigResult::ConfigHistoryInfoPage(size, list));
+            ConfigCmd::SearchContent(search_param) => {
+                let list = self.search_candidates(search_param.as_ref());
+                return Ok(ConfigResult::ConfigSearchCandidates(list));
+            }
+            ConfigCmd::QueryIncludeGraph(param) => {
+                let cache = &self.cache;
+                let graph =
+                    self.include_index
+                        .query_graph(&param.tenant, param.key.as_ref(), |k| cache.contains_key(k));
+                return Ok(ConfigResult::IncludeGraph(Box::new(graph)));
+            }
+            ConfigCmd::QueryListener(param) => {
+                let list = self.query_listener(param.as_ref());
+                return Ok(ConfigResult::ListenerInfoList(list));
             }
             ConfigCmd::BuildSnapshot(writer) => {
                 self.build_snapshot(writer).ok();
@@ -709,35 +1325,91 @@ impl Handler<ConfigAsyncCmd> for ConfigActor {
 
     fn handle(&mut self, msg: ConfigAsyncCmd, _ctx: &mut Context<Self>) -> Self::Result {
         let raft = self.raft.clone();
-        let history_info = if let ConfigAsyncCmd::Add(_, _, _) = &msg {
-            match self.sequence.next_state() {
-                Ok(v) => Some(v),
-                Err(_) => None,
+        let history_info = match &msg {
+            ConfigAsyncCmd::Add(_) | ConfigAsyncCmd::PublishBeta(_, _) => {
+                self.sequence.next_state().ok()
             }
-        } else {
-            None
+            _ => None,
+        };
+        let check_result = match &msg {
+            ConfigAsyncCmd::AddBeta(key, _, rule, _) => {
+                if rule.is_empty() {
+                    Err(anyhow::anyhow!("beta rule is empty"))
+                } else if !self.cache.contains_key(key) {
+                    Err(anyhow::anyhow!("config not exist"))
+                } else {
+                    Ok(())
+                }
+            }
+            ConfigAsyncCmd::PublishBeta(key, _) => {
+                if matches!(self.cache.get(key), Some(v) if v.beta.is_some()) {
+                    Ok(())
+                } else {
+                    Err(anyhow::anyhow!("beta config not exist"))
+                }
+            }
+            _ => Ok(()),
         };
         let fut = async move {
+            if let Err(err) = check_result {
+                return Err(err);
+            }
             match msg {
-                ConfigAsyncCmd::Add(key, value, op_user) => {
+                ConfigAsyncCmd::Add(set_req) => {
                     if let Some((history_id, history_table_id)) = history_info {
                         let req = ClientRequest::ConfigSet {
-                            key: key.build_key(),
-                            value,
+                            key: set_req.config_key.build_key(),
+                            value: set_req.value,
                             history_id,
                             history_table_id,
                             op_time: now_millis_i64(),
-                            op_user,
+                            op_user: set_req.op_user,
+                            config_type: set_req.config_type,
+                            desc: set_req.desc,
+                            config_tags: set_req.config_tags,
+                            app_name: set_req.app_name,
+                            cas_md5: set_req.cas_md5,
+                            encrypted_data_key: set_req.encrypted_data_key,
                         };
-                        Self::send_raft_request(&raft, req).await.ok();
+                        Self::send_raft_request(&raft, req).await?;
                     }
                 }
-                ConfigAsyncCmd::Delete(key) => {
+                ConfigAsyncCmd::Delete(key, op_user) => {
                     let req = ClientRequest::ConfigRemove {
                         key: key.build_key(),
+                        op_user,
+                        op_time: now_millis_i64(),
                     };
                     Self::send_raft_request(&raft, req).await.ok();
                 }
+                ConfigAsyncCmd::AddBeta(key, value, rule, op_user) => {
+                    let req = ClientRequest::ConfigSetBeta {
+                        key: key.build_key(),
+                        value,
+                        rule,
+                        op_time: now_millis_i64(),
+                        op_user,
+                    };
+                    Self::send_raft_request(&raft, req).await?;
+                }
+                ConfigAsyncCmd::RemoveBeta(key) => {
+                    let req = ClientRequest::ConfigRemoveBeta {
+                        key: key.build_key(),
+                    };
+                    Self::send_raft_request(&raft, req).await?;
+                }
+                ConfigAsyncCmd::PublishBeta(key, op_user) => {
+                    if let Some((history_id, history_table_id)) = history_info {
+                        let req = ClientRequest::ConfigPublishBeta {
+                            key: key.build_key(),
+                            history_id,
+                            history_table_id,
+                            op_time: now_millis_i64(),
+                            op_user,
+                        };
+                        Self::send_raft_request(&raft, req).await?;
+                    }
+                }
             }
             Ok(ConfigResult::NULL)
         }
@@ -759,21 +1431,71 @@ impl Handler<ConfigRaftCmd> for ConfigActor {
                 history_table_id,
                 op_time,
                 op_user,
+                config_type,
+                desc,
+                config_tags,
+                app_name,
+                cas_md5,
+                encrypted_data_key,
             } => {
                 let config_key: ConfigKey = (&key as &str).into();
+                if !self.check_cas_md5(&config_key, cas_md5.as_ref()) {
+                    return Ok(ConfigRaftResult::CasConflict);
+                }
+                let old_value = self.get_committed_value(&config_key);
                 self.set_config(
-                    config_key,
+                    config_key.clone(),
                     value,
                     history_id,
                     history_table_id,
                     op_time,
-                    op_user,
+                    op_user.clone(),
+                    encrypted_data_key,
                 )
                 .ok();
+                self.update_config_meta(&config_key, config_type, desc, config_tags, app_name);
+                self.notify_webhook(config_key, CONFIG_EVENT_SET, old_value, op_user, op_time);
             }
-            ConfigRaftCmd::ConfigRemove { key } => {
+            ConfigRaftCmd::ConfigRemove {
+                key,
+                op_user,
+                op_time,
+            } => {
                 let config_key: ConfigKey = (&key as &str).into();
-                self.del_config(config_key).ok();
+                //旧版本日志没有删除时间，保持为0，保证各节点回收记录的key一致
+                let op_time = op_time.max(0);
+                let old_value = self.get_committed_value(&config_key);
+                self.save_recycle(&config_key, op_user.clone(), op_time);
+                self.del_config(config_key.clone()).ok();
+                self.notify_webhook(config_key, CONFIG_EVENT_REMOVE, old_value, op_user, op_time);
+            }
+            ConfigRaftCmd::ConfigSetBeta {
+                key,
+                value,
+                rule,
+                op_time,
+                op_user,
+            } => {
+                let config_key: ConfigKey = (&key as &str).into();
+                self.set_beta_config(config_key, value, rule, op_time, op_user)
+                    .ok();
+            }
+            ConfigRaftCmd::ConfigRemoveBeta { key } => {
+                let config_key: ConfigKey = (&key as &str).into();
+                self.remove_beta_config(config_key).ok();
+            }
+            ConfigRaftCmd::ConfigPublishBeta {
+                key,
+                history_id,
+                history_table_id: _,
+                op_time,
+                op_user,
+            } => {
+                let config_key: ConfigKey = (&key as &str).into();
+                let old_value = self.get_committed_value(&config_key);
+                self.publish_beta_config(config_key.clone(), history_id, op_time, op_user.clone())
+                    .ok();
+                self.notify_webhook(config_key, CONFIG_EVENT_SET, old_value, op_user, op_time);
             }
             ConfigRaftCmd::ApplySnaphot => {
                 //self.load_config();
@@ -782,3 +1504,67 @@ impl Handler<ConfigRaftCmd> for ConfigActor {
         Ok(ConfigRaftResult::None)
     }
 }
+
+#[cfg(test)]
+fn build_config_add_cmd(key: &ConfigKey, value: &str, cas_md5: Option<&str>) -> ConfigRaftCmd {
+    ConfigRaftCmd::ConfigAdd {
+        key: key.build_key(),
+        value: Arc::new(value.to_owned()),
+        history_id: 0,
+        history_table_id: None,
+        op_time: 0,
+        op_user: None,
+        config_type: None,
+        desc: None,
+        config_tags: None,
+        app_name: None,
+        cas_md5: cas_md5.map(|e| Arc::new(e.to_owned())),
+        encrypted_data_key: None,
+    }
+}
+
+#[cfg(test)]
+async fn apply_config_add(
+    addr: &Addr<ConfigActor>,
+    key: &ConfigKey,
+    value: &str,
+    cas_md5: Option<&str>,
+) -> anyhow::Result<()> {
+    let cmd = build_config_add_cmd(key, value, cas_md5);
+    let resp: ClientResponse = addr.send(cmd).await??.into();
+    ConfigActor::check_client_response(&resp)
+}
+
+#[cfg(test)]
+async fn query_raw_config(addr: &Addr<ConfigActor>, key: &ConfigKey) -> ConfigQueryData {
+    match addr.send(ConfigCmd::GetRaw(key.clone())).await.unwrap() {
+        Ok(ConfigResult::QueryData(data)) => *data,
+        _ => panic!("config not found"),
+    }
+}
+
+#[actix_rt::test]
+async fn config_cas_conflict_on_apply() {
+    let addr = ConfigActor::new().start();
+    let key = ConfigKey::new("cas.yaml", "DEFAULT_GROUP", "");
+
+    apply_config_add(&addr, &key, "a=1", Some(CAS_MD5_NOT_EXIST))
+        .await
+        .unwrap();
+    //配置已存在时，要求配置不存在的写入失败
+    let err = apply_config_add(&addr, &key, "a=0", Some(CAS_MD5_NOT_EXIST))
+        .await
+        .unwrap_err();
+    assert!(ConfigError::is_cas_conflict(&err));
+
+    //两次写入使用相同的casMd5，只有第一次生效
+    let md5 = query_raw_config(&addr, &key).await.md5;
+    apply_config_add(&addr, &key, "a=2", Some(md5.as_str()))
+        .await
+        .unwrap();
+    let err = apply_config_add(&addr, &key, "a=3", Some(md5.as_str()))
+        .await
+        .unwrap_err();
+    assert!(ConfigError::is_cas_conflict(&err));
+    assert_eq!(query_raw_config(&addr, &key).await.content.as_str(), "a=2");
+}
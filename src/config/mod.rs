@@ -1,8 +1,21 @@
 pub mod api;
+pub mod api_v2;
 pub mod config_db;
+pub mod config_diff;
+pub mod config_encryption;
+pub mod config_history;
+pub mod config_include;
 pub mod config_index;
+pub mod config_push_trace;
+pub mod config_recycle;
+pub mod config_schema;
+pub mod config_search;
 pub mod config_sled;
 pub mod config_subscribe;
+pub mod config_transfer;
+pub mod config_type;
+pub mod config_watch;
+pub mod config_webhook;
 pub mod core;
 pub mod dal;
 pub mod model;
@@ -1,8 +1,11 @@
+use crate::config::config_encryption::CONFIG_ENCRYPTION;
 use crate::config::core::{ConfigHistoryInfoDto, ConfigKey, ConfigValue};
 use crate::utils::get_md5;
 use actix::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
+use thiserror::Error;
 
 #[derive(Message)]
 #[rtype(result = "anyhow::Result<ConfigRaftResult>")]
@@ -14,9 +17,34 @@ pub enum ConfigRaftCmd {
         history_table_id: Option<u64>,
         op_time: i64,
         op_user: Option<Arc<String>>,
+        config_type: Option<Arc<String>>,
+        desc: Option<Arc<String>>,
+        config_tags: Option<Arc<String>>,
+        app_name: Option<Arc<String>>,
+        cas_md5: Option<Arc<String>>,
+        encrypted_data_key: Option<Arc<String>>,
     },
     ConfigRemove {
         key: String,
+        op_user: Option<Arc<String>>,
+        op_time: i64,
+    },
+    ConfigSetBeta {
+        key: String,
+        value: Arc<String>,
+        rule: ConfigBetaRule,
+        op_time: i64,
+        op_user: Option<Arc<String>>,
+    },
+    ConfigRemoveBeta {
+        key: String,
+    },
+    ConfigPublishBeta {
+        key: String,
+        history_id: u64,
+        history_table_id: Option<u64>,
+        op_time: i64,
+        op_user: Option<Arc<String>>,
     },
     ApplySnaphot,
 }
@@ -26,27 +54,317 @@ pub enum ConfigRaftResult {
         data: Vec<(ConfigKey, Arc<String>)>,
         history_table_id: u64,
     },
+    CasConflict,
     None,
 }
 
+///
+/// cas_md5取该值时表示只在配置不存在时写入
+pub const CAS_MD5_NOT_EXIST: &str = "NOT_EXIST";
+
+#[derive(Clone, Debug, Error, Serialize, Deserialize)]
+pub enum ConfigError {
+    #[error("config md5 is changed, cas publish failed")]
+    CasConflict,
+    #[error("config content does not match schema, {}", .0.join("; "))]
+    SchemaInvalid(Vec<String>),
+    #[error("config include cycle, {}", .0.join(" -> "))]
+    IncludeCycle(Vec<String>),
+}
+
+impl ConfigError {
+    pub fn is_cas_conflict(err: &anyhow::Error) -> bool {
+        matches!(
+            err.downcast_ref::<ConfigError>(),
+            Some(ConfigError::CasConflict)
+        )
+    }
+
+    pub fn is_schema_invalid(err: &anyhow::Error) -> bool {
+        matches!(
+            err.downcast_ref::<ConfigError>(),
+            Some(ConfigError::SchemaInvalid(_))
+        )
+    }
+}
+
 #[derive(Clone)]
 pub struct HistoryItem {
     pub id: u64,
     pub content: Arc<String>,
     pub modified_time: i64,
     pub op_user: Option<Arc<String>>,
+    pub encrypted_data_key: Option<Arc<String>>,
 }
 
 impl HistoryItem {
+    ///
+    /// 加密配置的历史内容会被解密
     pub(crate) fn to_dto(&self, key: &ConfigKey) -> ConfigHistoryInfoDto {
+        let content = match CONFIG_ENCRYPTION.decrypt(
+            &key.data_id,
+            self.encrypted_data_key.as_ref(),
+            self.content.clone(),
+        ) {
+            Ok(v) => v,
+            Err(err) => {
+                log::warn!("config history decrypt error,{:?},{}", key, err);
+                self.content.clone()
+            }
+        };
         ConfigHistoryInfoDto {
             id: Some(self.id as i64),
             tenant: Some(key.tenant.to_string()),
             group: Some(key.group.to_string()),
             data_id: Some(key.data_id.to_string()),
-            content: Some(self.content.to_string()),
+            content: Some(content.to_string()),
             modified_time: Some(self.modified_time),
+            op_user: self.op_user.as_ref().map(|e| e.to_string()),
+        }
+    }
+}
+
+///
+/// 请求配置的客户端信息，用于灰度规则匹配
+#[derive(Debug, Clone, Default)]
+pub struct ConfigClientInfo {
+    pub ip: String,
+    pub labels: HashMap<String, String>,
+}
+
+impl ConfigClientInfo {
+    pub fn new(ip: String, labels: HashMap<String, String>) -> Self {
+        Self { ip, labels }
+    }
+}
+
+///
+/// 配置监听查询条件，key为空时按客户端ip或连接id查询
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigListenerQueryParam {
+    pub key: Option<String>,
+    pub client_ip: Option<String>,
+    pub conn_id: Option<String>,
+}
+
+impl ConfigListenerQueryParam {
+    pub fn get_config_key(&self) -> Option<ConfigKey> {
+        self.key.as_ref().map(|e| (e as &str).into())
+    }
+
+    pub fn match_client(&self, client_ip: &str, conn_id: Option<&Arc<String>>) -> bool {
+        if let Some(ip) = &self.client_ip {
+            if !ip.is_empty() && ip != client_ip {
+                return false;
+            }
+        }
+        if let Some(id) = &self.conn_id {
+            if !id.is_empty() && Some(id.as_str()) != conn_id.map(|e| e.as_str()) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+///
+/// 配置监听信息，md5为客户端最后上报的md5
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigListenerInfoDto {
+    pub tenant: Arc<String>,
+    pub group: Arc<String>,
+    pub data_id: Arc<String>,
+    pub md5: Arc<String>,
+    ///服务端当前下发给该客户端的md5
+    pub server_md5: Option<Arc<String>>,
+    ///客户端md5与服务端不一致
+    pub stale: bool,
+    pub client_ip: Arc<String>,
+    pub labels: HashMap<String, String>,
+    ///grpc长链接id,http长轮询为空
+    pub conn_id: Option<Arc<String>>,
+    pub node_id: u64,
+}
+
+impl ConfigListenerInfoDto {
+    pub fn new(
+        key: &ConfigKey,
+        md5: Arc<String>,
+        client: &ConfigClientInfo,
+        conn_id: Option<Arc<String>>,
+    ) -> Self {
+        Self {
+            tenant: key.tenant.clone(),
+            group: key.group.clone(),
+            data_id: key.data_id.clone(),
+            md5,
+            client_ip: Arc::new(client.ip.clone()),
+            labels: client.labels.clone(),
+            conn_id,
+            ..Default::default()
+        }
+    }
+
+    pub fn get_config_key(&self) -> ConfigKey {
+        ConfigKey::new_by_arc(
+            self.data_id.clone(),
+            self.group.clone(),
+            self.tenant.clone(),
+        )
+    }
+
+    pub fn get_client_info(&self) -> ConfigClientInfo {
+        ConfigClientInfo::new(self.client_ip.as_ref().to_owned(), self.labels.clone())
+    }
+}
+
+///
+/// 灰度发布规则；客户端ip命中或标签全部匹配即使用灰度内容
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigBetaRule {
+    #[serde(default)]
+    pub ips: Vec<String>,
+    #[serde(default)]
+    pub labels: HashMap<String, String>,
+}
+
+impl ConfigBetaRule {
+    pub fn new(ips: Vec<String>, labels: HashMap<String, String>) -> Self {
+        Self { ips, labels }
+    }
+
+    /// 解析nacos betaIps格式: ip1,ip2
+    pub fn parse_ips(ips: &str) -> Vec<String> {
+        ips.split(',')
+            .map(|e| e.trim())
+            .filter(|e| !e.is_empty())
+            .map(|e| e.to_owned())
+            .collect()
+    }
+
+    /// 解析标签格式: k1=v1,k2=v2
+    pub fn parse_labels(labels: &str) -> HashMap<String, String> {
+        let mut map = HashMap::new();
+        for item in labels.split(',') {
+            if let Some((k, v)) = item.split_once('=') {
+                let k = k.trim();
+                if !k.is_empty() {
+                    map.insert(k.to_owned(), v.trim().to_owned());
+                }
+            }
+        }
+        map
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ips.is_empty() && self.labels.is_empty()
+    }
+
+    pub fn is_match(&self, client: &ConfigClientInfo) -> bool {
+        if !client.ip.is_empty() && self.ips.iter().any(|e| e == &client.ip) {
+            return true;
+        }
+        if self.labels.is_empty() {
+            return false;
         }
+        self.labels
+            .iter()
+            .all(|(k, v)| client.labels.get(k).map(|e| e == v).unwrap_or(false))
+    }
+}
+
+#[derive(Clone)]
+pub struct ConfigBetaValue {
+    pub content: Arc<String>,
+    pub md5: Arc<String>,
+    pub rule: Arc<ConfigBetaRule>,
+    pub modified_time: i64,
+    pub op_user: Option<Arc<String>>,
+}
+
+impl ConfigBetaValue {
+    pub fn new(
+        content: Arc<String>,
+        rule: ConfigBetaRule,
+        modified_time: i64,
+        op_user: Option<Arc<String>>,
+    ) -> Self {
+        let md5 = Arc::new(get_md5(&content));
+        Self {
+            content,
+            md5,
+            rule: Arc::new(rule),
+            modified_time,
+            op_user,
+        }
+    }
+
+    pub(crate) fn to_dto(&self, key: &ConfigKey) -> ConfigBetaInfoDto {
+        ConfigBetaInfoDto {
+            tenant: key.tenant.clone(),
+            group: key.group.clone(),
+            data_id: key.data_id.clone(),
+            content: self.content.clone(),
+            md5: self.md5.clone(),
+            beta_ips: self.rule.ips.clone(),
+            beta_labels: self.rule.labels.clone(),
+            modified_time: self.modified_time,
+            op_user: self.op_user.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigBetaInfoDto {
+    pub tenant: Arc<String>,
+    pub group: Arc<String>,
+    pub data_id: Arc<String>,
+    pub content: Arc<String>,
+    pub md5: Arc<String>,
+    pub beta_ips: Vec<String>,
+    pub beta_labels: HashMap<String, String>,
+    pub modified_time: i64,
+    pub op_user: Option<Arc<String>>,
+}
+
+#[derive(Clone, PartialEq, prost_derive::Message, Deserialize, Serialize)]
+pub struct ConfigBetaDO {
+    #[prost(string, optional, tag = "1")]
+    pub content: Option<String>,
+    #[prost(string, repeated, tag = "2")]
+    pub beta_ips: Vec<String>,
+    #[prost(map = "string, string", tag = "3")]
+    pub beta_labels: HashMap<String, String>,
+    #[prost(int64, optional, tag = "4")]
+    pub last_time: Option<i64>,
+    #[prost(string, optional, tag = "5")]
+    pub op_user: Option<String>,
+}
+
+impl From<ConfigBetaValue> for ConfigBetaDO {
+    fn from(value: ConfigBetaValue) -> Self {
+        Self {
+            content: Some(value.content.as_ref().to_owned()),
+            beta_ips: value.rule.ips.clone(),
+            beta_labels: value.rule.labels.clone(),
+            last_time: Some(value.modified_time),
+            op_user: value.op_user.map(|e| e.as_ref().to_string()),
+        }
+    }
+}
+
+impl From<ConfigBetaDO> for ConfigBetaValue {
+    fn from(value: ConfigBetaDO) -> Self {
+        ConfigBetaValue::new(
+            Arc::new(value.content.unwrap_or_default()),
+            ConfigBetaRule::new(value.beta_ips, value.beta_labels),
+            value.last_time.unwrap_or_default(),
+            value.op_user.map(Arc::new),
+        )
     }
 }
 
@@ -60,6 +378,23 @@ pub struct ConfigHistoryItemDO {
     pub last_time: Option<i64>,
     #[prost(string, optional, tag = "4")]
     pub op_user: Option<String>,
+    #[prost(string, optional, tag = "5")]
+    pub encrypted_data_key: Option<String>,
+}
+
+impl ConfigHistoryItemDO {
+    pub fn to_bytes(&self) -> anyhow::Result<Vec<u8>> {
+        use prost::Message;
+        let mut v = Vec::new();
+        self.encode(&mut v)?;
+        Ok(v)
+    }
+
+    pub fn from_bytes(data: &[u8]) -> anyhow::Result<Self> {
+        use prost::Message;
+        let s = Self::decode(data)?;
+        Ok(s)
+    }
 }
 
 impl From<HistoryItem> for ConfigHistoryItemDO {
@@ -69,6 +404,7 @@ impl From<HistoryItem> for ConfigHistoryItemDO {
             content: Some(value.content.as_ref().to_string()),
             last_time: Some(value.modified_time),
             op_user: value.op_user.map(|e| e.as_ref().to_string()),
+            encrypted_data_key: value.encrypted_data_key.map(|e| e.as_ref().to_string()),
         }
     }
 }
@@ -80,6 +416,7 @@ impl From<ConfigHistoryItemDO> for HistoryItem {
             content: Arc::new(value.content.unwrap_or_default()),
             modified_time: value.last_time.unwrap_or_default(),
             op_user: value.op_user.map(Arc::new),
+            encrypted_data_key: value.encrypted_data_key.map(Arc::new),
         }
     }
 }
@@ -89,6 +426,18 @@ pub struct ConfigValueDO {
     pub content: Option<String>,
     #[prost(repeated, message, tag = "2")]
     pub histories: Vec<ConfigHistoryItemDO>,
+    #[prost(message, optional, tag = "3")]
+    pub beta: Option<ConfigBetaDO>,
+    #[prost(string, optional, tag = "4")]
+    pub config_type: Option<String>,
+    #[prost(string, optional, tag = "5")]
+    pub desc: Option<String>,
+    #[prost(string, optional, tag = "6")]
+    pub config_tags: Option<String>,
+    #[prost(string, optional, tag = "7")]
+    pub app_name: Option<String>,
+    #[prost(string, optional, tag = "8")]
+    pub encrypted_data_key: Option<String>,
 }
 
 impl ConfigValueDO {
@@ -110,7 +459,13 @@ impl From<ConfigValue> for ConfigValueDO {
     fn from(value: ConfigValue) -> Self {
         Self {
             content: Some(value.content.as_ref().to_owned()),
-            histories: value.histories.into_iter().map(|e| e.into()).collect(),
+            histories: value.last_history.into_iter().map(|e| e.into()).collect(),
+            beta: value.beta.map(|e| e.into()),
+            config_type: value.config_type.map(|e| e.as_ref().to_owned()),
+            desc: value.desc.map(|e| e.as_ref().to_owned()),
+            config_tags: value.config_tags.map(|e| e.as_ref().to_owned()),
+            app_name: value.app_name.map(|e| e.as_ref().to_owned()),
+            encrypted_data_key: value.encrypted_data_key.map(|e| e.as_ref().to_owned()),
         }
     }
 }
@@ -123,7 +478,30 @@ impl From<ConfigValueDO> for ConfigValue {
             content: Arc::new(content),
             md5,
             tmp: false,
-            histories: value.histories.into_iter().map(|e| e.into()).collect(),
+            last_history: value.histories.into_iter().last().map(|e| e.into()),
+            beta: value.beta.map(|e| e.into()),
+            config_type: value.config_type.map(Arc::new),
+            desc: value.desc.map(Arc::new),
+            config_tags: value.config_tags.map(Arc::new),
+            app_name: value.app_name.map(Arc::new),
+            encrypted_data_key: value.encrypted_data_key.map(Arc::new),
         }
     }
 }
+
+#[test]
+fn beta_rule_match() {
+    let rule = ConfigBetaRule::new(
+        ConfigBetaRule::parse_ips("127.0.0.1, 10.0.0.2"),
+        ConfigBetaRule::parse_labels("zone=cn-east"),
+    );
+    let client = ConfigClientInfo::new("10.0.0.2".to_owned(), Default::default());
+    assert!(rule.is_match(&client));
+    let client = ConfigClientInfo::new("10.0.0.3".to_owned(), Default::default());
+    assert!(!rule.is_match(&client));
+    let mut labels = HashMap::new();
+    labels.insert("zone".to_owned(), "cn-east".to_owned());
+    let client = ConfigClientInfo::new("10.0.0.3".to_owned(), labels);
+    assert!(rule.is_match(&client));
+    assert!(ConfigBetaRule::default().is_empty());
+}
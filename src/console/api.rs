@@ -12,10 +12,18 @@ use crate::naming::api::{
 use crate::naming::ops::ops_api::query_opt_service_list;
 //use crate::console::raft_api::{raft_add_learner, raft_change_membership, raft_init, raft_metrics, raft_read, raft_write};
 
-use super::cluster_api::query_cluster_info;
+use super::cluster_api::{query_cluster_info, query_compress_metrics};
 use super::config_api::query_config_list;
 use super::{
-    config_api::{download_config, import_config, query_history_config_page},
+    config_api::{
+        clone_config, diff_config_history, download_config, get_config_recycle, import_config,
+        publish_beta_config, purge_config_recycle, query_beta_config, query_config_include_graph,
+        query_config_listener, query_config_push_trace, query_config_recycle_page,
+        query_config_schema_list, query_config_webhook_list, query_config_webhook_log,
+        query_history_config_page, remove_beta_config, remove_config_schema, remove_config_webhook,
+        restore_config_recycle, rollback_config, search_config_content, set_beta_config,
+        set_config_schema, set_config_webhook, validate_config_schema,
+    },
     connection_api::query_grpc_connection,
     model::{ConsoleResult, NamespaceInfo},
     naming_api::{query_grpc_client_instance_count, query_ops_instances_list},
@@ -110,11 +118,69 @@ pub fn console_api_config(config: &mut web::ServiceConfig) {
                     .route(web::delete().to(remove_namespace)),
             )
             .service(web::resource("/configs").route(web::get().to(query_config_list)))
+            .service(web::resource("/config/search").route(web::get().to(search_config_content)))
+            .service(web::resource("/config/listener").route(web::get().to(query_config_listener)))
+            .service(
+                web::resource("/config/include/graph")
+                    .route(web::get().to(query_config_include_graph)),
+            )
+            .service(
+                web::resource("/config/push/trace").route(web::get().to(query_config_push_trace)),
+            )
+            .service(
+                web::resource("/config/webhook")
+                    .route(web::get().to(query_config_webhook_list))
+                    .route(web::post().to(set_config_webhook))
+                    .route(web::delete().to(remove_config_webhook)),
+            )
+            .service(
+                web::resource("/config/webhook/delivery")
+                    .route(web::get().to(query_config_webhook_log)),
+            )
+            .service(
+                web::resource("/config/schema")
+                    .route(web::get().to(query_config_schema_list))
+                    .route(web::post().to(set_config_schema))
+                    .route(web::delete().to(remove_config_schema)),
+            )
+            .service(
+                web::resource("/config/schema/validate")
+                    .route(web::post().to(validate_config_schema)),
+            )
+            .service(
+                web::resource("/config/recycle").route(web::get().to(query_config_recycle_page)),
+            )
+            .service(
+                web::resource("/config/recycle/detail").route(web::get().to(get_config_recycle)),
+            )
+            .service(
+                web::resource("/config/recycle/restore")
+                    .route(web::post().to(restore_config_recycle)),
+            )
+            .service(
+                web::resource("/config/recycle/purge").route(web::post().to(purge_config_recycle)),
+            )
             .service(web::resource("/config/import").route(web::post().to(import_config)))
+            .service(web::resource("/config/clone").route(web::post().to(clone_config)))
             .service(web::resource("/config/download").route(web::get().to(download_config)))
             .service(
                 web::resource("/config/history").route(web::get().to(query_history_config_page)),
             )
+            .service(
+                web::resource("/config/history/rollback").route(web::post().to(rollback_config)),
+            )
+            .service(
+                web::resource("/config/history/diff").route(web::get().to(diff_config_history)),
+            )
+            .service(
+                web::resource("/config/beta")
+                    .route(web::get().to(query_beta_config))
+                    .route(web::post().to(set_beta_config))
+                    .route(web::delete().to(remove_beta_config)),
+            )
+            .service(
+                web::resource("/config/beta/publish").route(web::post().to(publish_beta_config)),
+            )
             .service(web::resource("/instances").route(web::get().to(query_ops_instances_list)))
             .service(
                 web::resource("/naming/client_instance_count")
@@ -124,6 +190,10 @@ pub fn console_api_config(config: &mut web::ServiceConfig) {
                 web::resource("/cluster/cluster_node_list")
                     .route(web::get().to(query_cluster_info)),
             )
+            .service(
+                web::resource("/cluster/compress_metrics")
+                    .route(web::get().to(query_compress_metrics)),
+            )
             .service(web::resource("/connections").route(web::get().to(query_grpc_connection)))
             .service(web::resource("/login/login").route(web::post().to(login_api::login)))
             .service(web::resource("/login/captcha").route(web::get().to(login_api::gen_captcha)))
@@ -177,11 +247,69 @@ pub fn console_api_config_new(config: &mut web::ServiceConfig) {
                     .route(web::delete().to(remove_namespace)),
             )
             .service(web::resource("/configs").route(web::get().to(query_config_list)))
+            .service(web::resource("/config/search").route(web::get().to(search_config_content)))
+            .service(web::resource("/config/listener").route(web::get().to(query_config_listener)))
+            .service(
+                web::resource("/config/include/graph")
+                    .route(web::get().to(query_config_include_graph)),
+            )
+            .service(
+                web::resource("/config/push/trace").route(web::get().to(query_config_push_trace)),
+            )
+            .service(
+                web::resource("/config/webhook")
+                    .route(web::get().to(query_config_webhook_list))
+                    .route(web::post().to(set_config_webhook))
+                    .route(web::delete().to(remove_config_webhook)),
+            )
+            .service(
+                web::resource("/config/webhook/delivery")
+                    .route(web::get().to(query_config_webhook_log)),
+            )
+            .service(
+                web::resource("/config/schema")
+                    .route(web::get().to(query_config_schema_list))
+                    .route(web::post().to(set_config_schema))
+                    .route(web::delete().to(remove_config_schema)),
+            )
+            .service(
+                web::resource("/config/schema/validate")
+                    .route(web::post().to(validate_config_schema)),
+            )
+            .service(
+                web::resource("/config/recycle").route(web::get().to(query_config_recycle_page)),
+            )
+            .service(
+                web::resource("/config/recycle/detail").route(web::get().to(get_config_recycle)),
+            )
+            .service(
+                web::resource("/config/recycle/restore")
+                    .route(web::post().to(restore_config_recycle)),
+            )
+            .service(
+                web::resource("/config/recycle/purge").route(web::post().to(purge_config_recycle)),
+            )
             .service(web::resource("/config/import").route(web::post().to(import_config)))
+            .service(web::resource("/config/clone").route(web::post().to(clone_config)))
             .service(web::resource("/config/download").route(web::get().to(download_config)))
             .service(
                 web::resource("/config/history").route(web::get().to(query_history_config_page)),
             )
+            .service(
+                web::resource("/config/history/rollback").route(web::post().to(rollback_config)),
+            )
+            .service(
+                web::resource("/config/history/diff").route(web::get().to(diff_config_history)),
+            )
+            .service(
+                web::resource("/config/beta")
+                    .route(web::get().to(query_beta_config))
+                    .route(web::post().to(set_beta_config))
+                    .route(web::delete().to(remove_beta_config)),
+            )
+            .service(
+                web::resource("/config/beta/publish").route(web::post().to(publish_beta_config)),
+            )
             .service(web::resource("/instances").route(web::get().to(query_ops_instances_list)))
             .service(
                 web::resource("/naming/client_instance_count")
@@ -191,6 +319,10 @@ pub fn console_api_config_new(config: &mut web::ServiceConfig) {
                 web::resource("/cluster/cluster_node_list")
                     .route(web::get().to(query_cluster_info)),
             )
+            .service(
+                web::resource("/cluster/compress_metrics")
+                    .route(web::get().to(query_compress_metrics)),
+            )
             .service(web::resource("/connections").route(web::get().to(query_grpc_connection)))
             .service(web::resource("/login/login").route(web::post().to(login_api::login)))
             .service(web::resource("/login/captcha").route(web::get().to(login_api::gen_captcha)))
@@ -3,6 +3,7 @@ use std::sync::Arc;
 use actix_web::{http::header, web, HttpResponse, Responder};
 
 use crate::common::appdata::AppShareData;
+use crate::common::compress_utils::RAFT_DATA_COMPRESS;
 
 use super::model::{cluster_model::ClusterNodeInfo, ConsoleResult};
 
@@ -28,3 +29,13 @@ pub async fn query_cluster_info(app: web::Data<Arc<AppShareData>>) -> impl Respo
         .insert_header(header::ContentType(mime::APPLICATION_JSON))
         .body(v)
 }
+
+///
+/// 当前节点raft日志及快照的压缩统计
+pub async fn query_compress_metrics() -> impl Responder {
+    let resp = ConsoleResult::success(RAFT_DATA_COMPRESS.get_metrics());
+    let v = serde_json::to_string(&resp).unwrap();
+    HttpResponse::Ok()
+        .insert_header(header::ContentType(mime::APPLICATION_JSON))
+        .body(v)
+}
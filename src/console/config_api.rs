@@ -1,9 +1,11 @@
 #![allow(unused_imports)]
 
+use std::collections::HashSet;
 use std::fs::File;
 use std::io::{self, Read, Seek, SeekFrom, Write};
 use std::sync::Arc;
 
+use actix_http::HttpMessage;
 use actix_multipart::form::tempfile::TempFile;
 use actix_multipart::form::text::Text;
 use actix_multipart::form::MultipartForm;
@@ -12,15 +14,37 @@ use actix_web::{http::header, web, Error, HttpRequest, HttpResponse, Responder};
 use zip::write::FileOptions;
 
 use crate::common::appdata::AppShareData;
+use crate::common::model::{ApiResult, UserSession};
+use crate::config::config_diff::{ConfigDiffDto, ConfigDiffUtils};
+use crate::config::config_encryption::CONFIG_ENCRYPTION;
+use crate::config::config_history::ConfigHistoryUtils;
+use crate::config::config_index::ConfigQueryParam;
+use crate::config::config_recycle::ConfigRecycleUtils;
+use crate::config::config_schema::{ConfigSchemaDO, ConfigSchemaUtils, ConfigSchemaValidateResult};
+use crate::config::config_search;
+use crate::config::config_transfer::{
+    ConfigImportItem, ConfigImportPolicy, ConfigImportResult, ConfigMetaItem, ConfigMetadata,
+    CONFIG_METADATA_FILE_NAME, CONFIG_META_FILE_NAME,
+};
+use crate::config::config_webhook::{ConfigWebhookCmd, ConfigWebhookDO, ConfigWebhookResult};
 use crate::config::core::{
     ConfigActor, ConfigAsyncCmd, ConfigCmd, ConfigInfoDto, ConfigKey, ConfigResult,
 };
+use crate::config::model::{ConfigError, CAS_MD5_NOT_EXIST};
 use crate::config::ConfigUtils;
 use crate::console::model::config_model::{
-    OpsConfigOptQueryListResponse, OpsConfigQueryListRequest,
+    OpsConfigBetaParam, OpsConfigCloneRequest, OpsConfigDiffParam, OpsConfigIncludeGraphRequest,
+    OpsConfigListenerRequest, OpsConfigOptQueryListResponse, OpsConfigPushTraceRequest,
+    OpsConfigQueryListRequest, OpsConfigRecycleParam, OpsConfigRecycleRequest,
+    OpsConfigRollbackParam, OpsConfigSchemaRemoveParam, OpsConfigSchemaValidateRequest,
+    OpsConfigSearchRequest, OpsConfigWebhookLogRequest, OpsConfigWebhookRemoveParam,
 };
 use crate::now_millis;
-use crate::raft::cluster::model::SetConfigReq;
+use crate::raft::cluster::model::{SetBetaConfigReq, SetConfigReq};
+use crate::raft::cluster::{
+    query_cluster_config_listener, query_cluster_config_push_trace, query_leader_config_webhook_log,
+};
+use crate::utils::get_md5;
 use actix::prelude::Addr;
 use tokio_stream::StreamExt;
 use uuid::Uuid;
@@ -57,7 +81,7 @@ pub async fn query_config_list(
 
 pub async fn query_history_config_page(
     request: web::Query<OpsConfigQueryListRequest>,
-    config_addr: web::Data<Addr<ConfigActor>>,
+    app: web::Data<Arc<AppShareData>>,
 ) -> impl Responder {
     let param = match request.0.to_history_param() {
         Ok(param) => param,
@@ -65,23 +89,16 @@ pub async fn query_history_config_page(
             return HttpResponse::InternalServerError().body(err.to_string());
         }
     };
-    let cmd = ConfigCmd::QueryHistoryPageInfo(Box::new(param));
-    match config_addr.send(cmd).await {
-        Ok(res) => {
-            let r: ConfigResult = res.unwrap();
-            match r {
-                ConfigResult::ConfigHistoryInfoPage(size, list) => {
-                    let response = PageResult {
-                        count: size as u64,
-                        list,
-                    };
-                    let v = serde_json::to_string(&response).unwrap();
-                    HttpResponse::Ok()
-                        .insert_header(header::ContentType(mime::APPLICATION_JSON))
-                        .body(v)
-                }
-                _ => HttpResponse::InternalServerError().body("config result error"),
-            }
+    match ConfigHistoryUtils::query_history_page(&app.raft_table_manage, &param).await {
+        Ok((size, list)) => {
+            let response = PageResult {
+                count: size as u64,
+                list,
+            };
+            let v = serde_json::to_string(&response).unwrap();
+            HttpResponse::Ok()
+                .insert_header(header::ContentType(mime::APPLICATION_JSON))
+                .body(v)
         }
         Err(err) => HttpResponse::InternalServerError().body(err.to_string()),
     }
@@ -91,60 +108,267 @@ pub async fn query_history_config_page(
 pub struct UploadForm {
     #[multipart(rename = "tenant")]
     pub tenant: Option<Text<String>>,
+    ///配置已存在时的处理策略: ABORT/SKIP/OVERWRITE
+    #[multipart(rename = "policy")]
+    pub policy: Option<Text<String>>,
     #[multipart(rename = "file")]
     pub files: Vec<TempFile>,
 }
 
+///
+/// 从导出包读取配置条目及元数据，无法识别的条目记为失败
+fn read_import_items(
+    file: File,
+    items: &mut Vec<(String, String, String)>,
+    metadata: &mut ConfigMetadata,
+    result: &mut ConfigImportResult,
+) -> anyhow::Result<()> {
+    let mut archive = ZipArchive::new(file)?;
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i)?;
+        let filename = file.name().to_owned();
+        if filename.ends_with('/') || filename == ".ignore" {
+            continue;
+        }
+        if filename == CONFIG_META_FILE_NAME || filename == CONFIG_METADATA_FILE_NAME {
+            let content = io::read_to_string(&mut file)?;
+            metadata
+                .metadata
+                .extend(ConfigMetadata::parse(&content)?.metadata);
+            continue;
+        }
+        let parts = filename.split('/').collect::<Vec<_>>();
+        if parts.len() != 2 || parts[0].is_empty() || parts[1].is_empty() {
+            result.add_fail(ConfigImportItem::new(
+                "",
+                &filename,
+                Some("unrecognized config file".to_owned()),
+            ));
+            continue;
+        }
+        match io::read_to_string(&mut file) {
+            Ok(value) => items.push((parts[0].to_owned(), parts[1].to_owned(), value)),
+            Err(err) => result.add_fail(ConfigImportItem::new(
+                parts[0],
+                parts[1],
+                Some(err.to_string()),
+            )),
+        }
+    }
+    Ok(())
+}
+
 pub async fn import_config(
     req: HttpRequest,
     MultipartForm(form): MultipartForm<UploadForm>,
     app: web::Data<Arc<AppShareData>>,
 ) -> Result<impl Responder, Error> {
-    let tenant = Arc::new(ConfigUtils::default_tenant(
-        match req.headers().get("tenant") {
-            Some(v) => String::from_utf8_lossy(v.as_bytes()).to_string(),
-            None => "".to_owned(),
+    let tenant = match req.headers().get("tenant") {
+        Some(v) => String::from_utf8_lossy(v.as_bytes()).to_string(),
+        None => form.tenant.map(|e| e.0).unwrap_or_default(),
+    };
+    let tenant = Arc::new(ConfigUtils::default_tenant(tenant));
+    let policy = match form.policy.as_ref().map(|e| e.0.as_str()) {
+        Some(v) if !v.is_empty() => match ConfigImportPolicy::new_by_value(v) {
+            Ok(v) => v,
+            Err(err) => {
+                return Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+                    "PARAM_ERROR".to_owned(),
+                    Some(err.to_string()),
+                )));
+            }
         },
-    ));
-    //let tenant = Arc::new(ConfigUtils::default_tenant(config_info.0.tenant.unwrap_or_default()));
+        _ => ConfigImportPolicy::default(),
+    };
+    let op_user = get_op_user(&req);
+    let mut result = ConfigImportResult::default();
+    let mut items = vec![];
+    let mut metadata = ConfigMetadata::default();
     for f in form.files {
-        match zip::ZipArchive::new(f.file) {
-            Ok(mut archive) => {
-                for i in 0..archive.len() {
-                    let mut file = archive.by_index(i).unwrap();
-                    /*
-                    let filepath = match file.enclosed_name() {
-                        Some(path) => path,
-                        None => continue,
-                    };
-                    */
-                    let filename = file.name();
-                    if !(*filename).ends_with('/') {
-                        let parts = filename.split('/').collect::<Vec<_>>();
-                        if parts.len() != 2 {
-                            continue;
-                        }
-                        assert!(parts.len() == 2);
-                        let config_key = ConfigKey::new_by_arc(
-                            Arc::new(parts[1].to_owned()),
-                            Arc::new(parts[0].to_owned()),
-                            tenant.clone(),
-                        );
-                        let value = match io::read_to_string(&mut file) {
-                            Ok(v) => v,
-                            Err(_) => continue,
-                        };
-                        //println!("update load, {:?}:{}",&config_key,&value);
-                        //config_addr.do_send(ConfigAsyncCmd::Add(config_key, Arc::new(value)));
-                        let req = SetConfigReq::new(config_key, Arc::new(value));
-                        app.config_route.set_config(req).await.ok();
-                    }
-                }
+        if let Err(err) =
+            read_import_items(f.file.into_file(), &mut items, &mut metadata, &mut result)
+        {
+            return Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+                "PARAM_ERROR".to_owned(),
+                Some(format!("invalid zip file, {}", err)),
+            )));
+        }
+    }
+    let metadata = metadata.to_map();
+    let items = items
+        .into_iter()
+        .map(|(group, data_id, value)| {
+            let meta = match ConfigMetadata::find(&metadata, &group, &data_id) {
+                Some(v) => ConfigMetaItem {
+                    group,
+                    data_id,
+                    ..v.clone()
+                },
+                None => ConfigMetaItem {
+                    group,
+                    data_id,
+                    ..Default::default()
+                },
+            };
+            (meta, value)
+        })
+        .collect();
+    write_config_items(&app, &tenant, items, policy, op_user, &mut result).await;
+    Ok(HttpResponse::Ok().json(ApiResult::success(Some(result))))
+}
+
+///
+/// 按冲突策略写入配置，写入经过ConfigRoute::set_config同步到集群并记录历史
+async fn write_config_items(
+    app: &AppShareData,
+    tenant: &Arc<String>,
+    items: Vec<(ConfigMetaItem, String)>,
+    policy: ConfigImportPolicy,
+    op_user: Option<Arc<String>>,
+    result: &mut ConfigImportResult,
+) {
+    //先检查冲突，ABORT策略下存在冲突时不写入任何配置
+    let mut exists = Vec::with_capacity(items.len());
+    for (meta, _) in &items {
+        let config_key = ConfigKey::new(&meta.data_id, &meta.group, tenant);
+        let exist = matches!(
+            app.config_addr.send(ConfigCmd::GET(config_key)).await,
+            Ok(Ok(ConfigResult::DATA(_, _)))
+        );
+        exists.push(exist);
+    }
+    if policy == ConfigImportPolicy::Abort && exists.iter().any(|e| *e) {
+        for ((meta, _), exist) in items.iter().zip(exists) {
+            if exist {
+                result.add_fail(ConfigImportItem::new(
+                    &meta.group,
+                    &meta.data_id,
+                    Some("config already exists".to_owned()),
+                ));
+            } else {
+                result.add_skip(ConfigImportItem::new(&meta.group, &meta.data_id, None));
             }
-            Err(_) => todo!(),
+        }
+        return;
+    }
+    for ((meta, value), exist) in items.into_iter().zip(exists) {
+        if exist && policy == ConfigImportPolicy::Skip {
+            result.add_skip(ConfigImportItem::new(&meta.group, &meta.data_id, None));
+            continue;
+        }
+        let config_key = ConfigKey::new(&meta.data_id, &meta.group, tenant);
+        let mut set_req = SetConfigReq::new(config_key, Arc::new(value));
+        set_req.op_user = op_user.clone();
+        set_req.config_type = meta.config_type.map(Arc::new);
+        set_req.desc = meta.desc.map(Arc::new);
+        set_req.config_tags = meta.config_tags.map(Arc::new);
+        set_req.app_name = meta.app_name.map(Arc::new);
+        //ABORT策略下检查后写入前可能有并发创建，写入时要求配置仍不存在
+        if policy == ConfigImportPolicy::Abort {
+            set_req.cas_md5 = Some(Arc::new(CAS_MD5_NOT_EXIST.to_owned()));
+        }
+        match app.config_route.set_config(set_req).await {
+            Ok(_) => result.add_succ(ConfigImportItem::new(&meta.group, &meta.data_id, None)),
+            Err(err) => result.add_fail(ConfigImportItem::new(
+                &meta.group,
+                &meta.data_id,
+                Some(err.to_string()),
+            )),
+        }
+    }
+}
+
+///
+/// 将源命名空间中选中的配置复制到目标命名空间，可按规则重写group、dataId
+pub async fn clone_config(
+    req: HttpRequest,
+    web::Json(param): web::Json<OpsConfigCloneRequest>,
+    app: web::Data<Arc<AppShareData>>,
+) -> actix_web::Result<impl Responder> {
+    let (src_tenant, target_tenant, policy, rewriter) = match param.to_clone_param() {
+        Ok(v) => v,
+        Err(err) => {
+            return Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+                "PARAM_ERROR".to_owned(),
+                Some(err.to_string()),
+            )));
+        }
+    };
+    let query_param = ConfigQueryParam {
+        tenant: Some(src_tenant),
+        query_context: true,
+        limit: 0xffff_ffff,
+        ..Default::default()
+    };
+    let list = match app
+        .config_addr
+        .send(ConfigCmd::QueryPageInfo(Box::new(query_param)))
+        .await
+    {
+        Ok(Ok(ConfigResult::ConfigInfoPage(_, list))) => list,
+        Ok(Ok(_)) => vec![],
+        Ok(Err(err)) => {
+            return Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+                "SYSTEM_ERROR".to_owned(),
+                Some(err.to_string()),
+            )));
+        }
+        Err(err) => {
+            return Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+                "SYSTEM_ERROR".to_owned(),
+                Some(err.to_string()),
+            )));
+        }
+    };
+    let mut result = ConfigImportResult::default();
+    let mut items = Vec::with_capacity(list.len());
+    let mut found = HashSet::new();
+    for (meta, info) in ConfigMetadata::build(&list).metadata.into_iter().zip(list) {
+        if !param.is_selected(&meta.group, &meta.data_id) {
+            continue;
+        }
+        found.insert((meta.group.clone(), meta.data_id.clone()));
+        let (group, data_id) = rewriter.rewrite(&meta.group, &meta.data_id);
+        if group.is_empty() || data_id.is_empty() {
+            result.add_fail(ConfigImportItem::new(
+                &meta.group,
+                &meta.data_id,
+                Some("group or dataId is empty after rewrite".to_owned()),
+            ));
+            continue;
+        }
+        let content = info
+            .content
+            .map(|e| e.as_ref().to_owned())
+            .unwrap_or_default();
+        items.push((
+            ConfigMetaItem {
+                group,
+                data_id,
+                ..meta
+            },
+            content,
+        ));
+    }
+    for item in &param.items {
+        if !found.contains(&(item.group.clone(), item.data_id.clone())) {
+            result.add_fail(ConfigImportItem::new(
+                &item.group,
+                &item.data_id,
+                Some("config not exist".to_owned()),
+            ));
         }
     }
-    Ok(HttpResponse::Ok())
+    write_config_items(
+        &app,
+        &target_tenant,
+        items,
+        policy,
+        get_op_user(&req),
+        &mut result,
+    )
+    .await;
+    Ok(HttpResponse::Ok().json(ApiResult::success(Some(result))))
 }
 
 fn zip_file(mut zip: ZipWriter<&mut File>, list: Vec<ConfigInfoDto>) -> anyhow::Result<()> {
@@ -162,11 +386,18 @@ fn zip_file(mut zip: ZipWriter<&mut File>, list: Vec<ConfigInfoDto>) -> anyhow::
             .compression_method(zip::CompressionMethod::Stored)
             .unix_permissions(0o755);
         zip.start_file(
-            &format!("{}/{}", &item.group.as_str(), &item.data_id.as_str()),
+            format!("{}/{}", &item.group.as_str(), &item.data_id.as_str()),
             options,
         )?;
         zip.write_all(item.content.as_ref().unwrap().as_bytes())?;
     }
+    if !list.is_empty() {
+        let options = FileOptions::default()
+            .compression_method(zip::CompressionMethod::Stored)
+            .unix_permissions(0o755);
+        zip.start_file(CONFIG_META_FILE_NAME, options)?;
+        zip.write_all(ConfigMetadata::build(&list).to_yaml()?.as_bytes())?;
+    }
     zip.finish()?;
     Ok(())
 }
@@ -209,3 +440,783 @@ pub async fn download_config(
         Err(err) => HttpResponse::InternalServerError().body(err.to_string()),
     }
 }
+
+pub(crate) fn get_op_user(req: &HttpRequest) -> Option<Arc<String>> {
+    req.extensions()
+        .get::<Arc<UserSession>>()
+        .map(|session| session.username.clone())
+}
+
+pub async fn query_beta_config(
+    request: web::Query<OpsConfigBetaParam>,
+    config_addr: web::Data<Addr<ConfigActor>>,
+) -> actix_web::Result<impl Responder> {
+    let config_key = match request.0.to_config_key() {
+        Ok(v) => v,
+        Err(err) => {
+            return Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+                "PARAM_ERROR".to_owned(),
+                Some(err.to_string()),
+            )));
+        }
+    };
+    match config_addr.send(ConfigCmd::GetBeta(config_key)).await {
+        Ok(Ok(ConfigResult::BetaInfo(info))) => {
+            Ok(HttpResponse::Ok().json(ApiResult::success(Some(info))))
+        }
+        Ok(_) => Ok(HttpResponse::Ok().json(ApiResult::<()>::success(None))),
+        Err(err) => Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+            "SYSTEM_ERROR".to_owned(),
+            Some(err.to_string()),
+        ))),
+    }
+}
+
+pub async fn set_beta_config(
+    req: HttpRequest,
+    web::Form(param): web::Form<OpsConfigBetaParam>,
+    app: web::Data<Arc<AppShareData>>,
+) -> actix_web::Result<impl Responder> {
+    let config_key = match param.to_config_key() {
+        Ok(v) => v,
+        Err(err) => {
+            return Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+                "PARAM_ERROR".to_owned(),
+                Some(err.to_string()),
+            )));
+        }
+    };
+    let content = param.content.clone().unwrap_or_default();
+    if content.is_empty() {
+        return Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+            "PARAM_ERROR".to_owned(),
+            Some("content is blank".to_owned()),
+        )));
+    }
+    let mut beta_req = SetBetaConfigReq::new(config_key, Arc::new(content), param.to_rule());
+    beta_req.op_user = get_op_user(&req);
+    match app.config_route.set_beta_config(beta_req).await {
+        Ok(_) => Ok(HttpResponse::Ok().json(ApiResult::success(Some(true)))),
+        Err(err) => Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+            "SYSTEM_ERROR".to_owned(),
+            Some(err.to_string()),
+        ))),
+    }
+}
+
+///
+/// 灰度配置转为正式配置
+pub async fn publish_beta_config(
+    req: HttpRequest,
+    web::Form(param): web::Form<OpsConfigBetaParam>,
+    app: web::Data<Arc<AppShareData>>,
+) -> actix_web::Result<impl Responder> {
+    let config_key = match param.to_config_key() {
+        Ok(v) => v,
+        Err(err) => {
+            return Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+                "PARAM_ERROR".to_owned(),
+                Some(err.to_string()),
+            )));
+        }
+    };
+    match app
+        .config_route
+        .publish_beta_config(config_key, get_op_user(&req))
+        .await
+    {
+        Ok(_) => Ok(HttpResponse::Ok().json(ApiResult::success(Some(true)))),
+        Err(err) => Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+            "SYSTEM_ERROR".to_owned(),
+            Some(err.to_string()),
+        ))),
+    }
+}
+
+///
+/// 终止灰度，移除灰度配置
+pub async fn remove_beta_config(
+    web::Query(param): web::Query<OpsConfigBetaParam>,
+    app: web::Data<Arc<AppShareData>>,
+) -> actix_web::Result<impl Responder> {
+    let config_key = match param.to_config_key() {
+        Ok(v) => v,
+        Err(err) => {
+            return Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+                "PARAM_ERROR".to_owned(),
+                Some(err.to_string()),
+            )));
+        }
+    };
+    match app.config_route.remove_beta_config(config_key).await {
+        Ok(_) => Ok(HttpResponse::Ok().json(ApiResult::success(Some(true)))),
+        Err(err) => Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+            "SYSTEM_ERROR".to_owned(),
+            Some(err.to_string()),
+        ))),
+    }
+}
+
+///
+/// 按历史记录回滚配置，回滚内容作为新的历史记录发布
+pub async fn rollback_config(
+    req: HttpRequest,
+    web::Form(param): web::Form<OpsConfigRollbackParam>,
+    app: web::Data<Arc<AppShareData>>,
+) -> actix_web::Result<impl Responder> {
+    let config_key = match param.to_config_key() {
+        Ok(v) => v,
+        Err(err) => {
+            return Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+                "PARAM_ERROR".to_owned(),
+                Some(err.to_string()),
+            )));
+        }
+    };
+    let (history_id, md5) = match (param.history_id, param.md5) {
+        (Some(history_id), Some(md5)) if !md5.is_empty() => (history_id, md5),
+        _ => {
+            return Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+                "PARAM_ERROR".to_owned(),
+                Some("historyId or md5 can't empty".to_owned()),
+            )));
+        }
+    };
+    //md5的比较在状态机中通过cas_md5完成，这里只做快速失败
+    let current_md5 = match app
+        .config_addr
+        .send(ConfigCmd::GET(config_key.clone()))
+        .await
+    {
+        Ok(Ok(ConfigResult::DATA(_, current_md5))) => current_md5,
+        Ok(Ok(_)) => {
+            return Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+                "NOT_FOUND".to_owned(),
+                Some("config not exist".to_owned()),
+            )));
+        }
+        Ok(Err(err)) => {
+            return Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+                "SYSTEM_ERROR".to_owned(),
+                Some(err.to_string()),
+            )));
+        }
+        Err(err) => {
+            return Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+                "SYSTEM_ERROR".to_owned(),
+                Some(err.to_string()),
+            )));
+        }
+    };
+    if current_md5.as_str() != md5 {
+        return Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+            "CONFIG_CHANGED".to_owned(),
+            Some("config has been changed, please reload history".to_owned()),
+        )));
+    }
+    let content = match ConfigHistoryUtils::get_history(
+        &app.raft_table_manage,
+        &config_key,
+        history_id,
+    )
+    .await
+    {
+        Ok(Some(history)) => history.to_dto(&config_key).content.unwrap_or_default(),
+        Ok(None) => {
+            return Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+                "NOT_FOUND".to_owned(),
+                Some("config history not exist".to_owned()),
+            )));
+        }
+        Err(err) => {
+            return Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+                "SYSTEM_ERROR".to_owned(),
+                Some(err.to_string()),
+            )));
+        }
+    };
+    let mut set_req = SetConfigReq::new(config_key, Arc::new(content));
+    set_req.op_user = get_op_user(&req);
+    set_req.cas_md5 = Some(Arc::new(md5));
+    match app.config_route.set_config(set_req).await {
+        Ok(_) => Ok(HttpResponse::Ok().json(ApiResult::success(Some(true)))),
+        Err(err) if ConfigError::is_cas_conflict(&err) => {
+            Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+                "CONFIG_CHANGED".to_owned(),
+                Some("config has been changed, please reload history".to_owned()),
+            )))
+        }
+        Err(err) => Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+            "SYSTEM_ERROR".to_owned(),
+            Some(err.to_string()),
+        ))),
+    }
+}
+
+///
+/// 查询配置指定版本的内容，history_id为空时返回当前配置;
+/// 历史记录保存的是引用展开前的内容，当前配置也按原始内容比较
+async fn get_config_version(
+    app: &AppShareData,
+    config_key: &ConfigKey,
+    history_id: Option<u64>,
+) -> anyhow::Result<(Arc<String>, Arc<String>)> {
+    if let Some(history_id) = history_id {
+        let history =
+            ConfigHistoryUtils::get_history(&app.raft_table_manage, config_key, history_id)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("config version not exist"))?;
+        let content = history.to_dto(config_key).content.unwrap_or_default();
+        let md5 = get_md5(&content);
+        return Ok((Arc::new(content), Arc::new(md5)));
+    }
+    let cmd = ConfigCmd::GetRaw(config_key.clone());
+    match app.config_addr.send(cmd).await?? {
+        ConfigResult::QueryData(data) => {
+            let content = CONFIG_ENCRYPTION.decrypt(
+                &config_key.data_id,
+                data.encrypted_data_key.as_ref(),
+                data.content,
+            )?;
+            let md5 = get_md5(&content);
+            Ok((content, Arc::new(md5)))
+        }
+        _ => Err(anyhow::anyhow!("config version not exist")),
+    }
+}
+
+///
+/// 比较配置两个版本的差异，toId为空时与当前配置比较
+pub async fn diff_config_history(
+    web::Query(param): web::Query<OpsConfigDiffParam>,
+    app: web::Data<Arc<AppShareData>>,
+) -> actix_web::Result<impl Responder> {
+    let config_key = match param.to_config_key() {
+        Ok(v) => v,
+        Err(err) => {
+            return Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+                "PARAM_ERROR".to_owned(),
+                Some(err.to_string()),
+            )));
+        }
+    };
+    let from_id = if let Some(from_id) = param.from_id {
+        from_id
+    } else {
+        return Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+            "PARAM_ERROR".to_owned(),
+            Some("fromId can't empty".to_owned()),
+        )));
+    };
+    let from = get_config_version(&app, &config_key, Some(from_id)).await;
+    let to = get_config_version(&app, &config_key, param.to_id).await;
+    let ((from_content, from_md5), (to_content, to_md5)) = match (from, to) {
+        (Ok(from), Ok(to)) => (from, to),
+        (Err(err), _) | (_, Err(err)) => {
+            return Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+                "NOT_FOUND".to_owned(),
+                Some(err.to_string()),
+            )));
+        }
+    };
+    let to_name = if let Some(to_id) = param.to_id {
+        to_id.to_string()
+    } else {
+        "current".to_owned()
+    };
+    let (diff, stat) = ConfigDiffUtils::unified_diff(
+        &from_id.to_string(),
+        &from_content,
+        &to_name,
+        &to_content,
+        param.context_lines.unwrap_or(3),
+    );
+    let data = ConfigDiffDto {
+        tenant: config_key.tenant.to_string(),
+        group: config_key.group.to_string(),
+        data_id: config_key.data_id.to_string(),
+        from_id,
+        to_id: param.to_id,
+        from_md5: from_md5.to_string(),
+        to_md5: to_md5.to_string(),
+        diff,
+        added_lines: stat.added_lines,
+        removed_lines: stat.removed_lines,
+        unchanged_lines: stat.unchanged_lines,
+    };
+    Ok(HttpResponse::Ok().json(ApiResult::success(Some(data))))
+}
+
+///
+/// 按关键字或正则检索配置内容
+pub async fn search_config_content(
+    web::Query(request): web::Query<OpsConfigSearchRequest>,
+    config_addr: web::Data<Addr<ConfigActor>>,
+) -> actix_web::Result<impl Responder> {
+    let param = match request.to_param() {
+        Ok(v) => v,
+        Err(err) => {
+            return Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+                "PARAM_ERROR".to_owned(),
+                Some(err.to_string()),
+            )));
+        }
+    };
+    let candidates = match config_addr
+        .send(ConfigCmd::SearchContent(Box::new(param.clone())))
+        .await
+    {
+        Ok(Ok(ConfigResult::ConfigSearchCandidates(list))) => list,
+        Ok(Ok(_)) => {
+            return Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+                "SYSTEM_ERROR".to_owned(),
+                Some("config result error".to_owned()),
+            )));
+        }
+        Ok(Err(err)) => {
+            return Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+                "SYSTEM_ERROR".to_owned(),
+                Some(err.to_string()),
+            )));
+        }
+        Err(err) => {
+            return Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+                "SYSTEM_ERROR".to_owned(),
+                Some(err.to_string()),
+            )));
+        }
+    };
+    //逐行匹配在阻塞线程池中执行，不占用ConfigActor
+    match web::block(move || config_search::search_contents(&param, candidates)).await {
+        Ok(Ok((size, list))) => Ok(HttpResponse::Ok().json(ApiResult::success(Some(PageResult {
+            count: size as u64,
+            list,
+        })))),
+        Ok(Err(err)) => Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+            "PARAM_ERROR".to_owned(),
+            Some(err.to_string()),
+        ))),
+        Err(err) => Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+            "SYSTEM_ERROR".to_owned(),
+            Some(err.to_string()),
+        ))),
+    }
+}
+
+///
+/// 查询配置引用关系图
+pub async fn query_config_include_graph(
+    web::Query(request): web::Query<OpsConfigIncludeGraphRequest>,
+    app: web::Data<Arc<AppShareData>>,
+) -> actix_web::Result<impl Responder> {
+    let cmd = ConfigCmd::QueryIncludeGraph(Box::new(request.to_param()));
+    match app.config_addr.send(cmd).await {
+        Ok(Ok(ConfigResult::IncludeGraph(graph))) => {
+            Ok(HttpResponse::Ok().json(ApiResult::success(Some(*graph))))
+        }
+        Ok(Ok(_)) => Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+            "SYSTEM_ERROR".to_owned(),
+            Some("config result type is error".to_owned()),
+        ))),
+        Ok(Err(err)) => Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+            "SYSTEM_ERROR".to_owned(),
+            Some(err.to_string()),
+        ))),
+        Err(err) => Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+            "SYSTEM_ERROR".to_owned(),
+            Some(err.to_string()),
+        ))),
+    }
+}
+
+///
+/// 查询配置变更推送给各客户端的状态
+pub async fn query_config_push_trace(
+    web::Query(request): web::Query<OpsConfigPushTraceRequest>,
+    app: web::Data<Arc<AppShareData>>,
+) -> actix_web::Result<impl Responder> {
+    let param = match request.to_param() {
+        Ok(v) => v,
+        Err(err) => {
+            return Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+                "PARAM_ERROR".to_owned(),
+                Some(err.to_string()),
+            )));
+        }
+    };
+    match query_cluster_config_push_trace(&app, param).await {
+        Ok((size, list)) => Ok(HttpResponse::Ok().json(ApiResult::success(Some(PageResult {
+            count: size as u64,
+            list,
+        })))),
+        Err(err) => Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+            "SYSTEM_ERROR".to_owned(),
+            Some(err.to_string()),
+        ))),
+    }
+}
+
+///
+/// 查询集群中配置的监听客户端，或指定客户端监听的配置
+pub async fn query_config_listener(
+    web::Query(request): web::Query<OpsConfigListenerRequest>,
+    app: web::Data<Arc<AppShareData>>,
+) -> actix_web::Result<impl Responder> {
+    let param = match request.to_param() {
+        Ok(v) => v,
+        Err(err) => {
+            return Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+                "PARAM_ERROR".to_owned(),
+                Some(err.to_string()),
+            )));
+        }
+    };
+    match query_cluster_config_listener(&app, param).await {
+        Ok(list) => Ok(HttpResponse::Ok().json(ApiResult::success(Some(PageResult {
+            count: list.len() as u64,
+            list,
+        })))),
+        Err(err) => Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+            "SYSTEM_ERROR".to_owned(),
+            Some(err.to_string()),
+        ))),
+    }
+}
+
+pub async fn query_config_webhook_list(
+    app: web::Data<Arc<AppShareData>>,
+) -> actix_web::Result<impl Responder> {
+    match app
+        .config_webhook_manager
+        .send(ConfigWebhookCmd::QueryList)
+        .await
+    {
+        Ok(Ok(ConfigWebhookResult::WebhookList(list))) => {
+            Ok(HttpResponse::Ok().json(ApiResult::success(Some(PageResult {
+                count: list.len() as u64,
+                list,
+            }))))
+        }
+        Ok(Ok(_)) => Ok(
+            HttpResponse::Ok().json(ApiResult::success(Some(PageResult::<ConfigWebhookDO> {
+                count: 0,
+                list: vec![],
+            }))),
+        ),
+        Ok(Err(err)) => Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+            "SYSTEM_ERROR".to_owned(),
+            Some(err.to_string()),
+        ))),
+        Err(err) => Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+            "SYSTEM_ERROR".to_owned(),
+            Some(err.to_string()),
+        ))),
+    }
+}
+
+///
+/// 新增或更新webhook订阅，按name覆盖
+pub async fn set_config_webhook(
+    web::Json(webhook): web::Json<ConfigWebhookDO>,
+    app: web::Data<Arc<AppShareData>>,
+) -> actix_web::Result<impl Responder> {
+    if let Err(err) = webhook.check() {
+        return Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+            "PARAM_ERROR".to_owned(),
+            Some(err.to_string()),
+        )));
+    }
+    match app
+        .config_webhook_manager
+        .send(ConfigWebhookCmd::Set(webhook))
+        .await
+    {
+        Ok(Ok(_)) => Ok(HttpResponse::Ok().json(ApiResult::success(Some(true)))),
+        Ok(Err(err)) => Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+            "SYSTEM_ERROR".to_owned(),
+            Some(err.to_string()),
+        ))),
+        Err(err) => Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+            "SYSTEM_ERROR".to_owned(),
+            Some(err.to_string()),
+        ))),
+    }
+}
+
+pub async fn remove_config_webhook(
+    web::Query(param): web::Query<OpsConfigWebhookRemoveParam>,
+    app: web::Data<Arc<AppShareData>>,
+) -> actix_web::Result<impl Responder> {
+    let name = match param.name {
+        Some(v) if !v.is_empty() => v,
+        _ => {
+            return Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+                "PARAM_ERROR".to_owned(),
+                Some("name can't empty".to_owned()),
+            )));
+        }
+    };
+    match app
+        .config_webhook_manager
+        .send(ConfigWebhookCmd::Remove(name))
+        .await
+    {
+        Ok(Ok(_)) => Ok(HttpResponse::Ok().json(ApiResult::success(Some(true)))),
+        Ok(Err(err)) => Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+            "SYSTEM_ERROR".to_owned(),
+            Some(err.to_string()),
+        ))),
+        Err(err) => Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+            "SYSTEM_ERROR".to_owned(),
+            Some(err.to_string()),
+        ))),
+    }
+}
+
+///
+/// 查询webhook投递记录
+pub async fn query_config_webhook_log(
+    web::Query(request): web::Query<OpsConfigWebhookLogRequest>,
+    app: web::Data<Arc<AppShareData>>,
+) -> actix_web::Result<impl Responder> {
+    match query_leader_config_webhook_log(&app, request.to_param()).await {
+        Ok((size, list)) => Ok(HttpResponse::Ok().json(ApiResult::success(Some(PageResult {
+            count: size as u64,
+            list,
+        })))),
+        Err(err) => Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+            "SYSTEM_ERROR".to_owned(),
+            Some(err.to_string()),
+        ))),
+    }
+}
+
+pub async fn query_config_schema_list(
+    app: web::Data<Arc<AppShareData>>,
+) -> actix_web::Result<impl Responder> {
+    match ConfigSchemaUtils::query_schemas(&app.raft_table_manage).await {
+        Ok(list) => Ok(HttpResponse::Ok().json(ApiResult::success(Some(PageResult {
+            count: list.len() as u64,
+            list,
+        })))),
+        Err(err) => Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+            "SYSTEM_ERROR".to_owned(),
+            Some(err.to_string()),
+        ))),
+    }
+}
+
+///
+/// 新增或更新配置schema，按name覆盖
+pub async fn set_config_schema(
+    web::Json(schema): web::Json<ConfigSchemaDO>,
+    app: web::Data<Arc<AppShareData>>,
+) -> actix_web::Result<impl Responder> {
+    if let Err(err) = schema.check() {
+        return Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+            "PARAM_ERROR".to_owned(),
+            Some(err.to_string()),
+        )));
+    }
+    match ConfigSchemaUtils::set_schema(&app.raft_table_route, schema).await {
+        Ok(_) => Ok(HttpResponse::Ok().json(ApiResult::success(Some(true)))),
+        Err(err) => Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+            "SYSTEM_ERROR".to_owned(),
+            Some(err.to_string()),
+        ))),
+    }
+}
+
+pub async fn remove_config_schema(
+    web::Query(param): web::Query<OpsConfigSchemaRemoveParam>,
+    app: web::Data<Arc<AppShareData>>,
+) -> actix_web::Result<impl Responder> {
+    let name = match param.name {
+        Some(v) if !v.is_empty() => v,
+        _ => {
+            return Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+                "PARAM_ERROR".to_owned(),
+                Some("name can't empty".to_owned()),
+            )));
+        }
+    };
+    match ConfigSchemaUtils::remove_schema(&app.raft_table_route, &name).await {
+        Ok(_) => Ok(HttpResponse::Ok().json(ApiResult::success(Some(true)))),
+        Err(err) => Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+            "SYSTEM_ERROR".to_owned(),
+            Some(err.to_string()),
+        ))),
+    }
+}
+
+///
+/// 试校验配置内容，不发布配置
+pub async fn validate_config_schema(
+    web::Json(param): web::Json<OpsConfigSchemaValidateRequest>,
+    app: web::Data<Arc<AppShareData>>,
+) -> actix_web::Result<impl Responder> {
+    let (group, data_id) = match (param.group, param.data_id) {
+        (Some(group), Some(data_id)) if !group.is_empty() && !data_id.is_empty() => {
+            (group, data_id)
+        }
+        _ => {
+            return Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+                "PARAM_ERROR".to_owned(),
+                Some("group and dataId can't empty".to_owned()),
+            )));
+        }
+    };
+    let tenant = ConfigUtils::default_tenant(param.tenant.unwrap_or_default());
+    let config_key = ConfigKey::new(&data_id, &group, &tenant);
+    let schemas = match param.schema {
+        Some(schema) if !schema.is_empty() => {
+            let schema = ConfigSchemaDO {
+                name: "dry-run".to_owned(),
+                schema,
+                enable: true,
+                ..Default::default()
+            };
+            vec![schema]
+        }
+        _ => match ConfigSchemaUtils::query_schemas(&app.raft_table_manage).await {
+            Ok(v) => v,
+            Err(err) => {
+                return Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+                    "SYSTEM_ERROR".to_owned(),
+                    Some(err.to_string()),
+                )));
+            }
+        },
+    };
+    let config_type = param.config_type.map(Arc::new);
+    let errors = ConfigSchemaUtils::validate_by_schemas(
+        &schemas,
+        &config_key,
+        config_type.as_ref(),
+        &param.content.unwrap_or_default(),
+    );
+    Ok(
+        HttpResponse::Ok().json(ApiResult::success(Some(ConfigSchemaValidateResult {
+            valid: errors.is_empty(),
+            errors,
+        }))),
+    )
+}
+
+///
+/// 查询回收站中删除的配置
+pub async fn query_config_recycle_page(
+    web::Query(request): web::Query<OpsConfigRecycleRequest>,
+    app: web::Data<Arc<AppShareData>>,
+) -> actix_web::Result<impl Responder> {
+    let param = request.to_param();
+    match ConfigRecycleUtils::query_page(&app.raft_table_manage, &param).await {
+        Ok((size, list)) => Ok(HttpResponse::Ok().json(ApiResult::success(Some(PageResult {
+            count: size as u64,
+            list,
+        })))),
+        Err(err) => Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+            "SYSTEM_ERROR".to_owned(),
+            Some(err.to_string()),
+        ))),
+    }
+}
+
+pub async fn get_config_recycle(
+    web::Query(param): web::Query<OpsConfigRecycleParam>,
+    app: web::Data<Arc<AppShareData>>,
+) -> actix_web::Result<impl Responder> {
+    let (config_key, delete_time) = match param.to_key() {
+        Ok(v) => v,
+        Err(err) => {
+            return Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+                "PARAM_ERROR".to_owned(),
+                Some(err.to_string()),
+            )));
+        }
+    };
+    match ConfigRecycleUtils::get(&app.raft_table_manage, &config_key, delete_time).await {
+        Ok(Some(item)) => match item.to_dto(true) {
+            Ok(dto) => Ok(HttpResponse::Ok().json(ApiResult::success(Some(dto)))),
+            Err(err) => Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+                "SYSTEM_ERROR".to_owned(),
+                Some(err.to_string()),
+            ))),
+        },
+        Ok(None) => Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+            "NOT_FOUND".to_owned(),
+            Some("recycle config not exist".to_owned()),
+        ))),
+        Err(err) => Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+            "SYSTEM_ERROR".to_owned(),
+            Some(err.to_string()),
+        ))),
+    }
+}
+
+///
+/// 从回收站恢复配置
+pub async fn restore_config_recycle(
+    req: HttpRequest,
+    web::Form(param): web::Form<OpsConfigRecycleParam>,
+    app: web::Data<Arc<AppShareData>>,
+) -> actix_web::Result<impl Responder> {
+    let (config_key, delete_time) = match param.to_key() {
+        Ok(v) => v,
+        Err(err) => {
+            return Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+                "PARAM_ERROR".to_owned(),
+                Some(err.to_string()),
+            )));
+        }
+    };
+    match ConfigRecycleUtils::restore(
+        &app.raft_table_manage,
+        &app.raft_table_route,
+        &app.config_route,
+        &app.config_addr,
+        &config_key,
+        delete_time,
+        get_op_user(&req),
+    )
+    .await
+    {
+        Ok(_) => Ok(HttpResponse::Ok().json(ApiResult::success(Some(true)))),
+        Err(err) => Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+            "SYSTEM_ERROR".to_owned(),
+            Some(err.to_string()),
+        ))),
+    }
+}
+
+///
+/// 彻底删除回收站中的配置
+pub async fn purge_config_recycle(
+    web::Form(param): web::Form<OpsConfigRecycleParam>,
+    app: web::Data<Arc<AppShareData>>,
+) -> actix_web::Result<impl Responder> {
+    let (config_key, delete_time) = match param.to_key() {
+        Ok(v) => v,
+        Err(err) => {
+            return Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+                "PARAM_ERROR".to_owned(),
+                Some(err.to_string()),
+            )));
+        }
+    };
+    match ConfigRecycleUtils::purge(
+        &app.raft_table_manage,
+        &app.raft_table_route,
+        &app.config_addr,
+        &config_key,
+        delete_time,
+    )
+    .await
+    {
+        Ok(_) => Ok(HttpResponse::Ok().json(ApiResult::success(Some(true)))),
+        Err(err) => Ok(HttpResponse::Ok().json(ApiResult::<()>::error(
+            "SYSTEM_ERROR".to_owned(),
+            Some(err.to_string()),
+        ))),
+    }
+}
@@ -1,6 +1,13 @@
-use crate::config::config_index::ConfigQueryParam;
-use crate::config::core::ConfigInfoDto;
+use crate::config::config_include::ConfigIncludeGraphParam;
+use crate::config::config_index::{ConfigIndexMeta, ConfigQueryParam};
+use crate::config::config_push_trace::{ConfigPushStatus, ConfigPushTraceQueryParam};
+use crate::config::config_recycle::ConfigRecycleQueryParam;
+use crate::config::config_search::ConfigSearchParam;
+use crate::config::config_transfer::{ConfigImportPolicy, ConfigRewriteRule, ConfigRewriter};
+use crate::config::config_webhook::ConfigWebhookLogQueryParam;
+use crate::config::core::{ConfigInfoDto, ConfigKey};
 use crate::config::dal::ConfigHistoryParam;
+use crate::config::model::{ConfigBetaRule, ConfigListenerQueryParam};
 use crate::config::ConfigUtils;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
@@ -21,6 +28,8 @@ pub struct OpsConfigQueryListRequest {
     pub data_param: Option<String>,
     pub group: Option<String>,
     pub data_id: Option<String>,
+    pub app_name: Option<String>,
+    pub config_tags: Option<String>,
 }
 
 impl OpsConfigQueryListRequest {
@@ -32,6 +41,11 @@ impl OpsConfigQueryListRequest {
             offset,
             like_group: self.group_param,
             like_data_id: self.data_param,
+            app_name: self.app_name.filter(|e| !e.is_empty()).map(Arc::new),
+            config_tags: self
+                .config_tags
+                .map(|e| ConfigIndexMeta::split_tags(&e))
+                .unwrap_or_default(),
             ..Default::default()
         };
         if let Some(tenant) = self.tenant {
@@ -76,3 +90,398 @@ pub struct OpsConfigOptQueryListResponse {
     pub count: u64,
     pub list: Vec<ConfigInfoDto>,
 }
+
+///
+/// 由控制台参数构建配置key,group与dataId不能为空
+fn build_config_key(
+    tenant: &Option<String>,
+    group: &Option<String>,
+    data_id: &Option<String>,
+) -> anyhow::Result<ConfigKey> {
+    match (group, data_id) {
+        (Some(group), Some(data_id)) if !group.is_empty() && !data_id.is_empty() => {
+            let tenant = ConfigUtils::default_tenant(tenant.clone().unwrap_or_default());
+            Ok(ConfigKey::new(data_id, group, &tenant))
+        }
+        _ => Err(anyhow::anyhow!("group or dataId can't empty")),
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct OpsConfigBetaParam {
+    pub tenant: Option<String>,
+    pub group: Option<String>,
+    pub data_id: Option<String>,
+    pub content: Option<String>,
+    pub beta_ips: Option<String>,
+    pub beta_labels: Option<String>,
+}
+
+impl OpsConfigBetaParam {
+    pub fn to_config_key(&self) -> anyhow::Result<ConfigKey> {
+        build_config_key(&self.tenant, &self.group, &self.data_id)
+    }
+
+    pub fn to_rule(&self) -> ConfigBetaRule {
+        let ips = self
+            .beta_ips
+            .as_ref()
+            .map(|e| ConfigBetaRule::parse_ips(e))
+            .unwrap_or_default();
+        let labels = self
+            .beta_labels
+            .as_ref()
+            .map(|e| ConfigBetaRule::parse_labels(e))
+            .unwrap_or_default();
+        ConfigBetaRule::new(ips, labels)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct OpsConfigRollbackParam {
+    pub tenant: Option<String>,
+    pub group: Option<String>,
+    pub data_id: Option<String>,
+    pub history_id: Option<u64>,
+    ///查看历史记录时配置的md5,用于判断配置是否已被修改
+    pub md5: Option<String>,
+}
+
+impl OpsConfigRollbackParam {
+    pub fn to_config_key(&self) -> anyhow::Result<ConfigKey> {
+        build_config_key(&self.tenant, &self.group, &self.data_id)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct OpsConfigRecycleRequest {
+    pub tenant: Option<String>,
+    pub group: Option<String>,
+    pub data_id: Option<String>,
+    pub page_no: Option<usize>,
+    pub page_size: Option<usize>,
+}
+
+impl OpsConfigRecycleRequest {
+    pub fn to_param(self) -> ConfigRecycleQueryParam {
+        let limit = self.page_size.unwrap_or(20);
+        let offset = (self.page_no.unwrap_or(1).max(1) - 1) * limit;
+        ConfigRecycleQueryParam {
+            tenant: Some(ConfigUtils::default_tenant(self.tenant.unwrap_or_default())),
+            group: self.group,
+            data_id: self.data_id,
+            offset,
+            limit,
+        }
+    }
+}
+
+///
+/// 指定一条回收记录，删除时间用于区分同一配置的多次删除
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct OpsConfigRecycleParam {
+    pub tenant: Option<String>,
+    pub group: Option<String>,
+    pub data_id: Option<String>,
+    pub delete_time: Option<i64>,
+}
+
+impl OpsConfigRecycleParam {
+    pub fn to_key(&self) -> anyhow::Result<(ConfigKey, i64)> {
+        match (&self.group, &self.data_id, self.delete_time) {
+            (Some(group), Some(data_id), Some(delete_time))
+                if !group.is_empty() && !data_id.is_empty() =>
+            {
+                let tenant = ConfigUtils::default_tenant(self.tenant.clone().unwrap_or_default());
+                Ok((ConfigKey::new(data_id, group, &tenant), delete_time))
+            }
+            _ => Err(anyhow::anyhow!("group, dataId or deleteTime can't empty")),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct OpsConfigDiffParam {
+    pub tenant: Option<String>,
+    pub group: Option<String>,
+    pub data_id: Option<String>,
+    pub from_id: Option<u64>,
+    ///为空时与当前配置比较
+    pub to_id: Option<u64>,
+    pub context_lines: Option<usize>,
+}
+
+impl OpsConfigDiffParam {
+    pub fn to_config_key(&self) -> anyhow::Result<ConfigKey> {
+        build_config_key(&self.tenant, &self.group, &self.data_id)
+    }
+}
+
+///
+/// 按配置(dataId不为空时)或客户端ip、连接id查询监听
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct OpsConfigListenerRequest {
+    pub tenant: Option<String>,
+    pub group: Option<String>,
+    pub data_id: Option<String>,
+    pub client_ip: Option<String>,
+    pub conn_id: Option<String>,
+}
+
+impl OpsConfigListenerRequest {
+    pub fn to_param(self) -> anyhow::Result<ConfigListenerQueryParam> {
+        let client_ip = self.client_ip.filter(|e| !e.is_empty());
+        let conn_id = self.conn_id.filter(|e| !e.is_empty());
+        let key = match self.data_id.filter(|e| !e.is_empty()) {
+            Some(data_id) => {
+                let group = self
+                    .group
+                    .filter(|e| !e.is_empty())
+                    .unwrap_or("DEFAULT_GROUP".to_owned());
+                let tenant = ConfigUtils::default_tenant(self.tenant.unwrap_or_default());
+                Some(ConfigKey::new(&data_id, &group, &tenant).build_key())
+            }
+            None => None,
+        };
+        if key.is_none() && client_ip.is_none() && conn_id.is_none() {
+            return Err(anyhow::anyhow!(
+                "dataId, clientIp and connId can't all empty"
+            ));
+        }
+        Ok(ConfigListenerQueryParam {
+            key,
+            client_ip,
+            conn_id,
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct OpsConfigSearchRequest {
+    pub page_no: Option<usize>,
+    pub page_size: Option<usize>,
+    pub tenant: Option<String>,
+    pub group: Option<String>,
+    pub keyword: Option<String>,
+    pub regex: Option<bool>,
+    pub ignore_case: Option<bool>,
+    pub max_lines: Option<usize>,
+}
+
+impl OpsConfigSearchRequest {
+    pub fn to_param(self) -> anyhow::Result<ConfigSearchParam> {
+        let keyword = self.keyword.unwrap_or_default();
+        if keyword.is_empty() {
+            return Err(anyhow::anyhow!("keyword can't empty"));
+        }
+        let limit = self.page_size.unwrap_or(20);
+        let offset = (self.page_no.unwrap_or(1).max(1) - 1) * limit;
+        let param = ConfigSearchParam {
+            keyword,
+            regex: self.regex.unwrap_or_default(),
+            ignore_case: self.ignore_case.unwrap_or_default(),
+            tenant: self
+                .tenant
+                .map(|e| Arc::new(ConfigUtils::default_tenant(e))),
+            group: self.group.filter(|e| !e.is_empty()).map(Arc::new),
+            max_lines: self.max_lines.unwrap_or(10),
+            offset,
+            limit,
+        };
+        //提前校验关键字及正则，无效请求不进入ConfigActor
+        param.build_matcher()?;
+        Ok(param)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct OpsConfigCloneItem {
+    pub group: String,
+    pub data_id: String,
+}
+
+///
+/// 跨命名空间复制配置，group不为空时复制整个分组，items指定复制的配置
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct OpsConfigCloneRequest {
+    pub src_tenant: Option<String>,
+    pub target_tenant: Option<String>,
+    pub group: Option<String>,
+    #[serde(default)]
+    pub items: Vec<OpsConfigCloneItem>,
+    ///配置已存在时的处理策略: ABORT/SKIP/OVERWRITE
+    pub policy: Option<String>,
+    #[serde(default)]
+    pub rules: Vec<ConfigRewriteRule>,
+}
+
+impl OpsConfigCloneRequest {
+    ///
+    /// 校验参数，返回(源命名空间,目标命名空间,冲突策略,重写规则)
+    pub fn to_clone_param(
+        &self,
+    ) -> anyhow::Result<(Arc<String>, Arc<String>, ConfigImportPolicy, ConfigRewriter)> {
+        if self.group.as_ref().map(|e| e.is_empty()).unwrap_or(true) && self.items.is_empty() {
+            return Err(anyhow::anyhow!("group and items can't all empty"));
+        }
+        let src_tenant = ConfigUtils::default_tenant(self.src_tenant.clone().unwrap_or_default());
+        let target_tenant =
+            ConfigUtils::default_tenant(self.target_tenant.clone().unwrap_or_default());
+        if src_tenant == target_tenant && self.rules.is_empty() {
+            return Err(anyhow::anyhow!(
+                "targetTenant can't be same as srcTenant without rewrite rules"
+            ));
+        }
+        let policy = match &self.policy {
+            Some(v) if !v.is_empty() => ConfigImportPolicy::new_by_value(v)?,
+            _ => ConfigImportPolicy::default(),
+        };
+        let rewriter = ConfigRewriter::new(&self.rules)?;
+        Ok((
+            Arc::new(src_tenant),
+            Arc::new(target_tenant),
+            policy,
+            rewriter,
+        ))
+    }
+
+    ///
+    /// 是否为需要复制的配置
+    pub fn is_selected(&self, group: &str, data_id: &str) -> bool {
+        if let Some(g) = &self.group {
+            if !g.is_empty() && g == group {
+                return true;
+            }
+        }
+        self.items
+            .iter()
+            .any(|e| e.group == group && e.data_id == data_id)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct OpsConfigWebhookRemoveParam {
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct OpsConfigSchemaRemoveParam {
+    pub name: Option<String>,
+}
+
+///
+/// 试校验配置内容，未指定schema时按已绑定的schema校验
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct OpsConfigSchemaValidateRequest {
+    pub tenant: Option<String>,
+    pub group: Option<String>,
+    pub data_id: Option<String>,
+    pub config_type: Option<String>,
+    pub content: Option<String>,
+    pub schema: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct OpsConfigWebhookLogRequest {
+    pub webhook_name: Option<String>,
+    pub data_id: Option<String>,
+    pub success: Option<bool>,
+    pub page_no: Option<usize>,
+    pub page_size: Option<usize>,
+}
+
+impl OpsConfigWebhookLogRequest {
+    pub fn to_param(self) -> ConfigWebhookLogQueryParam {
+        let limit = self.page_size.unwrap_or(20);
+        let offset = (self.page_no.unwrap_or(1).max(1) - 1) * limit;
+        ConfigWebhookLogQueryParam {
+            webhook_name: self.webhook_name,
+            data_id: self.data_id,
+            success: self.success,
+            offset,
+            limit,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct OpsConfigPushTraceRequest {
+    pub tenant: Option<String>,
+    pub group: Option<String>,
+    pub data_id: Option<String>,
+    pub conn_id: Option<String>,
+    pub status: Option<String>,
+    pub page_no: Option<usize>,
+    pub page_size: Option<usize>,
+}
+
+impl OpsConfigPushTraceRequest {
+    pub fn to_param(self) -> anyhow::Result<ConfigPushTraceQueryParam> {
+        let limit = self.page_size.unwrap_or(20);
+        let offset = (self.page_no.unwrap_or(1).max(1) - 1) * limit;
+        let key = match self.data_id.filter(|e| !e.is_empty()) {
+            Some(data_id) => {
+                let group = self
+                    .group
+                    .filter(|e| !e.is_empty())
+                    .unwrap_or("DEFAULT_GROUP".to_owned());
+                let tenant = ConfigUtils::default_tenant(self.tenant.unwrap_or_default());
+                Some(ConfigKey::new(&data_id, &group, &tenant).build_key())
+            }
+            None => None,
+        };
+        let status = match self.status.filter(|e| !e.is_empty()) {
+            Some(v) => Some(ConfigPushStatus::new_by_value(&v)?),
+            None => None,
+        };
+        Ok(ConfigPushTraceQueryParam {
+            key,
+            conn_id: self.conn_id.filter(|e| !e.is_empty()).map(Arc::new),
+            status,
+            offset,
+            limit,
+        })
+    }
+}
+
+///
+/// dataId为空时查询命名空间下所有引用关系
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct OpsConfigIncludeGraphRequest {
+    pub tenant: Option<String>,
+    pub group: Option<String>,
+    pub data_id: Option<String>,
+}
+
+impl OpsConfigIncludeGraphRequest {
+    pub fn to_param(self) -> ConfigIncludeGraphParam {
+        let tenant = ConfigUtils::default_tenant(self.tenant.unwrap_or_default());
+        let group = self
+            .group
+            .filter(|e| !e.is_empty())
+            .unwrap_or("DEFAULT_GROUP".to_owned());
+        let key = self
+            .data_id
+            .filter(|e| !e.is_empty())
+            .map(|data_id| ConfigKey::new(&data_id, &group, &tenant));
+        ConfigIncludeGraphParam {
+            tenant: Arc::new(tenant),
+            key,
+        }
+    }
+}
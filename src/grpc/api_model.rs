@@ -5,6 +5,8 @@ use serde::{Deserialize, Serialize};
 pub const SUCCESS_CODE: u16 = 200u16;
 pub const NOT_FOUND: u16 = 300u16;
 pub const ERROR_CODE: u16 = 500u16;
+pub const CONFIG_CAS_CONFLICT: u16 = 409u16;
+pub const CONFIG_SCHEMA_INVALID: u16 = 400u16;
 
 pub const INTERNAL_MODEL: &str = "internal";
 pub const CONFIG_MODEL: &str = "config";
@@ -252,6 +254,7 @@ pub struct SubscribeServiceRequest {
 
     pub subscribe: bool,
     pub clusters: Option<String>,
+    pub selector: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Default)]
@@ -319,6 +322,7 @@ pub struct ServiceQueryRequest {
 
     pub cluster: Option<String>,
     pub healthy_only: Option<bool>,
+    pub selector: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Default)]
@@ -332,6 +336,36 @@ pub struct ServiceQueryResponse {
     pub service_info: Option<ServiceInfo>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PickInstanceRequest {
+    pub module: Option<String>,
+    pub request_id: Option<String>,
+    pub headers: Option<HashMap<String, String>>,
+
+    pub namespace: Option<String>,
+    pub service_name: Option<String>,
+    pub group_name: Option<String>,
+
+    pub clusters: Option<String>,
+    pub selector: Option<String>,
+    pub strategy: Option<String>,
+    pub hash_key: Option<String>,
+    pub prefer_cluster: Option<String>,
+    pub prefer_zone: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PickInstanceResponse {
+    pub result_code: u16,
+    pub error_code: u16,
+    pub message: Option<String>,
+    pub request_id: Option<String>,
+
+    pub instance: Option<Instance>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct NotifySubscriberRequest {
@@ -5,16 +5,23 @@ use std::{
 };
 
 use crate::{
-    config::core::{ConfigActor, ConfigCmd, ConfigKey},
+    common::AppSysConfig,
+    config::{
+        config_push_trace::{ConfigPushTraceDto, ConfigPushTraceQueryParam, ConfigPushTraceStore},
+        core::{ConfigActor, ConfigCmd, ConfigKey},
+    },
     naming::{
         core::{NamingActor, NamingCmd},
         model::{ServiceInfo, ServiceKey},
     },
-    now_millis,
+    now_millis, now_millis_i64,
 };
 
 use super::{
-    api_model::{ConfigChangeNotifyRequest, NotifySubscriberRequest, CONFIG_MODEL, NAMING_MODEL},
+    api_model::{
+        BaseResponse, ConfigChangeNotifyRequest, NotifySubscriberRequest, CONFIG_MODEL,
+        NAMING_MODEL, SUCCESS_CODE,
+    },
     bistream_conn::{BiStreamConn, BiStreamSenderCmd},
     handler::converter::ModelConverter,
     nacos_proto::Payload,
@@ -49,13 +56,15 @@ pub struct BiStreamManage {
     request_id: u64,
     config_addr: Option<Addr<ConfigActor>>,
     naming_addr: Option<Addr<NamingActor>>,
+    config_push_trace: ConfigPushTraceStore,
 }
 
 impl BiStreamManage {
-    pub fn new() -> Self {
+    pub fn new(sys_config: &AppSysConfig) -> Self {
         Self {
             detection_time_out: 15000,
             response_time_out: 3000,
+            config_push_trace: ConfigPushTraceStore::new(sys_config.config_push_trace_size),
             ..Default::default()
         }
     }
@@ -152,11 +161,33 @@ impl BiStreamManage {
         }
     }
 
+    ///
+    /// 记录客户端对配置变更通知的响应
+    fn ack_config_push(&mut self, client_id: &Arc<String>, payload: &Payload) {
+        let body = match &payload.body {
+            Some(v) => v,
+            None => return,
+        };
+        if let Ok(res) = serde_json::from_slice::<BaseResponse>(&body.value) {
+            if let Some(request_id) = &res.request_id {
+                self.config_push_trace.ack(
+                    request_id,
+                    client_id,
+                    res.result_code == SUCCESS_CODE,
+                    res.message,
+                    now_millis_i64(),
+                );
+            }
+        }
+    }
+
     pub fn time_out_heartbeat(&self, ctx: &mut actix::Context<Self>) {
         ctx.run_later(Duration::new(2, 0), |act, ctx| {
             let now = now_millis();
             act.check_active_time_set(now);
             act.check_response_time_set(now);
+            act.config_push_trace
+                .check_timeout(now as i64, act.response_time_out as i64);
             act.time_out_heartbeat(ctx);
         });
     }
@@ -202,10 +233,12 @@ pub enum BiStreamManageCmd {
     NotifyConfig(ConfigKey, HashSet<Arc<String>>),
     NotifyNaming(ServiceKey, HashSet<Arc<String>>, ServiceInfo),
     QueryConnList,
+    QueryConfigPushTrace(ConfigPushTraceQueryParam),
 }
 
 pub enum BiStreamManageResult {
     ConnList(Vec<Arc<String>>),
+    ConfigPushTracePage(usize, Vec<ConfigPushTraceDto>),
     None,
 }
 
@@ -216,7 +249,10 @@ impl Handler<BiStreamManageCmd> for BiStreamManage {
         match msg {
             BiStreamManageCmd::Response(client_id, payload) => {
                 //println!("BiStreamManageCmd payload:{},client_id:{}",PayloadUtils::get_payload_string(&payload),&client_id);
-                if let Some(_t) = PayloadUtils::get_payload_type(&payload) {
+                if let Some(t) = PayloadUtils::get_payload_type(&payload) {
+                    if "ConfigChangeNotifyResponse" == t {
+                        self.ack_config_push(&client_id, &payload);
+                    }
                     self.active_client(client_id).ok();
                     //if "ClientDetectionResponse"== t {
                     //}
@@ -238,11 +274,12 @@ impl Handler<BiStreamManageCmd> for BiStreamManage {
                 self.active_client(client_id)?;
             }
             BiStreamManageCmd::NotifyConfig(config_key, client_id_set) => {
+                let request_id = self.next_request_id();
                 let request = ConfigChangeNotifyRequest {
-                    group: config_key.group,
-                    data_id: config_key.data_id,
-                    tenant: config_key.tenant,
-                    request_id: Some(self.next_request_id()),
+                    group: config_key.group.clone(),
+                    data_id: config_key.data_id.clone(),
+                    tenant: config_key.tenant.clone(),
+                    request_id: Some(request_id.clone()),
                     module: Some(CONFIG_MODEL.to_string()),
                     ..Default::default()
                 };
@@ -250,11 +287,22 @@ impl Handler<BiStreamManageCmd> for BiStreamManage {
                     "ConfigChangeNotifyRequest",
                     serde_json::to_string(&request).unwrap(),
                 ));
-                for item in &client_id_set {
-                    if let Some(item) = self.conn_cache.get(item) {
+                let mut trace_clients = Vec::with_capacity(client_id_set.len());
+                for client_id in client_id_set {
+                    let exist = if let Some(item) = self.conn_cache.get(&client_id) {
                         item.conn.do_send(BiStreamSenderCmd::Send(payload.clone()));
-                    }
+                        true
+                    } else {
+                        false
+                    };
+                    trace_clients.push((client_id, exist));
                 }
+                self.config_push_trace.add_push(
+                    &config_key,
+                    request_id,
+                    trace_clients,
+                    now_millis_i64(),
+                );
             }
             BiStreamManageCmd::NotifyNaming(service_key, client_id_set, service_info) => {
                 let service_info = ModelConverter::to_api_service_info(service_info);
@@ -284,6 +332,10 @@ impl Handler<BiStreamManageCmd> for BiStreamManage {
                 }
                 return Ok(BiStreamManageResult::ConnList(list));
             }
+            BiStreamManageCmd::QueryConfigPushTrace(param) => {
+                let (size, list) = self.config_push_trace.query(&param);
+                return Ok(BiStreamManageResult::ConfigPushTracePage(size, list));
+            }
         }
         Ok(BiStreamManageResult::None)
     }
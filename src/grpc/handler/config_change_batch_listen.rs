@@ -5,6 +5,7 @@ use std::sync::Arc;
 use crate::{
     common::appdata::AppShareData,
     config::core::{ConfigActor, ConfigCmd, ConfigKey, ConfigResult, ListenerItem},
+    config::model::ConfigClientInfo,
     grpc::{
         api_model::{
             BaseResponse, ConfigBatchListenRequest, ConfigChangeBatchListenResponse, ConfigContext,
@@ -43,7 +44,12 @@ impl PayloadHandler for ConfigChangeBatchListenRequestHandler {
             listener_items.push(ListenerItem::new(key, item.md5));
         }
         let cmd = if request.listen {
-            ConfigCmd::Subscribe(listener_items, request_meta.connection_id)
+            let mut labels = request_meta.labels;
+            if let Some(headers) = request.headers {
+                labels.extend(headers);
+            }
+            let client = ConfigClientInfo::new(request_meta.client_ip, labels);
+            ConfigCmd::Subscribe(listener_items, request_meta.connection_id, client)
         } else {
             ConfigCmd::RemoveSubscribe(listener_items, request_meta.connection_id)
         };
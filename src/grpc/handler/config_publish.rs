@@ -5,8 +5,11 @@ use std::sync::Arc;
 use crate::{
     common::appdata::AppShareData,
     config::core::{ConfigActor, ConfigAsyncCmd, ConfigCmd, ConfigKey, ConfigResult},
+    config::model::ConfigError,
     grpc::{
-        api_model::{BaseResponse, ConfigPublishRequest},
+        api_model::{
+            BaseResponse, ConfigPublishRequest, CONFIG_CAS_CONFLICT, CONFIG_SCHEMA_INVALID,
+        },
         nacos_proto::Payload,
         PayloadHandler, PayloadUtils,
     },
@@ -34,10 +37,27 @@ impl PayloadHandler for ConfigPublishRequestHandler {
     ) -> anyhow::Result<Payload> {
         let body_vec = request_payload.body.unwrap_or_default().value;
         let request: ConfigPublishRequest = serde_json::from_slice(&body_vec)?;
-        let req = SetConfigReq::new(
+        let mut req = SetConfigReq::new(
             ConfigKey::new(&request.data_id, &request.group, &request.tenant),
             request.content,
         );
+        if let Some(addition_map) = &request.addition_map {
+            req.config_type = addition_map
+                .get("type")
+                .filter(|e| !e.is_empty())
+                .map(|e| Arc::new(e.to_owned()));
+            req.desc = addition_map.get("desc").map(|e| Arc::new(e.to_owned()));
+            req.config_tags = addition_map
+                .get("config_tags")
+                .map(|e| Arc::new(e.to_owned()));
+            req.app_name = addition_map.get("appName").map(|e| Arc::new(e.to_owned()));
+            //客户端加密插件已加密的内容
+            req.encrypted_data_key = addition_map
+                .get("encryptedDataKey")
+                .filter(|e| !e.is_empty())
+                .map(|e| Arc::new(e.to_owned()));
+        }
+        req.cas_md5 = request.cas_md5.filter(|e| !e.is_empty()).map(Arc::new);
         match self.app_data.config_route.set_config(req).await {
             Ok(_res) => {
                 //let res:ConfigResult = res.unwrap();
@@ -48,6 +68,24 @@ impl PayloadHandler for ConfigPublishRequestHandler {
                     serde_json::to_string(&response)?,
                 ))
             }
+            Err(err) if ConfigError::is_cas_conflict(&err) => {
+                let mut response =
+                    BaseResponse::build_error_response(CONFIG_CAS_CONFLICT, err.to_string());
+                response.request_id = request.request_id;
+                Ok(PayloadUtils::build_payload(
+                    "ConfigPublishResponse",
+                    serde_json::to_string(&response)?,
+                ))
+            }
+            Err(err) if ConfigError::is_schema_invalid(&err) => {
+                let mut response =
+                    BaseResponse::build_error_response(CONFIG_SCHEMA_INVALID, err.to_string());
+                response.request_id = request.request_id;
+                Ok(PayloadUtils::build_payload(
+                    "ConfigPublishResponse",
+                    serde_json::to_string(&response)?,
+                ))
+            }
             Err(err) => {
                 let mut response = BaseResponse::build_error_response(500u16, err.to_string());
                 response.request_id = request.request_id;
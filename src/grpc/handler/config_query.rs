@@ -6,6 +6,7 @@ use crate::grpc::api_model::NOT_FOUND;
 use crate::{
     common::appdata::AppShareData,
     config::core::{ConfigActor, ConfigCmd, ConfigKey, ConfigResult},
+    config::model::ConfigClientInfo,
     grpc::{
         api_model::{
             BaseResponse, ConfigPublishRequest, ConfigQueryRequest, ConfigQueryResponse,
@@ -33,15 +34,19 @@ impl PayloadHandler for ConfigQueryRequestHandler {
     async fn handle(
         &self,
         request_payload: crate::grpc::nacos_proto::Payload,
-        _request_meta: crate::grpc::RequestMeta,
+        request_meta: crate::grpc::RequestMeta,
     ) -> anyhow::Result<Payload> {
         let body_vec = request_payload.body.unwrap_or_default().value;
         let request: ConfigQueryRequest = serde_json::from_slice(&body_vec)?;
-        let cmd = ConfigCmd::GET(ConfigKey::new(
-            &request.data_id,
-            &request.group,
-            &request.tenant,
-        ));
+        let mut labels = request_meta.labels;
+        if let Some(headers) = &request.headers {
+            labels.extend(headers.clone());
+        }
+        let client = ConfigClientInfo::new(request_meta.client_ip, labels);
+        let cmd = ConfigCmd::GetByClient(
+            ConfigKey::new(&request.data_id, &request.group, &request.tenant),
+            client,
+        );
         let mut response = ConfigQueryResponse {
             request_id: request.request_id,
             ..Default::default()
@@ -51,12 +56,20 @@ impl PayloadHandler for ConfigQueryRequestHandler {
                 //let res:ConfigResult = res.unwrap();
                 let r: ConfigResult = res.unwrap();
                 match r {
-                    ConfigResult::DATA(content, md5) => {
+                    ConfigResult::QueryData(data) => {
                         //v.to_owned()
+                        let md5 = data.md5;
                         response.result_code = SUCCESS_CODE;
-                        response.content = content;
-                        response.content_type = Some("text".to_owned());
-                        //response.encrypted_data_key = Some("".to_owned());
+                        response.content = data.content;
+                        response.content_type = Some(
+                            data.config_type
+                                .map(|e| e.as_ref().to_owned())
+                                .unwrap_or("text".to_owned()),
+                        );
+                        response.beta = data.beta;
+                        response.encrypted_data_key =
+                            data.encrypted_data_key.map(|e| e.as_ref().to_owned());
+                        response.last_modified = data.last_modified as u64;
                         //java nacos中定义tag类型是String;
                         //nacos-sdk-go中定义tag类型为bool, nacos-sdk-go中直接设置 response.tag = request.tag会报错
                         if let Some(tag) = request.tag {
@@ -6,8 +6,8 @@ use self::{
     config_change_batch_listen::ConfigChangeBatchListenRequestHandler,
     config_publish::ConfigPublishRequestHandler, config_query::ConfigQueryRequestHandler,
     config_remove::ConfigRemoveRequestHandler, naming_batch_instance::BatchInstanceRequestHandler,
-    naming_instance::InstanceRequestHandler, naming_route::NamingRouteRequestHandler,
-    naming_service_list::ServiceListRequestHandler,
+    naming_instance::InstanceRequestHandler, naming_pick_instance::PickInstanceRequestHandler,
+    naming_route::NamingRouteRequestHandler, naming_service_list::ServiceListRequestHandler,
     naming_service_query::ServiceQueryRequestHandler,
     naming_subscribe_service::SubscribeServiceRequestHandler, raft_route::RaftRouteRequestHandler,
 };
@@ -30,6 +30,7 @@ pub mod config_remove;
 pub mod converter;
 pub mod naming_batch_instance;
 pub mod naming_instance;
+pub mod naming_pick_instance;
 pub mod naming_route;
 pub mod naming_service_list;
 pub mod naming_service_query;
@@ -153,6 +154,10 @@ impl InvokerHandler {
             "ServiceListRequest",
             Box::new(ServiceListRequestHandler::new(app_data.clone())),
         );
+        self.add_handler(
+            "PickInstanceRequest",
+            Box::new(PickInstanceRequestHandler::new(app_data.clone())),
+        );
     }
 }
 
@@ -131,6 +131,7 @@ impl PayloadHandler for BatchInstanceRequestHandler {
                     enabled: !instance.enabled,
                     ephemeral: false,
                     from_update: false,
+                    healthy_only: false,
                 };
                 NamingCmd::Update(instance, Some(update_tag))
             };
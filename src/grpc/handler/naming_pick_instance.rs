@@ -0,0 +1,101 @@
+use std::sync::Arc;
+
+use crate::{
+    common::appdata::AppShareData,
+    grpc::{
+        api_model::{PickInstanceRequest, PickInstanceResponse, ERROR_CODE, SUCCESS_CODE},
+        nacos_proto::Payload,
+        PayloadHandler, PayloadUtils,
+    },
+    naming::{
+        balancer::PickInstanceParam,
+        core::{NamingCmd, NamingResult},
+        model::ServiceKey,
+        NamingUtils,
+    },
+};
+use async_trait::async_trait;
+
+use super::converter::ModelConverter;
+
+pub struct PickInstanceRequestHandler {
+    app_data: Arc<AppShareData>,
+}
+
+impl PickInstanceRequestHandler {
+    pub fn new(app_data: Arc<AppShareData>) -> Self {
+        Self { app_data }
+    }
+}
+
+#[async_trait]
+impl PayloadHandler for PickInstanceRequestHandler {
+    async fn handle(
+        &self,
+        request_payload: crate::grpc::nacos_proto::Payload,
+        _request_meta: crate::grpc::RequestMeta,
+    ) -> anyhow::Result<Payload> {
+        let body_vec = request_payload.body.unwrap_or_default().value;
+        let request: PickInstanceRequest = serde_json::from_slice(&body_vec)?;
+        let mut response = PickInstanceResponse {
+            request_id: request.request_id,
+            ..Default::default()
+        };
+        let param = match PickInstanceParam::build(
+            request.clusters.unwrap_or_default(),
+            request.selector.as_deref().unwrap_or_default(),
+            request.strategy.as_deref().unwrap_or_default(),
+            request.hash_key,
+            request.prefer_cluster,
+            request.prefer_zone,
+        ) {
+            Ok(v) => v,
+            Err(err) => {
+                response.result_code = ERROR_CODE;
+                response.error_code = 500u16;
+                response.message = Some(err.to_string());
+                return Ok(PayloadUtils::build_payload(
+                    "ErrorResponse",
+                    serde_json::to_string(&response)?,
+                ));
+            }
+        };
+        let namespace = NamingUtils::default_namespace(request.namespace.unwrap_or_default());
+        let key = ServiceKey::new(
+            &namespace,
+            &NamingUtils::default_group(request.group_name.unwrap_or_default()),
+            &request.service_name.unwrap_or_default(),
+        );
+        let cmd = NamingCmd::PickInstance(key, param);
+        match self.app_data.naming_addr.send(cmd).await {
+            Ok(res) => {
+                let result: NamingResult = res?;
+                match result {
+                    NamingResult::Instance(instance) => {
+                        response.instance =
+                            Some(ModelConverter::to_api_instance(instance.as_ref().clone()));
+                        response.result_code = SUCCESS_CODE;
+                    }
+                    _ => {
+                        response.result_code = ERROR_CODE;
+                        response.error_code = 500u16;
+                        response.message = Some("no available instance".to_owned());
+                    }
+                };
+            }
+            Err(err) => {
+                response.result_code = ERROR_CODE;
+                response.error_code = 500u16;
+                response.message = Some(err.to_string());
+                return Ok(PayloadUtils::build_payload(
+                    "ErrorResponse",
+                    serde_json::to_string(&response)?,
+                ));
+            }
+        };
+        Ok(PayloadUtils::build_payload(
+            "PickInstanceResponse",
+            serde_json::to_string(&response)?,
+        ))
+    }
+}
@@ -15,6 +15,7 @@ use crate::{
     naming::{
         core::{NamingActor, NamingCmd, NamingResult},
         model::{Instance, ServiceInfo, ServiceKey},
+        selector::InstanceSelector,
         NamingUtils,
     },
     now_millis_i64,
@@ -51,6 +52,19 @@ impl PayloadHandler for ServiceQueryRequestHandler {
             request_id: request.request_id,
             ..Default::default()
         };
+        let selector =
+            match InstanceSelector::parse_param(request.selector.as_deref().unwrap_or_default()) {
+                Ok(v) => v.map(Arc::new),
+                Err(err) => {
+                    response.result_code = ERROR_CODE;
+                    response.error_code = 500u16;
+                    response.message = Some(err.to_string());
+                    return Ok(PayloadUtils::build_payload(
+                        "ErrorResponse",
+                        serde_json::to_string(&response)?,
+                    ));
+                }
+            };
         let cluster = if let Some(v) = request.cluster.as_ref() {
             v.clone()
         } else {
@@ -68,7 +82,7 @@ impl PayloadHandler for ServiceQueryRequestHandler {
             &NamingUtils::default_group(request.group_name.unwrap_or_default()),
             &request.service_name.unwrap_or_default(),
         );
-        let cmd = NamingCmd::QueryServiceInfo(key, cluster, true);
+        let cmd = NamingCmd::QueryServiceInfo(key, cluster, true, selector);
         match self.app_data.naming_addr.send(cmd).await {
             Ok(res) => {
                 let result: NamingResult = res.unwrap();
@@ -16,6 +16,7 @@ use crate::{
         core::{NamingActor, NamingCmd, NamingResult},
         model::{Instance, ServiceInfo, ServiceKey},
         naming_subscriber::NamingListenerItem,
+        selector::InstanceSelector,
         NamingUtils,
     },
     now_millis_i64,
@@ -42,11 +43,13 @@ impl SubscribeServiceRequestHandler {
         &self,
         subscribe: bool,
         service_key: ServiceKey,
+        selector: Option<Arc<InstanceSelector>>,
         connection_id: Arc<String>,
     ) -> NamingCmd {
         let item = NamingListenerItem {
             service_key,
             clusters: None,
+            selector,
         };
         if subscribe {
             NamingCmd::Subscribe(vec![item], connection_id)
@@ -69,6 +72,19 @@ impl PayloadHandler for SubscribeServiceRequestHandler {
             request_id: request.request_id,
             ..Default::default()
         };
+        let selector =
+            match InstanceSelector::parse_param(request.selector.as_deref().unwrap_or_default()) {
+                Ok(v) => v.map(Arc::new),
+                Err(err) => {
+                    response.result_code = ERROR_CODE;
+                    response.error_code = 500u16;
+                    response.message = Some(err.to_string());
+                    return Ok(PayloadUtils::build_payload(
+                        "ErrorResponse",
+                        serde_json::to_string(&response)?,
+                    ));
+                }
+            };
         let cluster = if let Some(v) = request.clusters.as_ref() {
             v.clone()
         } else {
@@ -89,10 +105,28 @@ impl PayloadHandler for SubscribeServiceRequestHandler {
         let subscribe_cmd = self.build_subscribe_cmd(
             request.subscribe,
             key.clone(),
+            selector.clone(),
             request_meta.connection_id.clone(),
         );
-        self.app_data.naming_addr.do_send(subscribe_cmd);
-        let cmd = NamingCmd::QueryServiceInfo(key, cluster, true);
+        if request.subscribe {
+            //订阅的选择器与已有订阅冲突时直接返回错误
+            let res = match self.app_data.naming_addr.send(subscribe_cmd).await {
+                Ok(res) => res,
+                Err(err) => Err(err.into()),
+            };
+            if let Err(err) = res {
+                response.result_code = ERROR_CODE;
+                response.error_code = 500u16;
+                response.message = Some(err.to_string());
+                return Ok(PayloadUtils::build_payload(
+                    "ErrorResponse",
+                    serde_json::to_string(&response)?,
+                ));
+            }
+        } else {
+            self.app_data.naming_addr.do_send(subscribe_cmd);
+        }
+        let cmd = NamingCmd::QueryServiceInfo(key, cluster, true, selector);
         match self.app_data.naming_addr.send(cmd).await {
             Ok(res) => {
                 let result: NamingResult = res.unwrap();
@@ -1,10 +1,11 @@
 use std::sync::Arc;
 
 use crate::common::appdata::AppShareData;
+use crate::config::model::ConfigError;
 use crate::grpc::nacos_proto::Payload;
 use crate::grpc::{PayloadHandler, PayloadUtils, RequestMeta};
 use crate::raft::cluster::handle_route;
-use crate::raft::cluster::model::RouterRequest;
+use crate::raft::cluster::model::{RouterRequest, RouterResponse};
 use async_trait::async_trait;
 
 pub struct RaftRouteRequestHandler {
@@ -26,7 +27,14 @@ impl PayloadHandler for RaftRouteRequestHandler {
     ) -> anyhow::Result<Payload> {
         let body_vec = request_payload.body.unwrap_or_default().value;
         let request: RouterRequest = serde_json::from_slice(&body_vec)?;
-        let res = handle_route(&self.app_data, request).await?;
+        let res = match handle_route(&self.app_data, request).await {
+            Ok(v) => v,
+            //配置业务错误带类型返回，由请求节点还原
+            Err(err) => match err.downcast::<ConfigError>() {
+                Ok(error) => RouterResponse::ConfigError { error },
+                Err(err) => return Err(err),
+            },
+        };
         let value = serde_json::to_string(&res)?;
         let payload = PayloadUtils::build_payload("RaftRouteResponse", value);
         Ok(payload)
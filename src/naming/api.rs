@@ -4,9 +4,11 @@ use crate::common::web_utils::get_req_body;
 
 use super::super::utils::{get_bool_from_string, select_option_by_clone};
 use super::api_model::{InstanceVO, QueryListResult, ServiceInfoParam};
+use super::balancer::PickInstanceParam;
 use super::core::{NamingActor, NamingCmd, NamingResult};
 use super::model::{Instance, InstanceUpdateTag, ServiceKey};
 use super::ops::ops_api::query_opt_service_list;
+use super::selector::InstanceSelector;
 use super::{
     NamingUtils, CLIENT_BEAT_INTERVAL_KEY, LIGHT_BEAT_ENABLED_KEY, RESPONSE_CODE_KEY,
     RESPONSE_CODE_OK,
@@ -117,6 +119,7 @@ pub struct InstanceWebQueryListParams {
     #[serde(rename = "clientIP")]
     pub client_ip: Option<String>,
     pub udp_port: Option<u16>,
+    pub selector: Option<String>,
 }
 
 impl InstanceWebQueryListParams {
@@ -318,12 +321,84 @@ pub async fn get_instance(
     }
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstancePickParams {
+    pub namespace_id: Option<String>,
+    pub service_name: Option<String>,
+    pub group_name: Option<String>,
+    pub clusters: Option<String>,
+    pub selector: Option<String>,
+    pub strategy: Option<String>,
+    pub hash_key: Option<String>,
+    pub prefer_cluster: Option<String>,
+    pub prefer_zone: Option<String>,
+}
+
+impl InstancePickParams {
+    fn to_pick_param(&self) -> anyhow::Result<(ServiceKey, PickInstanceParam)> {
+        let grouped_name = self.service_name.clone().unwrap_or_default();
+        let (group_name, service_name) =
+            match NamingUtils::split_group_and_serivce_name(&grouped_name) {
+                Some(v) => v,
+                None => return Err(anyhow::anyhow!("serivceName is unvaild!")),
+            };
+        let group_name = match self.group_name.as_ref() {
+            Some(v) if !v.is_empty() => v.to_owned(),
+            _ => group_name,
+        };
+        let namespace_id =
+            NamingUtils::default_namespace(self.namespace_id.clone().unwrap_or_default());
+        let key = ServiceKey::new(&namespace_id, &group_name, &service_name);
+        let param = PickInstanceParam::build(
+            self.clusters.clone().unwrap_or_default(),
+            self.selector.as_deref().unwrap_or_default(),
+            self.strategy.as_deref().unwrap_or_default(),
+            self.hash_key.clone(),
+            self.prefer_cluster.clone(),
+            self.prefer_zone.clone(),
+        )?;
+        Ok((key, param))
+    }
+}
+
+pub async fn pick_instance(
+    param: web::Query<InstancePickParams>,
+    naming_addr: web::Data<Addr<NamingActor>>,
+) -> impl Responder {
+    let (key, pick_param) = match param.to_pick_param() {
+        Ok(v) => v,
+        Err(err) => return HttpResponse::InternalServerError().body(err.to_string()),
+    };
+    match naming_addr
+        .send(NamingCmd::PickInstance(key, pick_param))
+        .await
+    {
+        Ok(res) => match res {
+            Ok(NamingResult::Instance(v)) => {
+                let vo = InstanceVO::from_instance(&v);
+                HttpResponse::Ok()
+                    .insert_header(header::ContentType(mime::APPLICATION_JSON))
+                    .body(serde_json::to_string(&vo).unwrap())
+            }
+            Ok(_) => HttpResponse::NotFound().body("no available instance"),
+            Err(err) => HttpResponse::InternalServerError().body(err.to_string()),
+        },
+        Err(err) => HttpResponse::InternalServerError().body(err.to_string()),
+    }
+}
+
 pub async fn get_instance_list(
     param: web::Query<InstanceWebQueryListParams>,
     naming_addr: web::Data<Addr<NamingActor>>,
 ) -> impl Responder {
     let only_healthy = param.healthy_only.unwrap_or(true);
     let addr = param.get_addr();
+    let selector =
+        match InstanceSelector::parse_param(param.selector.as_deref().unwrap_or_default()) {
+            Ok(v) => v.map(Arc::new),
+            Err(err) => return HttpResponse::InternalServerError().body(err.to_string()),
+        };
     match param.to_clusters_key() {
         Ok((key, clusters)) => {
             match naming_addr
@@ -332,6 +407,7 @@ pub async fn get_instance_list(
                     clusters,
                     only_healthy,
                     addr,
+                    selector,
                 ))
                 .await
             {
@@ -379,6 +455,7 @@ pub async fn add_instance(
         enabled: false,
         ephemeral: false,
         from_update: false,
+        healthy_only: false,
     };
     let instance = param.convert_to_instance();
     match instance {
@@ -433,6 +510,7 @@ pub async fn update_instance(
             None => false,
         },
         from_update: true,
+        healthy_only: false,
     };
     let instance = param.convert_to_instance();
     match instance {
@@ -520,16 +598,24 @@ pub async fn beat_instance(
                     ephemeral: false,
                     metadata: false,
                     from_update: false,
+                    healthy_only: false,
                 };
+                let query_cmd = NamingCmd::Query(instance.clone());
+                let beat_interval = instance.get_heart_beat_interval();
                 match appdata
                     .naming_route
                     .update_instance(instance, Some(tag))
                     .await
                 {
                     Ok(_) => {
+                        //以服务端保存的实例元数据为准
+                        let beat_interval = match appdata.naming_addr.send(query_cmd).await {
+                            Ok(Ok(NamingResult::Instance(v))) => v.get_heart_beat_interval(),
+                            _ => beat_interval,
+                        };
                         let mut result = HashMap::new();
                         result.insert(RESPONSE_CODE_KEY, serde_json::json!(RESPONSE_CODE_OK));
-                        result.insert(CLIENT_BEAT_INTERVAL_KEY, serde_json::json!(5000));
+                        result.insert(CLIENT_BEAT_INTERVAL_KEY, serde_json::json!(beat_interval));
                         //result.insert(LIGHT_BEAT_ENABLED_KEY, serde_json::json!(false));
                         let v = serde_json::to_string(&result).unwrap();
                         HttpResponse::Ok()
@@ -671,6 +757,7 @@ pub fn app_config(config: &mut web::ServiceConfig) {
             )
             .service(web::resource("/instance/beat").route(web::put().to(beat_instance)))
             .service(web::resource("/instance/list").route(web::get().to(get_instance_list)))
+            .service(web::resource("/instance/pick").route(web::get().to(pick_instance)))
             .service(
                 web::resource("/service")
                     .route(web::post().to(update_service))
@@ -1,6 +1,7 @@
 use crate::now_millis_i64;
 
 use super::model::{Instance, ServiceDetailDto, ServiceKey};
+use super::selector::InstanceSelector;
 use super::NamingUtils;
 use chrono::Local;
 use serde::{Deserialize, Serialize};
@@ -168,6 +169,16 @@ impl ServiceInfoParam {
             } else {
                 None
             };
+            let selector = if let Some(selector_str) = self.selector {
+                let selector = InstanceSelector::parse_param(&selector_str)?;
+                Some(Arc::new(
+                    selector
+                        .map(|e| e.expression.as_ref().to_owned())
+                        .unwrap_or_default(),
+                ))
+            } else {
+                None
+            };
 
             Ok(ServiceDetailDto {
                 namespace_id: Arc::new(NamingUtils::default_namespace(
@@ -179,6 +190,7 @@ impl ServiceInfoParam {
                 )),
                 metadata,
                 protect_threshold: self.protect_threshold,
+                selector,
             })
         } else {
             Err(anyhow::anyhow!("service_name is empty"))
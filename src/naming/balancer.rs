@@ -0,0 +1,309 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
+
+use crate::common::hash_utils::get_hash_value;
+
+use super::{model::Instance, selector::InstanceSelector};
+
+///
+/// 实例所在可用区的metadata key
+pub const ZONE_METADATA_KEY: &str = "zone";
+///
+/// 一致性hash环上每个实例的虚拟节点数
+pub const VIRTUAL_NODE_COUNT: usize = 100;
+///
+/// 每个服务最多缓存的一致性hash环数量
+pub const HASH_RING_CACHE_SIZE: usize = 16;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LoadBalanceStrategy {
+    #[default]
+    WeightedRandom,
+    RoundRobin,
+    ConsistentHash,
+}
+
+impl LoadBalanceStrategy {
+    pub fn from_name(name: &str) -> anyhow::Result<Self> {
+        match name.to_lowercase().as_str() {
+            "" | "random" | "weighted_random" => Ok(Self::WeightedRandom),
+            "rr" | "round_robin" => Ok(Self::RoundRobin),
+            "hash" | "consistent_hash" => Ok(Self::ConsistentHash),
+            _ => Err(anyhow::anyhow!("unknown load balance strategy: {}", name)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PickInstanceParam {
+    pub clusters: String,
+    pub selector: Option<Arc<InstanceSelector>>,
+    pub strategy: LoadBalanceStrategy,
+    pub hash_key: Option<String>,
+    pub prefer_cluster: Option<String>,
+    pub prefer_zone: Option<String>,
+}
+
+impl PickInstanceParam {
+    ///
+    /// 由接口参数构建，空字符串视为未设置
+    pub fn build(
+        clusters: String,
+        selector: &str,
+        strategy: &str,
+        hash_key: Option<String>,
+        prefer_cluster: Option<String>,
+        prefer_zone: Option<String>,
+    ) -> anyhow::Result<Self> {
+        let param = Self {
+            clusters,
+            selector: InstanceSelector::parse_param(selector)?.map(Arc::new),
+            strategy: LoadBalanceStrategy::from_name(strategy)?,
+            hash_key: hash_key.filter(|e| !e.is_empty()),
+            prefer_cluster: prefer_cluster.filter(|e| !e.is_empty()),
+            prefer_zone: prefer_zone.filter(|e| !e.is_empty()),
+        };
+        param.check()?;
+        Ok(param)
+    }
+
+    pub fn check(&self) -> anyhow::Result<()> {
+        if self.strategy == LoadBalanceStrategy::ConsistentHash
+            && self.hash_key.as_ref().map(|e| e.is_empty()).unwrap_or(true)
+        {
+            return Err(anyhow::anyhow!("hash_key is empty"));
+        }
+        Ok(())
+    }
+}
+
+///
+/// 一致性hash环，ring的值为实例在ids中的下标
+#[derive(Debug, Default)]
+pub struct HashRing {
+    ids: Vec<(Arc<String>, u32)>,
+    ring: BTreeMap<u64, usize>,
+}
+
+impl HashRing {
+    fn new(ids: Vec<(Arc<String>, u32)>) -> Self {
+        let mut ring = BTreeMap::new();
+        for (index, (ip, port)) in ids.iter().enumerate() {
+            for i in 0..VIRTUAL_NODE_COUNT {
+                ring.insert(get_hash_value(&(ip.as_str(), *port, i)), index);
+            }
+        }
+        Self { ids, ring }
+    }
+
+    fn get(&self, hash_key: &str) -> Option<usize> {
+        let hash = get_hash_value(&hash_key);
+        self.ring
+            .range(hash..)
+            .next()
+            .or_else(|| self.ring.iter().next())
+            .map(|(_, index)| *index)
+    }
+}
+
+///
+/// 服务的一致性hash环缓存，按候选实例列表区分;
+/// 候选实例不变时复用，服务实例增减时清空
+#[derive(Debug, Clone, Default)]
+pub struct HashRingCache {
+    rings: HashMap<u64, Arc<HashRing>>,
+}
+
+impl HashRingCache {
+    pub fn clear(&mut self) {
+        self.rings.clear();
+    }
+
+    fn get_or_build(&mut self, instances: &[Arc<Instance>]) -> Arc<HashRing> {
+        let ids: Vec<(Arc<String>, u32)> =
+            instances.iter().map(|e| (e.ip.clone(), e.port)).collect();
+        let fingerprint = get_hash_value(&ids);
+        if let Some(ring) = self.rings.get(&fingerprint) {
+            if ring.ids == ids {
+                return ring.clone();
+            }
+        }
+        if self.rings.len() >= HASH_RING_CACHE_SIZE {
+            self.rings.clear();
+        }
+        let ring = Arc::new(HashRing::new(ids));
+        self.rings.insert(fingerprint, ring.clone());
+        ring
+    }
+}
+
+pub struct InstanceBalancer;
+
+impl InstanceBalancer {
+    ///
+    /// 从可用实例中选出一个实例;
+    /// 优先同集群，其次同可用区，权重不大于0的实例不参与选择
+    pub fn pick(
+        instances: Vec<Arc<Instance>>,
+        param: &PickInstanceParam,
+        sequence: u64,
+        ring_cache: &mut HashRingCache,
+    ) -> Option<Arc<Instance>> {
+        let mut instances: Vec<Arc<Instance>> =
+            instances.into_iter().filter(|e| e.weight > 0f32).collect();
+        instances = Self::prefer(instances, |e| {
+            param.prefer_cluster.as_ref().map(|v| &e.cluster_name == v)
+        });
+        instances = Self::prefer(instances, |e| {
+            param
+                .prefer_zone
+                .as_ref()
+                .map(|v| e.metadata.get(ZONE_METADATA_KEY) == Some(v))
+        });
+        if instances.is_empty() {
+            return None;
+        }
+        //按实例地址排序，保证轮询及hash结果稳定
+        instances.sort_by(|a, b| a.ip.cmp(&b.ip).then(a.port.cmp(&b.port)));
+        match param.strategy {
+            LoadBalanceStrategy::WeightedRandom => {
+                Self::weighted_pick(instances, rand::random::<u64>())
+            }
+            LoadBalanceStrategy::RoundRobin => {
+                let index = (sequence % instances.len() as u64) as usize;
+                instances.into_iter().nth(index)
+            }
+            LoadBalanceStrategy::ConsistentHash => {
+                let ring = ring_cache.get_or_build(&instances);
+                ring.get(param.hash_key.as_deref().unwrap_or_default())
+                    .map(|index| instances[index].clone())
+            }
+        }
+    }
+
+    ///
+    /// 存在匹配的实例时只保留匹配的实例，否则保留全部
+    fn prefer<F>(instances: Vec<Arc<Instance>>, f: F) -> Vec<Arc<Instance>>
+    where
+        F: Fn(&Instance) -> Option<bool>,
+    {
+        let matched: Vec<Arc<Instance>> = instances
+            .iter()
+            .filter(|e| f(e).unwrap_or(false))
+            .cloned()
+            .collect();
+        if matched.is_empty() {
+            instances
+        } else {
+            matched
+        }
+    }
+
+    fn weighted_pick(instances: Vec<Arc<Instance>>, random: u64) -> Option<Arc<Instance>> {
+        let total: f64 = instances.iter().map(|e| e.weight as f64).sum();
+        let mut point = (random as f64 / u64::MAX as f64) * total;
+        for item in &instances {
+            point -= item.weight as f64;
+            if point < 0f64 {
+                return Some(item.clone());
+            }
+        }
+        instances.last().cloned()
+    }
+}
+
+#[test]
+fn instance_balancer() {
+    use std::collections::{HashMap, HashSet};
+
+    let build_instance = |port: u32, weight: f32, cluster: &str, zone: &str| {
+        let mut metadata = HashMap::new();
+        metadata.insert(ZONE_METADATA_KEY.to_owned(), zone.to_owned());
+        Arc::new(Instance {
+            ip: Arc::new("127.0.0.1".to_owned()),
+            port,
+            weight,
+            cluster_name: cluster.to_owned(),
+            metadata: Arc::new(metadata),
+            ..Default::default()
+        })
+    };
+    let instances = vec![
+        build_instance(8080, 1f32, "A", "cn-east"),
+        build_instance(8081, 1f32, "B", "cn-east"),
+        build_instance(8082, 1f32, "C", "cn-west"),
+        build_instance(8083, 0f32, "D", "cn-west"),
+    ];
+
+    let mut cache = HashRingCache::default();
+    let mut param = PickInstanceParam {
+        strategy: LoadBalanceStrategy::RoundRobin,
+        ..Default::default()
+    };
+    let ports: Vec<u32> = (0..3)
+        .map(|i| {
+            InstanceBalancer::pick(instances.clone(), &param, i, &mut cache)
+                .unwrap()
+                .port
+        })
+        .collect();
+    assert_eq!(ports, vec![8080, 8081, 8082]);
+
+    param.prefer_zone = Some("cn-west".to_owned());
+    for i in 0..3 {
+        let item = InstanceBalancer::pick(instances.clone(), &param, i, &mut cache).unwrap();
+        assert_eq!(item.port, 8082);
+    }
+    param.prefer_cluster = Some("B".to_owned());
+    param.prefer_zone = Some("cn-east".to_owned());
+    let item = InstanceBalancer::pick(instances.clone(), &param, 0, &mut cache).unwrap();
+    assert_eq!(item.port, 8081);
+    param.prefer_cluster = Some("X".to_owned());
+    param.prefer_zone = Some("cn-north".to_owned());
+    let item = InstanceBalancer::pick(instances.clone(), &param, 3, &mut cache).unwrap();
+    assert_eq!(item.port, 8080);
+
+    let param = PickInstanceParam {
+        strategy: LoadBalanceStrategy::ConsistentHash,
+        ..Default::default()
+    };
+    assert!(param.check().is_err());
+    let mut ports = HashSet::new();
+    for i in 0..50 {
+        let param = PickInstanceParam {
+            hash_key: Some(format!("user-{}", i)),
+            ..param.clone()
+        };
+        let a = InstanceBalancer::pick(instances.clone(), &param, 0, &mut cache).unwrap();
+        let b = InstanceBalancer::pick(instances.clone(), &param, 1, &mut cache).unwrap();
+        assert_eq!(a.port, b.port);
+        ports.insert(a.port);
+    }
+    assert!(ports.len() > 1 && !ports.contains(&8083));
+    //相同候选实例复用同一个hash环
+    assert_eq!(cache.rings.len(), 1);
+    let ring = cache.get_or_build(&instances[..3]);
+    assert!(Arc::ptr_eq(&ring, &cache.get_or_build(&instances[..3])));
+    assert!(!Arc::ptr_eq(&ring, &cache.get_or_build(&instances[..2])));
+
+    assert_eq!(
+        InstanceBalancer::weighted_pick(instances[..3].to_vec(), 0)
+            .unwrap()
+            .port,
+        8080
+    );
+    assert_eq!(
+        InstanceBalancer::weighted_pick(instances[..3].to_vec(), u64::MAX)
+            .unwrap()
+            .port,
+        8082
+    );
+    let param = PickInstanceParam::default();
+    for _ in 0..20 {
+        let item = InstanceBalancer::pick(instances.clone(), &param, 0, &mut cache).unwrap();
+        assert_ne!(item.port, 8083);
+    }
+    assert!(InstanceBalancer::pick(vec![], &param, 0, &mut cache).is_none());
+}
@@ -5,8 +5,16 @@ use crate::{
     naming::{
         core::{NamingActor, NamingCmd, NamingResult},
         model::{Instance, InstanceUpdateTag},
+        naming_persistent::NamingPersistentReq,
+    },
+    raft::{
+        cluster::{
+            model::{RouteAddr, RouterRequest, RouterResponse},
+            route::RaftAddrRouter,
+        },
+        network::factory::RaftClusterRequestSender,
+        store::ClientRequest,
     },
-    raft::network::factory::RaftClusterRequestSender,
 };
 
 use super::{
@@ -20,6 +28,7 @@ use actix::prelude::*;
 pub struct NamingRoute {
     naming_addr: Addr<NamingActor>,
     node_manage: Arc<NodeManage>,
+    raft_addr_route: Arc<RaftAddrRouter>,
     cluster_sender: Arc<RaftClusterRequestSender>,
 }
 
@@ -27,11 +36,13 @@ impl NamingRoute {
     pub fn new(
         naming_addr: Addr<NamingActor>,
         node_manage: Arc<NodeManage>,
+        raft_addr_route: Arc<RaftAddrRouter>,
         cluster_sender: Arc<RaftClusterRequestSender>,
     ) -> Self {
         Self {
             naming_addr,
             node_manage,
+            raft_addr_route,
             cluster_sender,
         }
     }
@@ -41,6 +52,11 @@ impl NamingRoute {
         instance: Instance,
         tag: Option<InstanceUpdateTag>,
     ) -> anyhow::Result<()> {
+        //已存在的持久化实例不能被临时实例覆盖，统一通过raft更新
+        if !instance.ephemeral || self.is_persistent_instance(&instance).await? {
+            let req = NamingPersistentReq::UpdateInstance { instance, tag };
+            return self.persistent_request(req).await;
+        }
         let key = instance.get_service_key();
         match self.node_manage.route_addr(&key).await {
             NamingRouteAddr::Local(_) => {
@@ -108,6 +124,10 @@ impl NamingRoute {
     }
 
     pub async fn delete_instance(&self, instance: Instance) -> anyhow::Result<()> {
+        if !instance.ephemeral || self.is_persistent_instance(&instance).await? {
+            let req = NamingPersistentReq::RemoveInstance { instance };
+            return self.persistent_request(req).await;
+        }
         let key = instance.get_service_key();
         match self.node_manage.route_addr(&key).await {
             NamingRouteAddr::Local(_) => {
@@ -121,4 +141,36 @@ impl NamingRoute {
         };
         Ok(())
     }
+
+    async fn is_persistent_instance(&self, instance: &Instance) -> anyhow::Result<bool> {
+        let cmd = NamingCmd::Query(instance.clone());
+        if let NamingResult::Instance(v) = self.naming_addr.send(cmd).await?? {
+            return Ok(!v.ephemeral);
+        }
+        Ok(false)
+    }
+
+    ///
+    /// 持久化实例变更通过raft主节点写入，所有节点应用后生效
+    async fn persistent_request(&self, req: NamingPersistentReq) -> anyhow::Result<()> {
+        match self.raft_addr_route.get_route_addr().await? {
+            RouteAddr::Local => {
+                self.raft_addr_route
+                    .client_write(ClientRequest::NamingPersistentReq(req))
+                    .await?;
+            }
+            RouteAddr::Remote(_, addr) => {
+                let req = RouterRequest::NamingPersistentReq { req };
+                let request = serde_json::to_string(&req).unwrap_or_default();
+                let payload = PayloadUtils::build_payload("RaftRouteRequest", request);
+                let resp_payload = self.cluster_sender.send_request(addr, payload).await?;
+                let body_vec = resp_payload.body.unwrap_or_default().value;
+                let _: RouterResponse = serde_json::from_slice(&body_vec)?;
+            }
+            RouteAddr::Unknown => {
+                return Err(anyhow::anyhow!("unknown the raft leader addr!"));
+            }
+        }
+        Ok(())
+    }
 }
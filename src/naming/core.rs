@@ -7,6 +7,7 @@
 )]
 
 use super::api_model::QueryListResult;
+use super::balancer::{InstanceBalancer, PickInstanceParam};
 use super::cluster::instance_delay_notify::{
     ClusterInstanceDelayNotifyActor, InstanceDelayNotifyRequest,
 };
@@ -15,6 +16,7 @@ use super::cluster::model::{
 };
 use super::cluster::node_manage::{InnerNodeManage, NodeManageRequest};
 use super::filter::InstanceFilterUtils;
+use super::health_check::HealthCheckService;
 use super::listener::{InnerNamingListener, ListenerItem, NamingListenerCmd};
 use super::model::Instance;
 use super::model::InstanceKey;
@@ -27,20 +29,25 @@ use super::model::ServiceKey;
 use super::model::UpdateInstanceType;
 use super::naming_delay_nofity::DelayNotifyActor;
 use super::naming_delay_nofity::DelayNotifyCmd;
+use super::naming_persistent::NamingPersistentReq;
+use super::naming_persistent::NamingPersistentUtils;
 use super::naming_subscriber::NamingListenerItem;
 use super::naming_subscriber::Subscriber;
+use super::selector::InstanceSelector;
 use super::service::Service;
 use super::service::ServiceInfoDto;
 use super::service::ServiceMetadata;
 use super::service_index::NamespaceIndex;
 use super::service_index::ServiceQueryParam;
 use super::NamingUtils;
+use crate::common::constant::NAMING_PERSISTENT_INSTANCE_TREE_NAME;
 use crate::common::delay_notify;
 use crate::common::hash_utils::get_hash_value;
 use crate::common::NamingSysConfig;
 use crate::grpc::bistream_manage::BiStreamManage;
 use crate::now_millis;
 use crate::now_millis_i64;
+use crate::raft::db::table::{TableManager, TableManagerReq};
 use crate::utils::gz_encode;
 use bean_factory::{bean, Inject, InjectComponent};
 use chrono::Local;
@@ -73,6 +80,7 @@ pub struct NamingActor {
     pub(crate) client_instance_set: HashMap<Arc<String>, HashSet<InstanceKey>>,
     cluster_node_manage: Option<Addr<InnerNodeManage>>,
     cluster_delay_notify: Option<Addr<ClusterInstanceDelayNotifyActor>>,
+    table_manager: Option<Addr<TableManager>>,
     //dal_addr: Addr<ServiceDalActor>,
 }
 
@@ -101,6 +109,7 @@ impl Inject for NamingActor {
         }
         self.cluster_node_manage = factory_data.get_actor();
         self.cluster_delay_notify = factory_data.get_actor();
+        self.table_manager = factory_data.get_actor();
         log::info!("NamingActor inject complete");
     }
 }
@@ -128,6 +137,7 @@ impl NamingActor {
             client_instance_set: Default::default(),
             cluster_node_manage: None,
             cluster_delay_notify: None,
+            table_manager: None,
             //dal_addr,
         }
     }
@@ -187,6 +197,7 @@ impl NamingActor {
             service_info.group_name,
             service_info.service_name,
         );
+        let mut selector_changed = false;
         match self.get_service(&key) {
             Some(service) => {
                 if let Some(protect_threshold) = service_info.protect_threshold {
@@ -195,6 +206,9 @@ impl NamingActor {
                 if let Some(metadata) = service_info.metadata {
                     service.metadata = metadata;
                 }
+                if let Some(selector) = service_info.selector {
+                    selector_changed = Self::update_service_selector(service, &selector);
+                }
             }
             None => {
                 let mut service = Service::default();
@@ -213,6 +227,9 @@ impl NamingActor {
                 if let Some(metadata) = service_info.metadata {
                     service.metadata = metadata;
                 }
+                if let Some(selector) = service_info.selector {
+                    Self::update_service_selector(&mut service, &selector);
+                }
                 service.recalculate_checksum();
                 self.namespace_index.insert_service(key.clone());
                 //self.dal_addr.do_send(ServiceDalMsg::AddService(service.get_service_do()));
@@ -223,6 +240,30 @@ impl NamingActor {
                 );
             }
         }
+        if selector_changed {
+            self.subscriber.notify(key);
+        }
+    }
+
+    ///
+    /// 更新服务级实例选择器，返回选择器是否有变化
+    fn update_service_selector(service: &mut Service, selector: &str) -> bool {
+        match InstanceSelector::parse_param(selector) {
+            Ok(selector) => {
+                let selector = selector.map(Arc::new);
+                let changed = service.selector != selector;
+                service.selector = selector;
+                changed
+            }
+            Err(err) => {
+                log::warn!(
+                    "ignore invalid service selector,{},{}",
+                    &service.group_service,
+                    err
+                );
+                false
+            }
+        }
     }
 
     fn remove_empty_service(&mut self, service_map_key: ServiceKey) -> anyhow::Result<()> {
@@ -319,7 +360,8 @@ impl NamingActor {
             self.empty_service_set
                 .add(now + self.sys_config.service_time_out_millis, key.clone());
         }
-        let remove_instance = old_instance.filter(|e| !e.is_from_cluster());
+        //持久化实例由raft同步，不走集群实例同步
+        let remove_instance = old_instance.filter(|e| !e.is_from_cluster() && e.ephemeral);
         self.do_notify(&tag, key.clone(), remove_instance);
         tag
     }
@@ -354,12 +396,79 @@ impl NamingActor {
         }
         let instance = service
             .get_instance(&instance_key)
-            .filter(|e| !e.is_from_cluster());
+            .filter(|e| !e.is_from_cluster() && e.ephemeral);
         //change notify
         self.do_notify(&tag, key.clone(), instance);
         tag
     }
 
+    ///
+    /// 应用raft同步的持久化实例变更，并写入持久化实例表
+    fn apply_persistent_req(&mut self, req: NamingPersistentReq) {
+        match req {
+            NamingPersistentReq::UpdateInstance { instance, tag } => {
+                let instance = NamingPersistentUtils::normalize(instance);
+                let key = instance.get_service_key();
+                let short_key = instance.get_short_key();
+                self.update_instance(&key, instance, tag);
+                if let Some(instance) = self.get_instance(&key, &short_key) {
+                    if !instance.ephemeral {
+                        self.save_persistent_instance(&instance);
+                    }
+                }
+            }
+            NamingPersistentReq::RemoveInstance { instance } => {
+                let key = instance.get_service_key();
+                self.remove_instance(&key, &instance.get_short_key(), None);
+                if let Some(table_manager) = &self.table_manager {
+                    table_manager.do_send(TableManagerReq::Remove {
+                        table_name: NAMING_PERSISTENT_INSTANCE_TREE_NAME.clone(),
+                        key: NamingPersistentUtils::build_key(&instance.get_instance_key()),
+                    });
+                }
+            }
+        }
+    }
+
+    ///
+    /// 持久化实例不上报心跳，需要服务端主动探测
+    fn get_health_check_services(&self) -> Vec<HealthCheckService> {
+        let mut list = vec![];
+        for (key, service) in &self.service_map {
+            let instances: Vec<Arc<Instance>> = service
+                .instances
+                .values()
+                .filter(|e| !e.ephemeral)
+                .cloned()
+                .collect();
+            if instances.is_empty() {
+                continue;
+            }
+            list.push(HealthCheckService {
+                key: key.clone(),
+                metadata: service.metadata.clone(),
+                instances,
+            });
+        }
+        list
+    }
+
+    fn save_persistent_instance(&self, instance: &Instance) {
+        if let Some(table_manager) = &self.table_manager {
+            match NamingPersistentUtils::to_bytes(instance) {
+                Ok(value) => {
+                    table_manager.do_send(TableManagerReq::Set {
+                        table_name: NAMING_PERSISTENT_INSTANCE_TREE_NAME.clone(),
+                        key: NamingPersistentUtils::build_key(&instance.get_instance_key()),
+                        value,
+                        last_seq_id: None,
+                    });
+                }
+                Err(err) => log::warn!("persistent instance encode error,{}", err),
+            }
+        }
+    }
+
     pub(crate) fn remove_client_instance(&mut self, client_id: &Arc<String>) {
         if let Some(keys) = self.client_instance_set.remove(client_id) {
             for instance_key in keys {
@@ -387,11 +496,16 @@ impl NamingActor {
         key: &ServiceKey,
         cluster_str: &str,
         only_healthy: bool,
+        selector: Option<&InstanceSelector>,
     ) -> Vec<Arc<Instance>> {
         let cluster_names = NamingUtils::split_filters(cluster_str);
         if let Some(service) = self.service_map.get(key) {
+            let mut instances = service.get_instance_list(cluster_names, false, true);
+            if let Some(selector) = selector {
+                instances = selector.filter(instances);
+            }
             return InstanceFilterUtils::default_instance_filter(
-                service.get_instance_list(cluster_names, false, true),
+                instances,
                 Some(service.get_metadata()),
                 only_healthy,
             );
@@ -399,18 +513,39 @@ impl NamingActor {
         vec![]
     }
 
+    ///
+    /// 按负载均衡策略从可用实例中选出一个实例
+    pub(crate) fn pick_instance(
+        &mut self,
+        key: &ServiceKey,
+        param: &PickInstanceParam,
+    ) -> Option<Arc<Instance>> {
+        let instances =
+            self.get_instance_list(key, &param.clusters, true, param.selector.as_deref());
+        let service = self.service_map.get_mut(key)?;
+        service.pick_sequence = service.pick_sequence.wrapping_add(1);
+        InstanceBalancer::pick(
+            instances,
+            param,
+            service.pick_sequence,
+            &mut service.hash_ring_cache,
+        )
+    }
+
     pub fn get_instances_and_metadata(
         &self,
         key: &ServiceKey,
         cluster_str: &str,
         only_healthy: bool,
+        selector: Option<&InstanceSelector>,
     ) -> (Vec<Arc<Instance>>, Option<ServiceMetadata>) {
         let cluster_names = NamingUtils::split_filters(cluster_str);
         if let Some(service) = self.service_map.get(key) {
-            return (
-                service.get_instance_list(cluster_names, only_healthy, true),
-                Some(service.get_metadata()),
-            );
+            let mut instances = service.get_instance_list(cluster_names, only_healthy, true);
+            if let Some(selector) = selector {
+                instances = selector.filter(instances);
+            }
+            return (instances, Some(service.get_metadata()));
         }
         (vec![], None)
     }
@@ -443,8 +578,9 @@ impl NamingActor {
         key: &ServiceKey,
         cluster_str: String,
         only_healthy: bool,
+        selector: Option<&InstanceSelector>,
     ) -> ServiceInfo {
-        let (hosts, metadata) = self.get_instances_and_metadata(key, &cluster_str, false);
+        let (hosts, metadata) = self.get_instances_and_metadata(key, &cluster_str, false, selector);
         let service_info = ServiceInfo {
             name: Some(key.service_name.clone()),
             group_name: Some(key.group_name.clone()),
@@ -463,21 +599,20 @@ impl NamingActor {
         key: &ServiceKey,
         cluster_str: String,
         only_healthy: bool,
+        selector: Option<&InstanceSelector>,
     ) -> String {
-        let list = self.get_instance_list(key, &cluster_str, only_healthy);
+        let list = self.get_instance_list(key, &cluster_str, only_healthy, selector);
         QueryListResult::get_instance_list_string(cluster_str, key, list)
     }
 
     pub fn time_check(&mut self) {
         let current_time = Local::now().timestamp_millis();
-        let healthy_time = current_time - 15000;
-        let offline_time = current_time - 30000;
         let mut size = 0;
         let now = now_millis();
         let mut change_list = vec![];
         for item in self.service_map.values_mut() {
             let service_key = item.get_service_key();
-            let (rlist, ulist) = item.time_check(healthy_time, offline_time);
+            let (rlist, ulist) = item.time_check(current_time);
             size += rlist.len() + ulist.len();
             if !rlist.is_empty() {
                 for short_key in &rlist {
@@ -721,10 +856,23 @@ pub enum NamingCmd {
     Delete(Instance),
     DeleteBatch(Vec<Instance>),
     Query(Instance),
-    QueryList(ServiceKey, String, bool, Option<SocketAddr>),
+    QueryList(
+        ServiceKey,
+        String,
+        bool,
+        Option<SocketAddr>,
+        Option<Arc<InstanceSelector>>,
+    ),
     QueryAllInstanceList(ServiceKey),
-    QueryListString(ServiceKey, String, bool, Option<SocketAddr>),
-    QueryServiceInfo(ServiceKey, String, bool),
+    QueryListString(
+        ServiceKey,
+        String,
+        bool,
+        Option<SocketAddr>,
+        Option<Arc<InstanceSelector>>,
+    ),
+    QueryServiceInfo(ServiceKey, String, bool, Option<Arc<InstanceSelector>>),
+    PickInstance(ServiceKey, PickInstanceParam),
     QueryServicePage(ServiceKey, usize, usize),
     //查询服务实际信息列表
     QueryServiceInfoPage(ServiceQueryParam),
@@ -742,6 +890,8 @@ pub enum NamingCmd {
     QueryDalAddr,
     QuerySnapshot(Vec<ProcessRange>),
     ReceiveSnapshot(SnapshotForReceive),
+    PersistentUpdate(NamingPersistentReq),
+    QueryHealthCheckServices,
 }
 
 pub enum NamingResult {
@@ -755,6 +905,7 @@ pub enum NamingResult {
     ClientInstanceCount(Vec<(Arc<String>, usize)>),
     RewriteToCluster(u64, Instance),
     Snapshot(SnapshotForSend),
+    HealthCheckServices(Vec<HealthCheckService>),
 }
 
 impl Supervised for NamingActor {
@@ -808,28 +959,49 @@ impl Handler<NamingCmd> for NamingActor {
                 }
                 Ok(NamingResult::NULL)
             }
-            NamingCmd::QueryList(service_key, cluster_str, only_healthy, addr) => {
+            NamingCmd::QueryList(service_key, cluster_str, only_healthy, addr, selector) => {
                 let cluster_names = NamingUtils::split_filters(&cluster_str);
                 if let Some(addr) = addr {
                     self.update_listener(&service_key, &cluster_names, addr, only_healthy);
                 }
-                let list = self.get_instance_list(&service_key, &cluster_str, only_healthy);
+                let list = self.get_instance_list(
+                    &service_key,
+                    &cluster_str,
+                    only_healthy,
+                    selector.as_deref(),
+                );
                 Ok(NamingResult::InstanceList(list))
             }
-            NamingCmd::QueryListString(service_key, cluster_str, only_healthy, addr) => {
+            NamingCmd::QueryListString(service_key, cluster_str, only_healthy, addr, selector) => {
                 //println!("QUERY_LIST_STRING addr: {:?}",&addr);
                 let cluster_names = NamingUtils::split_filters(&cluster_str);
                 if let Some(addr) = addr {
                     self.update_listener(&service_key, &cluster_names, addr, only_healthy);
                 }
-                let data = self.get_instance_list_string(&service_key, cluster_str, only_healthy);
+                let data = self.get_instance_list_string(
+                    &service_key,
+                    cluster_str,
+                    only_healthy,
+                    selector.as_deref(),
+                );
                 Ok(NamingResult::InstanceListString(data))
             }
-            NamingCmd::QueryServiceInfo(service_key, cluster_str, only_healthy) => {
+            NamingCmd::QueryServiceInfo(service_key, cluster_str, only_healthy, selector) => {
                 let cluster_names = NamingUtils::split_filters(&cluster_str);
-                let service_info = self.get_service_info(&service_key, cluster_str, only_healthy);
+                let service_info = self.get_service_info(
+                    &service_key,
+                    cluster_str,
+                    only_healthy,
+                    selector.as_deref(),
+                );
                 Ok(NamingResult::ServiceInfo(service_info))
             }
+            NamingCmd::PickInstance(service_key, param) => {
+                match self.pick_instance(&service_key, &param) {
+                    Some(instance) => Ok(NamingResult::Instance(instance)),
+                    None => Ok(NamingResult::NULL),
+                }
+            }
             NamingCmd::QueryServicePage(service_key, page_size, page_index) => {
                 Ok(NamingResult::ServicePage(self.get_service_list(
                     page_size,
@@ -855,6 +1027,7 @@ impl Handler<NamingCmd> for NamingActor {
                 Ok(NamingResult::NULL)
             }
             NamingCmd::Subscribe(items, client_id) => {
+                self.subscriber.check_selector(&client_id, &items)?;
                 self.subscriber.add_subscribe(client_id, items.clone());
                 //debug
                 for item in items {
@@ -928,6 +1101,13 @@ impl Handler<NamingCmd> for NamingActor {
                 self.receive_snapshot(snapshot);
                 Ok(NamingResult::NULL)
             }
+            NamingCmd::PersistentUpdate(req) => {
+                self.apply_persistent_req(req);
+                Ok(NamingResult::NULL)
+            }
+            NamingCmd::QueryHealthCheckServices => Ok(NamingResult::HealthCheckServices(
+                self.get_health_check_services(),
+            )),
         }
     }
 }
@@ -951,10 +1131,10 @@ async fn query_healthy_instances() {
     }
 
     println!("-------------");
-    let items = naming.get_instance_list(&key, "", true);
+    let items = naming.get_instance_list(&key, "", true, None);
     assert!(!items.is_empty());
     println!("DEFUALT list:{}", serde_json::to_string(&items).unwrap());
-    let items = naming.get_instance_list(&key, "", true);
+    let items = naming.get_instance_list(&key, "", true, None);
     assert!(!items.is_empty());
     println!(
         "empty cluster list:{}",
@@ -963,7 +1143,7 @@ async fn query_healthy_instances() {
     tokio::time::sleep(Duration::from_millis(16000)).await;
     naming.time_check();
     println!("-------------");
-    let items = naming.get_instance_list(&key, "", false);
+    let items = naming.get_instance_list(&key, "", false, None);
     assert!(!items.is_empty());
     println!(
         "empty cluster list:{}",
@@ -972,7 +1152,7 @@ async fn query_healthy_instances() {
     tokio::time::sleep(Duration::from_millis(16000)).await;
     naming.time_check();
     println!("-------------");
-    let items = naming.get_instance_list(&key, "", false);
+    let items = naming.get_instance_list(&key, "", false, None);
     assert!(items.is_empty());
     println!(
         "empty cluster list:{}",
@@ -992,6 +1172,7 @@ fn test_add_service() {
         group_name: service_key.group_name.clone(),
         metadata: Default::default(),
         protect_threshold: Some(0.5),
+        selector: None,
     };
     assert!(naming.namespace_index.service_size == 0);
     naming.update_service(service_info);
@@ -1019,6 +1200,7 @@ fn test_remove_has_instance_service() {
         group_name: service_key.group_name.clone(),
         metadata: Default::default(),
         protect_threshold: Some(0.5),
+        selector: None,
     };
     assert!(naming.namespace_index.service_size == 1);
     naming.update_service(service_info);
@@ -1031,3 +1213,197 @@ fn test_remove_has_instance_service() {
     assert!(naming.remove_empty_service(service_key.clone()).is_ok());
     assert!(naming.namespace_index.service_size == 0);
 }
+
+#[test]
+fn test_persistent_instance_time_check() {
+    let mut naming = NamingActor::new();
+    let mut instance = Instance::new("127.0.0.1".to_owned(), 8080);
+    instance.namespace_id = Arc::new("public".to_owned());
+    instance.service_name = Arc::new("foo".to_owned());
+    instance.group_name = Arc::new("DEFUALT".to_owned());
+    instance.ephemeral = false;
+    let key = instance.get_service_key();
+    naming.apply_persistent_req(NamingPersistentReq::UpdateInstance {
+        instance: instance.clone(),
+        tag: None,
+    });
+    let mut ephemeral_instance = instance.clone();
+    ephemeral_instance.port = 8081;
+    ephemeral_instance.ephemeral = true;
+    naming.update_instance(&key, ephemeral_instance, None);
+
+    //心跳超时只移除临时实例
+    let check_time = now_millis_i64() + 60000;
+    let service = naming.service_map.get_mut(&key).unwrap();
+    let (remove_list, _) = service.time_check(check_time);
+    assert_eq!(remove_list.len(), 1);
+    assert_eq!(remove_list[0].port, 8081);
+    assert!(
+        !naming
+            .get_instance(&key, &instance.get_short_key())
+            .unwrap()
+            .ephemeral
+    );
+
+    naming.apply_persistent_req(NamingPersistentReq::RemoveInstance { instance });
+    assert!(naming.get_instance_list(&key, "", false, None).is_empty());
+}
+
+#[test]
+fn test_persistent_instance_healthy_only_update() {
+    let mut naming = NamingActor::new();
+    let mut instance = Instance::new("127.0.0.1".to_owned(), 8080);
+    instance.namespace_id = Arc::new("public".to_owned());
+    instance.service_name = Arc::new("foo".to_owned());
+    instance.group_name = Arc::new("DEFUALT".to_owned());
+    instance.ephemeral = false;
+    let key = instance.get_service_key();
+    let short_key = instance.get_short_key();
+    naming.apply_persistent_req(NamingPersistentReq::UpdateInstance {
+        instance: instance.clone(),
+        tag: None,
+    });
+    let mut updated = instance.clone();
+    updated.weight = 5f32;
+    naming.apply_persistent_req(NamingPersistentReq::UpdateInstance {
+        instance: updated,
+        tag: None,
+    });
+
+    //探测结果使用旧的实例信息，只更新健康状态
+    let mut probe = instance.clone();
+    probe.healthy = false;
+    naming.apply_persistent_req(NamingPersistentReq::UpdateInstance {
+        instance: probe.clone(),
+        tag: Some(InstanceUpdateTag::new_healthy_only()),
+    });
+    let v = naming.get_instance(&key, &short_key).unwrap();
+    assert!(!v.healthy);
+    assert_eq!(v.weight, 5f32);
+    assert_eq!(
+        naming.service_map.get(&key).unwrap().healthy_instance_size,
+        0
+    );
+
+    //实例删除后探测结果不再新增实例
+    naming.apply_persistent_req(NamingPersistentReq::RemoveInstance { instance });
+    naming.apply_persistent_req(NamingPersistentReq::UpdateInstance {
+        instance: probe,
+        tag: Some(InstanceUpdateTag::new_healthy_only()),
+    });
+    assert!(naming.get_instance(&key, &short_key).is_none());
+}
+
+#[test]
+fn test_instance_metadata_time_check() {
+    let mut naming = NamingActor::new();
+    let mut instance = Instance::new("127.0.0.1".to_owned(), 8080);
+    instance.namespace_id = Arc::new("public".to_owned());
+    instance.service_name = Arc::new("foo".to_owned());
+    instance.group_name = Arc::new("DEFUALT".to_owned());
+    let key = instance.get_service_key();
+    let mut default_instance = instance.clone();
+    default_instance.port = 8081;
+    let mut metadata = HashMap::new();
+    metadata.insert(
+        super::PRESERVED_HEART_BEAT_INTERVAL.to_owned(),
+        "1000".to_owned(),
+    );
+    metadata.insert(
+        super::PRESERVED_HEART_BEAT_TIMEOUT.to_owned(),
+        "3000".to_owned(),
+    );
+    metadata.insert(
+        super::PRESERVED_IP_DELETE_TIMEOUT.to_owned(),
+        "6000".to_owned(),
+    );
+    instance.metadata = Arc::new(metadata);
+    assert_eq!(instance.get_heart_beat_interval(), 1000);
+    assert_eq!(default_instance.get_heart_beat_interval(), 5000);
+    naming.update_instance(&key, instance.clone(), None);
+    naming.update_instance(&key, default_instance, None);
+
+    let now = now_millis_i64();
+    let service = naming.service_map.get_mut(&key).unwrap();
+    let (remove_list, update_list) = service.time_check(now + 4000);
+    assert!(remove_list.is_empty());
+    assert_eq!(update_list.len(), 1);
+    assert_eq!(update_list[0].port, 8080);
+    //已标记为不健康的实例不重复处理
+    let (_, update_list) = service.time_check(now + 5000);
+    assert!(update_list.is_empty());
+    let (remove_list, update_list) = service.time_check(now + 7000);
+    assert_eq!(remove_list.len(), 1);
+    assert_eq!(remove_list[0].port, 8080);
+    assert!(update_list.is_empty());
+    assert_eq!(service.instance_size, 1);
+}
+
+#[test]
+fn test_service_selector() {
+    let mut naming = NamingActor::new();
+    let key = ServiceKey::new("public", "DEFUALT", "foo");
+    for (port, zone) in [(8080, "cn-east"), (8081, "cn-west"), (8082, "cn-east")] {
+        let mut instance = Instance::new("127.0.0.1".to_owned(), port);
+        instance.namespace_id = Arc::new("public".to_owned());
+        instance.service_name = Arc::new("foo".to_owned());
+        instance.group_name = Arc::new("DEFUALT".to_owned());
+        let mut metadata = HashMap::new();
+        metadata.insert("zone".to_owned(), zone.to_owned());
+        metadata.insert("version".to_owned(), format!("v{}", port - 8079));
+        instance.metadata = Arc::new(metadata);
+        naming.update_instance(&key, instance, None);
+    }
+    assert_eq!(naming.get_instance_list(&key, "", false, None).len(), 3);
+
+    let service_info = ServiceDetailDto {
+        namespace_id: key.namespace_id.clone(),
+        service_name: key.service_name.clone(),
+        group_name: key.group_name.clone(),
+        metadata: None,
+        protect_threshold: None,
+        selector: Some(Arc::new("zone=cn-east".to_owned())),
+    };
+    naming.update_service(service_info.clone());
+    assert_eq!(naming.get_instance_list(&key, "", false, None).len(), 2);
+    let selector = InstanceSelector::parse("version in (v2,v3)").unwrap();
+    let list = naming.get_instance_list(&key, "", false, Some(&selector));
+    assert_eq!(list.len(), 1);
+    assert_eq!(list[0].port, 8082);
+    let service_info_data = naming.get_service_info(&key, "".to_owned(), true, Some(&selector));
+    assert_eq!(service_info_data.hosts.unwrap().len(), 1);
+
+    //空表达式清除服务级选择器
+    naming.update_service(ServiceDetailDto {
+        selector: Some(Arc::new("".to_owned())),
+        ..service_info
+    });
+    assert_eq!(naming.get_instance_list(&key, "", false, None).len(), 3);
+}
+
+#[test]
+fn test_pick_instance() {
+    use super::balancer::LoadBalanceStrategy;
+
+    let mut naming = NamingActor::new();
+    let key = ServiceKey::new("public", "DEFUALT", "foo");
+    for (port, healthy, enabled) in [(8080, true, true), (8081, false, true), (8082, true, false)] {
+        let mut instance = Instance::new("127.0.0.1".to_owned(), port);
+        instance.namespace_id = key.namespace_id.clone();
+        instance.service_name = key.service_name.clone();
+        instance.group_name = key.group_name.clone();
+        instance.healthy = healthy;
+        instance.enabled = enabled;
+        naming.update_instance(&key, instance, None);
+    }
+    let param = PickInstanceParam {
+        strategy: LoadBalanceStrategy::RoundRobin,
+        ..Default::default()
+    };
+    for _ in 0..3 {
+        let instance = naming.pick_instance(&key, &param).unwrap();
+        assert_eq!(instance.port, 8080);
+    }
+    let missing_key = ServiceKey::new("public", "DEFUALT", "bar");
+    assert!(naming.pick_instance(&missing_key, &param).is_none());
+}
@@ -12,13 +12,15 @@ impl InstanceFilterUtils {
         instances.into_iter().filter(|i| i.healthy).collect()
     }
     pub fn default_instance_filter(
-        all_instances: Vec<Arc<Instance>>,
+        mut all_instances: Vec<Arc<Instance>>,
         metadata: Option<ServiceMetadata>,
         filter_headlthy: bool,
     ) -> Vec<Arc<Instance>> {
         if let Some(metadata) = metadata {
+            if let Some(selector) = metadata.selector.as_ref() {
+                all_instances = selector.filter(all_instances);
+            }
             let original_total = all_instances.len();
-            // all_instances = from metadata select
             let mut healthy_count = 0;
             for item in &all_instances {
                 if item.healthy {
@@ -58,9 +60,11 @@ impl InstanceFilterUtils {
         metadata: Option<ServiceMetadata>,
         filter_headlthy: bool,
     ) -> ServiceInfo {
+        if let Some(selector) = metadata.as_ref().and_then(|e| e.selector.as_ref()) {
+            service_info.hosts = service_info.hosts.map(|hosts| selector.filter(hosts));
+        }
         if let (Some(all_instances), Some(metadata)) = (service_info.hosts.as_ref(), metadata) {
             let original_total = all_instances.len();
-            // all_instances = from metadata select
             let mut healthy_count = 0;
             for item in all_instances {
                 if item.healthy {
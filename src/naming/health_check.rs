@@ -0,0 +1,394 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use actix::prelude::*;
+use bean_factory::{bean, Inject};
+use tokio::net::TcpStream;
+
+use crate::common::{hash_utils::get_hash_value, AppSysConfig};
+
+use super::{
+    cluster::{model::NamingRouteAddr, node_manage::NodeManage, route::NamingRoute},
+    core::{NamingActor, NamingCmd, NamingResult},
+    model::{Instance, InstanceUpdateTag, ServiceKey},
+};
+
+pub const HEALTH_CHECKER_TYPE_KEY: &str = "preserved.health.checker.type";
+pub const HEALTH_CHECKER_PATH_KEY: &str = "preserved.health.checker.path";
+pub const HEALTH_CHECKER_EXPECTED_CODE_KEY: &str = "preserved.health.checker.expected.code";
+
+///探测时间随机偏移的最大值，避免同一批探测集中发出
+const PROBE_JITTER_MILLIS: u64 = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HealthCheckType {
+    Tcp,
+    Http,
+    None,
+}
+
+impl HealthCheckType {
+    pub fn from_value(v: &str) -> Option<Self> {
+        match v.to_lowercase().as_str() {
+            "tcp" => Some(Self::Tcp),
+            "http" => Some(Self::Http),
+            "none" => Some(Self::None),
+            _ => None,
+        }
+    }
+}
+
+///
+/// 持久化实例的主动健康检查配置，从服务metadata中读取;
+/// key后加`.{clusterName}`的配置只对该集群生效，优先级高于服务级配置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HealthCheckConfig {
+    pub check_type: HealthCheckType,
+    pub path: String,
+    pub expected_code: u16,
+}
+
+impl Default for HealthCheckConfig {
+    fn default() -> Self {
+        Self {
+            check_type: HealthCheckType::Tcp,
+            path: "/".to_owned(),
+            expected_code: 200,
+        }
+    }
+}
+
+impl HealthCheckConfig {
+    pub fn from_metadata(metadata: &HashMap<String, String>, cluster_name: &str) -> Self {
+        let get_value = |key: &str| {
+            metadata
+                .get(&format!("{}.{}", key, cluster_name))
+                .or_else(|| metadata.get(key))
+                .filter(|v| !v.is_empty())
+        };
+        let mut config = Self::default();
+        if let Some(check_type) =
+            get_value(HEALTH_CHECKER_TYPE_KEY).and_then(|v| HealthCheckType::from_value(v))
+        {
+            config.check_type = check_type;
+        }
+        if let Some(path) = get_value(HEALTH_CHECKER_PATH_KEY) {
+            config.path = if path.starts_with('/') {
+                path.to_owned()
+            } else {
+                format!("/{}", path)
+            };
+        }
+        if let Some(code) = get_value(HEALTH_CHECKER_EXPECTED_CODE_KEY).and_then(|v| v.parse().ok())
+        {
+            config.expected_code = code;
+        }
+        config
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct HealthCheckService {
+    pub key: ServiceKey,
+    pub metadata: Arc<HashMap<String, String>>,
+    pub instances: Vec<Arc<Instance>>,
+}
+
+pub struct HealthChecker {
+    client: reqwest::Client,
+    timeout: Duration,
+}
+
+impl HealthChecker {
+    pub fn new(timeout: Duration) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .unwrap_or_default();
+        Self { client, timeout }
+    }
+
+    ///
+    /// 返回探测结果，不需要检查时返回None
+    pub async fn check(&self, config: &HealthCheckConfig, ip: &str, port: u32) -> Option<bool> {
+        match config.check_type {
+            HealthCheckType::Tcp => Some(self.check_tcp(ip, port).await),
+            HealthCheckType::Http => Some(self.check_http(config, ip, port).await),
+            HealthCheckType::None => None,
+        }
+    }
+
+    async fn check_tcp(&self, ip: &str, port: u32) -> bool {
+        let addr = format!("{}:{}", ip, port);
+        matches!(
+            tokio::time::timeout(self.timeout, TcpStream::connect(addr)).await,
+            Ok(Ok(_))
+        )
+    }
+
+    async fn check_http(&self, config: &HealthCheckConfig, ip: &str, port: u32) -> bool {
+        let url = format!("http://{}:{}{}", ip, port, &config.path);
+        match self.client.get(url).send().await {
+            Ok(resp) => resp.status().as_u16() == config.expected_code,
+            Err(_) => false,
+        }
+    }
+}
+
+pub struct HealthCheckUtils;
+
+impl HealthCheckUtils {
+    ///
+    /// 按每秒探测数错开每个探测的开始时间，并加上随机偏移
+    pub fn probe_delay(index: usize, rate: u64, seed: u64) -> Duration {
+        let rate = rate.max(1);
+        let base = index as u64 * 1000 / rate;
+        Duration::from_millis(base + seed % PROBE_JITTER_MILLIS)
+    }
+}
+
+#[bean(inject)]
+pub struct NamingHealthCheckActor {
+    naming_addr: Option<Addr<NamingActor>>,
+    naming_route: Option<Arc<NamingRoute>>,
+    node_manage: Option<Arc<NodeManage>>,
+    checker: Arc<HealthChecker>,
+    interval: Duration,
+    rate: u64,
+    round: u64,
+    running: bool,
+}
+
+impl NamingHealthCheckActor {
+    pub fn new(sys_config: &AppSysConfig) -> Self {
+        Self {
+            naming_addr: None,
+            naming_route: None,
+            node_manage: None,
+            checker: Arc::new(HealthChecker::new(Duration::from_millis(
+                sys_config.naming_health_check_timeout_ms,
+            ))),
+            interval: Duration::from_millis(sys_config.naming_health_check_interval_ms),
+            rate: sys_config.naming_health_check_rate,
+            round: 0,
+            running: false,
+        }
+    }
+
+    fn check_timer(&self, ctx: &mut Context<Self>) {
+        ctx.run_later(self.interval, |act, ctx| {
+            act.check_round(ctx);
+            act.check_timer(ctx);
+        });
+    }
+
+    fn check_round(&mut self, ctx: &mut Context<Self>) {
+        //上一轮未完成时跳过
+        if self.running {
+            return;
+        }
+        let (naming_addr, naming_route, node_manage) =
+            match (&self.naming_addr, &self.naming_route, &self.node_manage) {
+                (Some(a), Some(b), Some(c)) => (a.clone(), b.clone(), c.clone()),
+                _ => return,
+            };
+        self.running = true;
+        self.round += 1;
+        let round = self.round;
+        let rate = self.rate;
+        let checker = self.checker.clone();
+        async move {
+            let services = match naming_addr
+                .send(NamingCmd::QueryHealthCheckServices)
+                .await??
+            {
+                NamingResult::HealthCheckServices(list) => list,
+                _ => return Ok(()),
+            };
+            let mut targets = vec![];
+            for service in services {
+                //只由服务所属分区的节点探测
+                if !matches!(
+                    node_manage.route_addr(&service.key).await,
+                    NamingRouteAddr::Local(_)
+                ) {
+                    continue;
+                }
+                for instance in service.instances {
+                    let config =
+                        HealthCheckConfig::from_metadata(&service.metadata, &instance.cluster_name);
+                    if config.check_type != HealthCheckType::None {
+                        targets.push((instance, config));
+                    }
+                }
+            }
+            let probes = targets
+                .into_iter()
+                .enumerate()
+                .map(|(i, (instance, config))| {
+                    let checker = checker.clone();
+                    let seed = get_hash_value(&(instance.get_instance_key(), round));
+                    let delay = HealthCheckUtils::probe_delay(i, rate, seed);
+                    async move {
+                        tokio::time::sleep(delay).await;
+                        let healthy = checker.check(&config, &instance.ip, instance.port).await;
+                        (instance, healthy)
+                    }
+                });
+            for (instance, healthy) in futures_util::future::join_all(probes).await {
+                match healthy {
+                    Some(healthy) if healthy != instance.healthy => {
+                        log::info!(
+                            "instance {}:{} of {} health status change to {}",
+                            &instance.ip,
+                            instance.port,
+                            &instance.service_name,
+                            healthy
+                        );
+                        let mut instance = instance.as_ref().clone();
+                        instance.healthy = healthy;
+                        //单个实例更新失败不影响本轮其它实例
+                        if let Err(err) = naming_route
+                            .update_instance(
+                                instance.clone(),
+                                Some(InstanceUpdateTag::new_healthy_only()),
+                            )
+                            .await
+                        {
+                            log::warn!(
+                                "update instance {}:{} health status error,{}",
+                                &instance.ip,
+                                instance.port,
+                                err
+                            );
+                            continue;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Ok(())
+        }
+        .into_actor(self)
+        .map(|r: anyhow::Result<()>, act, _ctx| {
+            act.running = false;
+            if let Err(err) = r {
+                log::warn!("naming health check error,{}", err);
+            }
+        })
+        .spawn(ctx);
+    }
+}
+
+impl Actor for NamingHealthCheckActor {
+    type Context = Context<Self>;
+
+    fn started(&mut self, _ctx: &mut Self::Context) {
+        log::info!("NamingHealthCheckActor started")
+    }
+}
+
+impl Inject for NamingHealthCheckActor {
+    type Context = Context<Self>;
+
+    fn inject(
+        &mut self,
+        factory_data: bean_factory::FactoryData,
+        _factory: bean_factory::BeanFactory,
+        ctx: &mut Self::Context,
+    ) {
+        self.naming_addr = factory_data.get_actor();
+        self.naming_route = factory_data.get_bean();
+        self.node_manage = factory_data.get_bean();
+        if !self.interval.is_zero() {
+            self.check_timer(ctx);
+        }
+    }
+}
+
+#[test]
+fn health_check_config() {
+    let mut metadata = HashMap::new();
+    assert_eq!(
+        HealthCheckConfig::from_metadata(&metadata, "DEFAULT"),
+        HealthCheckConfig::default()
+    );
+    metadata.insert(HEALTH_CHECKER_TYPE_KEY.to_owned(), "HTTP".to_owned());
+    metadata.insert(HEALTH_CHECKER_PATH_KEY.to_owned(), "health".to_owned());
+    metadata.insert(format!("{}.c1", HEALTH_CHECKER_TYPE_KEY), "none".to_owned());
+    metadata.insert(
+        format!("{}.c2", HEALTH_CHECKER_EXPECTED_CODE_KEY),
+        "204".to_owned(),
+    );
+    let config = HealthCheckConfig::from_metadata(&metadata, "DEFAULT");
+    assert_eq!(config.check_type, HealthCheckType::Http);
+    assert_eq!(config.path, "/health");
+    assert_eq!(config.expected_code, 200);
+    let config = HealthCheckConfig::from_metadata(&metadata, "c1");
+    assert_eq!(config.check_type, HealthCheckType::None);
+    let config = HealthCheckConfig::from_metadata(&metadata, "c2");
+    assert_eq!(config.expected_code, 204);
+
+    //每秒10个探测，第20个在2秒后开始，偏移不超过1秒
+    let delay = HealthCheckUtils::probe_delay(20, 10, 1234567);
+    assert!(delay >= Duration::from_millis(2000) && delay < Duration::from_millis(3000));
+}
+
+#[actix_rt::test]
+async fn health_check_probe() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    async fn serve_http(listener: TcpListener, status: &'static str) {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let mut buf = [0u8; 1024];
+            let _ = stream.read(&mut buf).await;
+            let resp = format!(
+                "HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                status
+            );
+            let _ = stream.write_all(resp.as_bytes()).await;
+        }
+    }
+
+    let checker = HealthChecker::new(Duration::from_millis(1000));
+    let tcp_config = HealthCheckConfig::default();
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port() as u32;
+    assert_eq!(
+        checker.check(&tcp_config, "127.0.0.1", port).await,
+        Some(true)
+    );
+    drop(listener);
+    assert_eq!(
+        checker.check(&tcp_config, "127.0.0.1", port).await,
+        Some(false)
+    );
+
+    let http_config = HealthCheckConfig {
+        check_type: HealthCheckType::Http,
+        ..Default::default()
+    };
+    let ok_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let ok_port = ok_listener.local_addr().unwrap().port() as u32;
+    tokio::spawn(serve_http(ok_listener, "200 OK"));
+    let err_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let err_port = err_listener.local_addr().unwrap().port() as u32;
+    tokio::spawn(serve_http(err_listener, "503 Service Unavailable"));
+    assert_eq!(
+        checker.check(&http_config, "127.0.0.1", ok_port).await,
+        Some(true)
+    );
+    assert_eq!(
+        checker.check(&http_config, "127.0.0.1", err_port).await,
+        Some(false)
+    );
+
+    let none_config = HealthCheckConfig {
+        check_type: HealthCheckType::None,
+        ..Default::default()
+    };
+    assert_eq!(
+        checker.check(&none_config, "127.0.0.1", ok_port).await,
+        None
+    );
+}
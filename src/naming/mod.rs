@@ -1,11 +1,15 @@
 pub mod api;
 pub mod api_model;
+pub mod balancer;
 pub mod core;
 pub(crate) mod filter;
+pub mod health_check;
 pub mod listener;
 pub mod model;
 pub mod naming_delay_nofity;
+pub mod naming_persistent;
 pub mod naming_subscriber;
+pub mod selector;
 pub mod service;
 pub mod udp_actor;
 //pub(crate) mod dal;
@@ -20,6 +24,13 @@ pub const RESPONSE_CODE_OK: i32 = 10200;
 pub const CLIENT_BEAT_INTERVAL_KEY: &str = "clientBeatInterval";
 pub const LIGHT_BEAT_ENABLED_KEY: &str = "lightBeatEnabled";
 
+pub const PRESERVED_HEART_BEAT_INTERVAL: &str = "preserved.heart.beat.interval";
+pub const PRESERVED_HEART_BEAT_TIMEOUT: &str = "preserved.heart.beat.timeout";
+pub const PRESERVED_IP_DELETE_TIMEOUT: &str = "preserved.ip.delete.timeout";
+pub const DEFAULT_HEART_BEAT_INTERVAL: i64 = 5000;
+pub const DEFAULT_HEART_BEAT_TIMEOUT: i64 = 15000;
+pub const DEFAULT_IP_DELETE_TIMEOUT: i64 = 30000;
+
 pub const DEFAULT_NAMESPACE: &str = "public";
 pub const DEFAULT_CLUSTER: &str = "DEFAULT";
 pub const DEFAULT_GROUP: &str = "DEFAULT_GROUP";
@@ -4,6 +4,11 @@ use serde::{Deserialize, Serialize};
 
 use crate::now_millis_i64;
 
+use super::{
+    DEFAULT_HEART_BEAT_INTERVAL, DEFAULT_HEART_BEAT_TIMEOUT, DEFAULT_IP_DELETE_TIMEOUT,
+    PRESERVED_HEART_BEAT_INTERVAL, PRESERVED_HEART_BEAT_TIMEOUT, PRESERVED_IP_DELETE_TIMEOUT,
+};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Instance {
@@ -102,8 +107,32 @@ impl Instance {
         format!("{}#{}", &self.ip, &self.port)
     }
 
+    fn get_metadata_millis(&self, key: &str, default_value: i64) -> i64 {
+        self.metadata
+            .get(key)
+            .and_then(|v| v.parse().ok())
+            .filter(|v: &i64| *v > 0)
+            .unwrap_or(default_value)
+    }
+
+    pub fn get_heart_beat_interval(&self) -> i64 {
+        self.get_metadata_millis(PRESERVED_HEART_BEAT_INTERVAL, DEFAULT_HEART_BEAT_INTERVAL)
+    }
+
+    pub fn get_heart_beat_timeout(&self) -> i64 {
+        self.get_metadata_millis(PRESERVED_HEART_BEAT_TIMEOUT, DEFAULT_HEART_BEAT_TIMEOUT)
+    }
+
+    pub fn get_ip_delete_timeout(&self) -> i64 {
+        self.get_metadata_millis(PRESERVED_IP_DELETE_TIMEOUT, DEFAULT_IP_DELETE_TIMEOUT)
+    }
+
     pub(crate) fn get_time_info(&self) -> InstanceTimeInfo {
-        InstanceTimeInfo::new(self.get_short_key(), self.last_modified_millis)
+        let mut time_info = InstanceTimeInfo::new(self.get_short_key(), self.last_modified_millis);
+        time_info.healthy_timeout = self.get_heart_beat_timeout();
+        //删除超时不能小于不健康超时
+        time_info.delete_timeout = self.get_ip_delete_timeout().max(time_info.healthy_timeout);
+        time_info
     }
 }
 
@@ -156,6 +185,9 @@ pub struct ServiceDetailDto {
     pub group_name: Arc<String>,
     pub metadata: Option<Arc<HashMap<String, String>>>,
     pub protect_threshold: Option<f32>,
+    /// 实例选择器表达式，空字符串表示清除
+    #[serde(default)]
+    pub selector: Option<Arc<String>>,
 }
 
 impl ServiceDetailDto {
@@ -175,9 +207,23 @@ pub struct InstanceUpdateTag {
     pub enabled: bool,
     pub ephemeral: bool,
     pub from_update: bool,
+    ///只更新健康状态，其它字段保持原值，实例不存在时不新增
+    #[serde(default)]
+    pub healthy_only: bool,
 }
 
 impl InstanceUpdateTag {
+    pub fn new_healthy_only() -> Self {
+        Self {
+            weight: false,
+            metadata: false,
+            enabled: false,
+            ephemeral: false,
+            from_update: false,
+            healthy_only: true,
+        }
+    }
+
     pub fn is_al(&self) -> bool {
         self.weight && self.metadata && self.enabled && self.ephemeral
     }
@@ -194,6 +240,7 @@ impl Default for InstanceUpdateTag {
             enabled: true,
             ephemeral: true,
             from_update: false,
+            healthy_only: false,
         }
     }
 }
@@ -296,6 +343,10 @@ pub(crate) struct InstanceTimeInfo {
     pub(crate) time: i64,
     pub(crate) instance_id: InstanceShortKey,
     pub(crate) enable: bool,
+    ///超过该时长未心跳标记为不健康
+    pub(crate) healthy_timeout: i64,
+    ///超过该时长未心跳删除实例
+    pub(crate) delete_timeout: i64,
 }
 
 impl InstanceTimeInfo {
@@ -304,6 +355,8 @@ impl InstanceTimeInfo {
             time,
             instance_id,
             enable: true,
+            healthy_timeout: DEFAULT_HEART_BEAT_TIMEOUT,
+            delete_timeout: DEFAULT_IP_DELETE_TIMEOUT,
         }
     }
 }
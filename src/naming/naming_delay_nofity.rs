@@ -14,11 +14,15 @@ use crate::{
 use super::{
     core::{NamingActor, NamingCmd, NamingResult},
     model::{ServiceInfo, ServiceKey},
+    selector::InstanceSelector,
 };
 
+type NamingDelayKey = (ServiceKey, Option<Arc<InstanceSelector>>);
+
 #[derive(Clone, Default)]
 pub struct NamingDelayEvent {
     pub key: ServiceKey,
+    pub selector: Option<Arc<InstanceSelector>>,
     pub client_id_set: HashSet<Arc<String>>,
     pub service_info: Option<ServiceInfo>,
     pub conn_manage: Option<Addr<BiStreamManage>>,
@@ -48,7 +52,7 @@ impl NotifyEvent for NamingDelayEvent {
 
 #[bean(inject)]
 pub struct DelayNotifyActor {
-    inner_delay_notify: DelayNotify<ServiceKey, NamingDelayEvent>,
+    inner_delay_notify: DelayNotify<NamingDelayKey, NamingDelayEvent>,
     conn_manage: Option<Addr<BiStreamManage>>,
     naming_addr: Option<Addr<NamingActor>>,
     delay: u64,
@@ -93,7 +97,12 @@ impl DelayNotifyActor {
         if let Some(naming_addr) = naming_addr {
             for mut event in events {
                 //println!("fill_event_data_and_notify, {:?}",&event.key);
-                let cmd = NamingCmd::QueryServiceInfo(event.key.clone(), "".to_owned(), true);
+                let cmd = NamingCmd::QueryServiceInfo(
+                    event.key.clone(),
+                    "".to_owned(),
+                    true,
+                    event.selector.clone(),
+                );
                 match naming_addr.send(cmd).await {
                     Ok(res) => {
                         let result: NamingResult = res.unwrap();
@@ -149,7 +158,11 @@ impl Supervised for DelayNotifyActor {
 #[derive(Message)]
 #[rtype(result = "anyhow::Result<DelayNotifyResult>")]
 pub enum DelayNotifyCmd {
-    Notify(ServiceKey, HashSet<Arc<String>>),
+    Notify(
+        ServiceKey,
+        Option<Arc<InstanceSelector>>,
+        HashSet<Arc<String>>,
+    ),
 }
 
 pub enum DelayNotifyResult {
@@ -161,15 +174,19 @@ impl Handler<DelayNotifyCmd> for DelayNotifyActor {
 
     fn handle(&mut self, msg: DelayNotifyCmd, _ctx: &mut Context<Self>) -> Self::Result {
         match msg {
-            DelayNotifyCmd::Notify(key, client_id_set) => {
+            DelayNotifyCmd::Notify(key, selector, client_id_set) => {
                 let event = NamingDelayEvent {
                     key,
+                    selector,
                     client_id_set,
                     service_info: None,
                     conn_manage: self.conn_manage.to_owned(),
                 };
-                self.inner_delay_notify
-                    .add_event(self.delay, event.key.clone(), event)?;
+                self.inner_delay_notify.add_event(
+                    self.delay,
+                    (event.key.clone(), event.selector.clone()),
+                    event,
+                )?;
             }
         }
         Ok(DelayNotifyResult::None)
@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+
+use super::model::{Instance, InstanceKey, InstanceUpdateTag};
+
+///
+/// 持久化实例(ephemeral=false)变更请求，通过raft日志同步到所有节点
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum NamingPersistentReq {
+    UpdateInstance {
+        instance: Instance,
+        tag: Option<InstanceUpdateTag>,
+    },
+    RemoveInstance {
+        instance: Instance,
+    },
+}
+
+pub struct NamingPersistentUtils;
+
+impl NamingPersistentUtils {
+    pub fn build_key(key: &InstanceKey) -> Vec<u8> {
+        format!(
+            "{}\x02{}\x02{}\x02{}\x02{}",
+            &key.namespace_id, &key.group_name, &key.service_name, &key.ip, key.port
+        )
+        .as_bytes()
+        .to_owned()
+    }
+
+    pub fn to_bytes(instance: &Instance) -> anyhow::Result<Vec<u8>> {
+        Ok(serde_json::to_vec(instance)?)
+    }
+
+    pub fn from_bytes(v: &[u8]) -> anyhow::Result<Instance> {
+        Ok(serde_json::from_slice(v)?)
+    }
+
+    ///
+    /// 持久化实例不绑定客户端连接，也不参与集群实例分区同步
+    pub fn normalize(mut instance: Instance) -> Instance {
+        instance.ephemeral = false;
+        instance.from_grpc = false;
+        instance.from_cluster = 0;
+        instance.client_id = Default::default();
+        instance
+    }
+}
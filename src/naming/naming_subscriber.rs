@@ -10,6 +10,7 @@ use actix::prelude::*;
 use super::{
     model::{Instance, ServiceInfo, ServiceKey},
     naming_delay_nofity::{DelayNotifyActor, DelayNotifyCmd},
+    selector::InstanceSelector,
 };
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
@@ -36,11 +37,14 @@ pub struct ListenerKey {
 pub struct NamingListenerItem {
     pub service_key: ServiceKey,
     pub clusters: Option<HashSet<String>>,
+    pub selector: Option<Arc<InstanceSelector>>,
 }
 
+type SubscribeValue = (Option<HashSet<String>>, Option<Arc<InstanceSelector>>);
+
 #[derive(Default)]
 pub struct Subscriber {
-    listener: HashMap<ServiceKey, HashMap<Arc<String>, Option<HashSet<String>>>>,
+    listener: HashMap<ServiceKey, HashMap<Arc<String>, SubscribeValue>>,
     client_keys: HashMap<Arc<String>, HashSet<ServiceKey>>,
     notify_addr: Option<Addr<DelayNotifyActor>>,
 }
@@ -58,6 +62,31 @@ impl Subscriber {
         self.notify_addr = Some(notify_addr);
     }
 
+    ///
+    /// 推送按服务区分，同一连接对同一服务只能使用一个选择器订阅，变更选择器需要先取消订阅
+    pub fn check_selector(
+        &self,
+        client_id: &Arc<String>,
+        items: &[NamingListenerItem],
+    ) -> anyhow::Result<()> {
+        for item in items {
+            if let Some((_, selector)) = self
+                .listener
+                .get(&item.service_key)
+                .and_then(|set| set.get(client_id))
+            {
+                if selector != &item.selector {
+                    return Err(anyhow::anyhow!(
+                        "service {}@@{} is already subscribed with another selector",
+                        &item.service_key.group_name,
+                        &item.service_key.service_name
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
     pub fn add_subscribe(&mut self, client_id: Arc<String>, items: Vec<NamingListenerItem>) {
         match self.client_keys.get_mut(&client_id) {
             Some(set) => {
@@ -76,11 +105,11 @@ impl Subscriber {
         for item in items {
             match self.listener.get_mut(&item.service_key) {
                 Some(set) => {
-                    set.insert(client_id.clone(), item.clusters);
+                    set.insert(client_id.clone(), (item.clusters, item.selector));
                 }
                 None => {
                     let mut set = HashMap::new();
-                    set.insert(client_id.clone(), item.clusters);
+                    set.insert(client_id.clone(), (item.clusters, item.selector));
                     self.listener.insert(item.service_key, set);
                 }
             };
@@ -153,12 +182,53 @@ impl Subscriber {
         //log::info!("naming_subscriber notify {:?}",&key);
         if let Some(notify_addr) = &self.notify_addr {
             if let Some(set) = self.listener.get(&key) {
-                let mut client_id_set = HashSet::new();
-                for item in set.keys() {
-                    client_id_set.insert(item.clone());
+                //按订阅的选择器分组推送，相同选择器的客户端共用一次查询结果
+                let mut selector_map: HashMap<Option<Arc<InstanceSelector>>, HashSet<Arc<String>>> =
+                    HashMap::new();
+                for (client_id, (_, selector)) in set {
+                    selector_map
+                        .entry(selector.clone())
+                        .or_default()
+                        .insert(client_id.clone());
+                }
+                for (selector, client_id_set) in selector_map {
+                    notify_addr.do_send(DelayNotifyCmd::Notify(
+                        key.clone(),
+                        selector,
+                        client_id_set,
+                    ));
                 }
-                notify_addr.do_send(DelayNotifyCmd::Notify(key, client_id_set));
             }
         }
     }
 }
+
+#[test]
+fn subscriber_check_selector() {
+    let key = ServiceKey::new("public", "DEFAULT_GROUP", "foo");
+    let build_item = |selector: Option<&str>| NamingListenerItem {
+        service_key: key.clone(),
+        clusters: None,
+        selector: selector.map(|e| Arc::new(InstanceSelector::parse(e).unwrap())),
+    };
+    let client_id = Arc::new("c1".to_owned());
+    let mut subscriber = Subscriber::new();
+    let items = vec![build_item(Some("zone=cn-east"))];
+    assert!(subscriber.check_selector(&client_id, &items).is_ok());
+    subscriber.add_subscribe(client_id.clone(), items.clone());
+    assert!(subscriber.check_selector(&client_id, &items).is_ok());
+    assert!(subscriber
+        .check_selector(&client_id, &[build_item(Some("zone=cn-west"))])
+        .is_err());
+    assert!(subscriber
+        .check_selector(&client_id, &[build_item(None)])
+        .is_err());
+    //其它连接不受影响
+    assert!(subscriber
+        .check_selector(&Arc::new("c2".to_owned()), &[build_item(None)])
+        .is_ok());
+    subscriber.remove_subscribe(client_id.clone(), items);
+    assert!(subscriber
+        .check_selector(&client_id, &[build_item(None)])
+        .is_ok());
+}
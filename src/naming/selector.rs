@@ -0,0 +1,291 @@
+use std::{
+    hash::{Hash, Hasher},
+    sync::Arc,
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::model::Instance;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SelectorOperator {
+    Equal,
+    NotEqual,
+    In,
+    NotIn,
+    Exists,
+    NotExists,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelectorRequirement {
+    pub key: String,
+    pub operator: SelectorOperator,
+    pub values: Vec<String>,
+}
+
+impl SelectorRequirement {
+    fn matches(&self, instance: &Instance) -> bool {
+        let value = instance.metadata.get(&self.key);
+        match self.operator {
+            SelectorOperator::Equal | SelectorOperator::In => value
+                .map(|v| self.values.iter().any(|e| e == v))
+                .unwrap_or(false),
+            SelectorOperator::NotEqual | SelectorOperator::NotIn => value
+                .map(|v| !self.values.iter().any(|e| e == v))
+                .unwrap_or(true),
+            SelectorOperator::Exists => value.is_some(),
+            SelectorOperator::NotExists => value.is_none(),
+        }
+    }
+}
+
+///
+/// nacos selector参数的json格式
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SelectorParam {
+    #[serde(default)]
+    r#type: Option<String>,
+    #[serde(default)]
+    expression: Option<String>,
+}
+
+///
+/// 按实例metadata过滤实例的标签表达式，多个条件用逗号分隔，需要同时满足;
+/// 支持 `k=v`、`k!=v`、`k in (v1,v2)`、`k notin (v1,v2)`、`k`、`!k`
+#[derive(Debug, Clone)]
+pub struct InstanceSelector {
+    pub expression: Arc<String>,
+    requirements: Vec<SelectorRequirement>,
+}
+
+impl PartialEq for InstanceSelector {
+    fn eq(&self, other: &Self) -> bool {
+        self.expression == other.expression
+    }
+}
+
+impl Eq for InstanceSelector {}
+
+impl Hash for InstanceSelector {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.expression.hash(state);
+    }
+}
+
+impl InstanceSelector {
+    pub fn parse(expression: &str) -> anyhow::Result<Self> {
+        let expression = expression.trim();
+        if expression.is_empty() {
+            return Err(anyhow::anyhow!("selector expression is empty"));
+        }
+        let mut requirements = vec![];
+        for term in Self::split_terms(expression)? {
+            requirements.push(Self::parse_term(term)?);
+        }
+        Ok(Self {
+            expression: Arc::new(expression.to_owned()),
+            requirements,
+        })
+    }
+
+    ///
+    /// 兼容nacos的`{"type":"label","expression":"..."}`格式，为空或type为none时返回None
+    pub fn parse_param(param: &str) -> anyhow::Result<Option<Self>> {
+        let param = param.trim();
+        let expression = if param.starts_with('{') {
+            let v: SelectorParam = serde_json::from_str(param)
+                .map_err(|e| anyhow::anyhow!("selector is not valid json, {}", e))?;
+            if v.r#type.as_deref().map(|t| t.eq_ignore_ascii_case("none")) == Some(true) {
+                return Ok(None);
+            }
+            v.expression.unwrap_or_default()
+        } else {
+            param.to_owned()
+        };
+        if expression.trim().is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Self::parse(&expression)?))
+    }
+
+    pub fn matches(&self, instance: &Instance) -> bool {
+        self.requirements.iter().all(|e| e.matches(instance))
+    }
+
+    pub fn filter(&self, instances: Vec<Arc<Instance>>) -> Vec<Arc<Instance>> {
+        instances.into_iter().filter(|e| self.matches(e)).collect()
+    }
+
+    fn split_terms(expression: &str) -> anyhow::Result<Vec<&str>> {
+        let mut terms = vec![];
+        let mut depth = 0;
+        let mut start = 0;
+        for (i, c) in expression.char_indices() {
+            match c {
+                '(' => depth += 1,
+                ')' => {
+                    if depth == 0 {
+                        return Err(anyhow::anyhow!("selector has unmatched ')'"));
+                    }
+                    depth -= 1;
+                }
+                ',' if depth == 0 => {
+                    terms.push(expression[start..i].trim());
+                    start = i + 1;
+                }
+                _ => {}
+            }
+        }
+        if depth != 0 {
+            return Err(anyhow::anyhow!("selector has unmatched '('"));
+        }
+        terms.push(expression[start..].trim());
+        if terms.iter().any(|e| e.is_empty()) {
+            return Err(anyhow::anyhow!("selector has empty requirement"));
+        }
+        Ok(terms)
+    }
+
+    fn parse_term(term: &str) -> anyhow::Result<SelectorRequirement> {
+        if let Some(pos) = term.find('(') {
+            if !term.ends_with(')') {
+                return Err(anyhow::anyhow!(
+                    "selector requirement '{}' is invalid",
+                    term
+                ));
+            }
+            let items: Vec<&str> = term[..pos].split_whitespace().collect();
+            let operator = match items.get(1) {
+                Some(&"in") => SelectorOperator::In,
+                Some(&"notin") => SelectorOperator::NotIn,
+                _ => {
+                    return Err(anyhow::anyhow!(
+                        "selector requirement '{}' need in or notin operator",
+                        term
+                    ))
+                }
+            };
+            if items.len() != 2 {
+                return Err(anyhow::anyhow!(
+                    "selector requirement '{}' is invalid",
+                    term
+                ));
+            }
+            let mut values = vec![];
+            for value in term[pos + 1..term.len() - 1].split(',') {
+                values.push(Self::check_value(value.trim(), term)?);
+            }
+            return Ok(SelectorRequirement {
+                key: Self::check_key(items[0], term)?,
+                operator,
+                values,
+            });
+        }
+        let (key, operator, value) = if let Some((k, v)) = term.split_once("!=") {
+            (k, SelectorOperator::NotEqual, Some(v))
+        } else if let Some((k, v)) = term.split_once("==") {
+            (k, SelectorOperator::Equal, Some(v))
+        } else if let Some((k, v)) = term.split_once('=') {
+            (k, SelectorOperator::Equal, Some(v))
+        } else if let Some(k) = term.strip_prefix('!') {
+            (k, SelectorOperator::NotExists, None)
+        } else {
+            (term, SelectorOperator::Exists, None)
+        };
+        let values = match value {
+            Some(v) => vec![Self::check_value(v.trim(), term)?],
+            None => vec![],
+        };
+        Ok(SelectorRequirement {
+            key: Self::check_key(key.trim(), term)?,
+            operator,
+            values,
+        })
+    }
+
+    fn check_key(key: &str, term: &str) -> anyhow::Result<String> {
+        if key.is_empty()
+            || !key
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_' | '/'))
+        {
+            return Err(anyhow::anyhow!(
+                "selector requirement '{}' has invalid key '{}'",
+                term,
+                key
+            ));
+        }
+        Ok(key.to_owned())
+    }
+
+    fn check_value(value: &str, term: &str) -> anyhow::Result<String> {
+        if value.is_empty()
+            || value
+                .chars()
+                .any(|c| c.is_whitespace() || matches!(c, '=' | '!' | '(' | ')' | ','))
+        {
+            return Err(anyhow::anyhow!(
+                "selector requirement '{}' has invalid value '{}'",
+                term,
+                value
+            ));
+        }
+        Ok(value.to_owned())
+    }
+}
+
+#[test]
+fn instance_selector() {
+    use std::collections::HashMap;
+
+    let build_instance = |items: &[(&str, &str)]| {
+        let mut metadata = HashMap::new();
+        for (k, v) in items {
+            metadata.insert(k.to_string(), v.to_string());
+        }
+        Instance {
+            metadata: Arc::new(metadata),
+            ..Default::default()
+        }
+    };
+    let a = build_instance(&[("zone", "cn-east"), ("version", "v2")]);
+    let b = build_instance(&[("zone", "cn-west"), ("version", "v3"), ("canary", "1")]);
+    let c = build_instance(&[("version", "v1")]);
+
+    let selector = InstanceSelector::parse("zone=cn-east").unwrap();
+    assert!(selector.matches(&a) && !selector.matches(&b) && !selector.matches(&c));
+    let selector = InstanceSelector::parse("version in (v2, v3), !canary").unwrap();
+    assert!(selector.matches(&a) && !selector.matches(&b) && !selector.matches(&c));
+    let selector = InstanceSelector::parse("version notin (v1),zone != cn-east").unwrap();
+    assert!(!selector.matches(&a) && selector.matches(&b) && !selector.matches(&c));
+    let selector = InstanceSelector::parse("zone").unwrap();
+    assert!(selector.matches(&a) && selector.matches(&b) && !selector.matches(&c));
+
+    for expression in [
+        "",
+        "zone=",
+        "=cn-east",
+        "version in ()",
+        "version in (v1",
+        "version between (v1,v2)",
+        "zone=cn east",
+        "a,,b",
+    ] {
+        assert!(
+            InstanceSelector::parse(expression).is_err(),
+            "{}",
+            expression
+        );
+    }
+
+    let selector = InstanceSelector::parse_param(r#"{"type":"label","expression":"zone=cn-east"}"#)
+        .unwrap()
+        .unwrap();
+    assert_eq!(selector.expression.as_str(), "zone=cn-east");
+    assert!(InstanceSelector::parse_param(r#"{"type":"none"}"#)
+        .unwrap()
+        .is_none());
+    assert!(InstanceSelector::parse_param("").unwrap().is_none());
+}
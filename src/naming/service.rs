@@ -12,15 +12,18 @@ use crate::now_millis;
 
 use super::{
     api_model::QueryListResult,
+    balancer::HashRingCache,
     model::{
         Instance, InstanceShortKey, InstanceTimeInfo, InstanceUpdateTag, ServiceDetailDto,
         ServiceKey, UpdateInstanceType,
     },
+    selector::InstanceSelector,
 };
 
 #[derive(Debug, Clone, Default)]
 pub struct ServiceMetadata {
     pub protect_threshold: f32,
+    pub selector: Option<Arc<InstanceSelector>>,
 }
 
 type InstanceMetaData = Arc<HashMap<String, String>>;
@@ -32,6 +35,8 @@ pub struct Service {
     pub group_service: Arc<String>,
     pub metadata: Arc<HashMap<String, String>>,
     pub protect_threshold: f32,
+    //服务级实例选择器，查询及推送时只返回匹配的实例
+    pub selector: Option<Arc<InstanceSelector>>,
     pub last_modified_millis: i64,
     //pub has_instance:bool,
     pub namespace_id: Arc<String>,
@@ -44,6 +49,10 @@ pub struct Service {
     pub(crate) instances: HashMap<InstanceShortKey, Arc<Instance>>,
     pub(crate) timeinfos: LinkedList<InstanceTimeInfo>,
     pub(crate) instance_metadata_map: HashMap<InstanceShortKey, InstanceMetaData>,
+    //负载均衡选择实例的轮询序号
+    pub(crate) pick_sequence: u64,
+    //一致性hash环缓存，实例增减时清空
+    pub(crate) hash_ring_cache: HashRingCache,
 }
 
 impl Service {
@@ -71,6 +80,9 @@ impl Service {
             //println!("service-consumer update_instance {:?}",&instance);
         }
         */
+        if update_tag.as_ref().map(|e| e.healthy_only).unwrap_or(false) {
+            return self.update_instance_healthy(&instance.get_short_key(), instance.healthy);
+        }
         instance.namespace_id = self.namespace_id.clone();
         instance.group_name = self.group_name.clone();
         instance.service_name = self.service_name.clone();
@@ -145,11 +157,12 @@ impl Service {
             }
             self.instance_size += 1;
             self.healthy_instance_size += 1;
+            self.hash_ring_cache.clear();
             rtype = UpdateInstanceType::New;
         }
         let new_instance = Arc::new(instance);
-        //grpc 不走timecheck
-        if !new_instance.from_grpc && !new_instance.is_from_cluster() {
+        //grpc及持久化实例不走timecheck
+        if !new_instance.from_grpc && !new_instance.is_from_cluster() && new_instance.ephemeral {
             let time_info = new_instance.get_time_info();
             self.update_timeinfos(time_info);
         }
@@ -171,34 +184,36 @@ impl Service {
         self.timeinfos.push_back(time_info);
     }
 
+    ///
+    /// 按每个实例的心跳超时及删除超时检查，返回需要删除及标记为不健康的实例
     pub(crate) fn time_check(
         &mut self,
-        healthy_time: i64,
-        offline_time: i64,
+        current_time: i64,
     ) -> (Vec<InstanceShortKey>, Vec<InstanceShortKey>) {
-        assert!(healthy_time >= offline_time);
-        let mut i = 0;
-        let t = self.timeinfos.iter();
         let mut remove_list = vec![];
         let mut update_list = vec![];
-        let mut remove_index = 0;
-        for item in t {
-            i += 1;
+        let mut timeinfos = LinkedList::new();
+        for item in std::mem::take(&mut self.timeinfos) {
             if !item.enable {
                 continue;
             }
-            if item.time <= healthy_time {
-                if item.time <= offline_time {
-                    remove_list.push(item.instance_id.clone());
-                    remove_index = i;
-                } else {
-                    update_list.push(item.instance_id.clone());
-                }
-            } else {
-                break;
+            let instance = self.instances.get(&item.instance_id);
+            //实例变更为持久化实例后，之前的心跳时间不再生效
+            if instance.map(|e| !e.ephemeral).unwrap_or(false) {
+                continue;
             }
+            if item.time + item.delete_timeout <= current_time {
+                remove_list.push(item.instance_id.clone());
+                continue;
+            }
+            if item.time + item.healthy_timeout <= current_time
+                && instance.map(|e| e.healthy).unwrap_or(false)
+            {
+                update_list.push(item.instance_id.clone());
+            }
+            timeinfos.push_back(item);
         }
-        self.timeinfos = self.timeinfos.split_off(remove_index);
+        self.timeinfos = timeinfos;
         for item in &remove_list {
             self.remove_instance(item, None);
         }
@@ -222,6 +237,7 @@ impl Service {
             }
         }
         if let Some(old) = self.instances.remove(instance_key) {
+            self.hash_ring_cache.clear();
             self.instance_size -= 1;
             if self.instance_size == 0 {
                 self.last_empty_times = now_millis();
@@ -235,6 +251,28 @@ impl Service {
         }
     }
 
+    ///
+    /// 只更新已有实例的健康状态，避免探测期间的旧实例信息覆盖最新变更
+    fn update_instance_healthy(
+        &mut self,
+        instance_id: &InstanceShortKey,
+        healthy: bool,
+    ) -> UpdateInstanceType {
+        let old = match self.instances.get(instance_id) {
+            Some(v) if v.healthy != healthy => v,
+            _ => return UpdateInstanceType::None,
+        };
+        let mut i = old.as_ref().clone();
+        i.healthy = healthy;
+        if healthy {
+            self.healthy_instance_size += 1;
+        } else {
+            self.healthy_instance_size -= 1;
+        }
+        self.instances.insert(instance_id.clone(), Arc::new(i));
+        UpdateInstanceType::UpdateValue
+    }
+
     pub(crate) fn update_instance_healthy_unvaild(&mut self, instance_id: &InstanceShortKey) {
         if let Some(i) = self.instances.remove(instance_id) {
             if i.healthy {
@@ -296,6 +334,7 @@ impl Service {
     pub fn get_metadata(&self) -> ServiceMetadata {
         ServiceMetadata {
             protect_threshold: self.protect_threshold,
+            selector: self.selector.clone(),
         }
     }
 
@@ -324,13 +363,14 @@ impl Service {
             group_name: self.group_name.clone(),
             metadata,
             protect_threshold: Some(self.protect_threshold),
+            selector: self.selector.as_ref().map(|e| e.expression.clone()),
         }
     }
 
     pub fn get_owner_http_instances(&self) -> Vec<Arc<Instance>> {
         self.instances
             .values()
-            .filter(|x| x.client_id.is_empty())
+            .filter(|x| x.client_id.is_empty() && x.ephemeral)
             .cloned()
             .collect::<Vec<_>>()
     }
@@ -1,13 +1,23 @@
+use std::cmp::Reverse;
 use std::sync::Arc;
 
 use async_raft_ext::raft::ClientWriteRequest;
 
 use crate::{
     common::appdata::AppShareData,
-    config::core::{ConfigAsyncCmd, ConfigKey},
+    config::{
+        config_push_trace::{ConfigPushTraceDto, ConfigPushTraceQueryParam},
+        config_webhook::{
+            ConfigWebhookCmd, ConfigWebhookDeliveryLog, ConfigWebhookLogQueryParam,
+            ConfigWebhookResult,
+        },
+        core::{ConfigAsyncCmd, ConfigCmd, ConfigKey, ConfigResult},
+        model::{ConfigListenerInfoDto, ConfigListenerQueryParam},
+    },
+    grpc::bistream_manage::{BiStreamManageCmd, BiStreamManageResult},
 };
 
-use self::model::{RouterRequest, RouterResponse};
+use self::model::{RouterRequest, RouterResponse, SetConfigReq};
 
 use super::{db::table::TableManagerAsyncReq, join_node, store::ClientRequest};
 
@@ -24,20 +34,56 @@ pub async fn handle_route(
             key,
             value,
             op_user,
+            config_type,
+            desc,
+            config_tags,
+            app_name,
+            cas_md5,
+            encrypted_data_key,
             extend_info: _,
         } => {
             let config_key: ConfigKey = (&key as &str).into();
-            app.config_addr
-                .send(ConfigAsyncCmd::Add(config_key, value, op_user))
-                .await??;
+            let mut req = SetConfigReq::new(config_key, value);
+            req.op_user = op_user;
+            req.config_type = config_type;
+            req.desc = desc;
+            req.config_tags = config_tags;
+            req.app_name = app_name;
+            req.cas_md5 = cas_md5;
+            req.encrypted_data_key = encrypted_data_key;
+            app.config_addr.send(ConfigAsyncCmd::Add(req)).await??;
         }
         RouterRequest::ConfigDel {
             key,
+            op_user,
             extend_info: _,
         } => {
             let config_key: ConfigKey = (&key as &str).into();
             app.config_addr
-                .send(ConfigAsyncCmd::Delete(config_key))
+                .send(ConfigAsyncCmd::Delete(config_key, op_user))
+                .await??;
+        }
+        RouterRequest::ConfigSetBeta {
+            key,
+            value,
+            rule,
+            op_user,
+        } => {
+            let config_key: ConfigKey = (&key as &str).into();
+            app.config_addr
+                .send(ConfigAsyncCmd::AddBeta(config_key, value, rule, op_user))
+                .await??;
+        }
+        RouterRequest::ConfigRemoveBeta { key } => {
+            let config_key: ConfigKey = (&key as &str).into();
+            app.config_addr
+                .send(ConfigAsyncCmd::RemoveBeta(config_key))
+                .await??;
+        }
+        RouterRequest::ConfigPublishBeta { key, op_user } => {
+            let config_key: ConfigKey = (&key as &str).into();
+            app.config_addr
+                .send(ConfigAsyncCmd::PublishBeta(config_key, op_user))
                 .await??;
         }
         RouterRequest::JoinNode {
@@ -68,6 +114,153 @@ pub async fn handle_route(
             let result = app.cache_manager.send(req).await??;
             return Ok(RouterResponse::CacheManagerResult { result });
         }
+        RouterRequest::ConfigListenerQuery { param } => {
+            let list = query_local_config_listener(app, param).await?;
+            return Ok(RouterResponse::ConfigListenerResult { list });
+        }
+        RouterRequest::ConfigWebhookLogQuery { param } => {
+            let (size, list) = query_local_config_webhook_log(app, param).await?;
+            return Ok(RouterResponse::ConfigWebhookLogResult { size, list });
+        }
+        RouterRequest::ConfigPushTraceQuery { param } => {
+            let (size, list) = query_local_config_push_trace(app, param).await?;
+            return Ok(RouterResponse::ConfigPushTraceResult { size, list });
+        }
+        RouterRequest::NamingPersistentReq { req } => {
+            app.raft
+                .client_write(ClientWriteRequest::new(ClientRequest::NamingPersistentReq(
+                    req,
+                )))
+                .await?;
+        }
     };
     Ok(RouterResponse::None)
 }
+
+async fn query_local_config_listener(
+    app: &Arc<AppShareData>,
+    param: ConfigListenerQueryParam,
+) -> anyhow::Result<Vec<ConfigListenerInfoDto>> {
+    let cmd = ConfigCmd::QueryListener(Box::new(param));
+    match app.config_addr.send(cmd).await?? {
+        ConfigResult::ListenerInfoList(mut list) => {
+            for item in list.iter_mut() {
+                item.node_id = app.sys_config.raft_node_id;
+            }
+            Ok(list)
+        }
+        _ => Ok(vec![]),
+    }
+}
+
+///
+/// 查询集群所有节点的配置监听信息，查询失败的节点只记录日志
+pub async fn query_cluster_config_listener(
+    app: &Arc<AppShareData>,
+    param: ConfigListenerQueryParam,
+) -> anyhow::Result<Vec<ConfigListenerInfoDto>> {
+    let mut list = query_local_config_listener(app, param.clone()).await?;
+    for node in app.naming_node_manage.get_other_valid_nodes().await? {
+        let req = RouterRequest::ConfigListenerQuery {
+            param: param.clone(),
+        };
+        match app.config_route.send_remote_request(node.addr, req).await {
+            Ok(RouterResponse::ConfigListenerResult { list: node_list }) => {
+                list.extend(node_list);
+            }
+            Ok(_) => {}
+            Err(err) => {
+                log::warn!("query config listener from node {} error,{}", node.id, err);
+            }
+        }
+    }
+    Ok(list)
+}
+
+async fn query_local_config_webhook_log(
+    app: &Arc<AppShareData>,
+    param: ConfigWebhookLogQueryParam,
+) -> anyhow::Result<(usize, Vec<ConfigWebhookDeliveryLog>)> {
+    let cmd = ConfigWebhookCmd::QueryDeliveryLog(param);
+    match app.config_webhook_manager.send(cmd).await?? {
+        ConfigWebhookResult::DeliveryLogPage(size, list) => Ok((size, list)),
+        _ => Ok((0, vec![])),
+    }
+}
+
+///
+/// webhook只由主节点投递，投递记录写入raft表，从主节点查询最新的记录
+pub async fn query_leader_config_webhook_log(
+    app: &Arc<AppShareData>,
+    param: ConfigWebhookLogQueryParam,
+) -> anyhow::Result<(usize, Vec<ConfigWebhookDeliveryLog>)> {
+    match app.raft.current_leader().await {
+        Some(node_id) if node_id != app.sys_config.raft_node_id => {
+            let addr = app.raft_store.get_target_addr(node_id).await?;
+            let req = RouterRequest::ConfigWebhookLogQuery { param };
+            match app.config_route.send_remote_request(addr, req).await? {
+                RouterResponse::ConfigWebhookLogResult { size, list } => Ok((size, list)),
+                _ => Err(anyhow::anyhow!(
+                    "ConfigWebhookLogQuery response type is error!"
+                )),
+            }
+        }
+        _ => query_local_config_webhook_log(app, param).await,
+    }
+}
+
+async fn query_local_config_push_trace(
+    app: &Arc<AppShareData>,
+    param: ConfigPushTraceQueryParam,
+) -> anyhow::Result<(usize, Vec<ConfigPushTraceDto>)> {
+    let cmd = BiStreamManageCmd::QueryConfigPushTrace(param);
+    match app.bi_stream_manage.send(cmd).await?? {
+        BiStreamManageResult::ConfigPushTracePage(size, mut list) => {
+            for item in list.iter_mut() {
+                item.node_id = app.sys_config.raft_node_id;
+            }
+            Ok((size, list))
+        }
+        _ => Ok((0, vec![])),
+    }
+}
+
+///
+/// 配置推送记录保存在客户端连接的节点上，查询所有节点后按推送时间倒序分页
+pub async fn query_cluster_config_push_trace(
+    app: &Arc<AppShareData>,
+    param: ConfigPushTraceQueryParam,
+) -> anyhow::Result<(usize, Vec<ConfigPushTraceDto>)> {
+    let (offset, limit) = (param.offset, param.limit);
+    let node_param = ConfigPushTraceQueryParam {
+        offset: 0,
+        limit: offset + limit,
+        ..param
+    };
+    let (mut size, mut list) = query_local_config_push_trace(app, node_param.clone()).await?;
+    for node in app.naming_node_manage.get_other_valid_nodes().await? {
+        let req = RouterRequest::ConfigPushTraceQuery {
+            param: node_param.clone(),
+        };
+        match app.config_route.send_remote_request(node.addr, req).await {
+            Ok(RouterResponse::ConfigPushTraceResult {
+                size: node_size,
+                list: node_list,
+            }) => {
+                size += node_size;
+                list.extend(node_list);
+            }
+            Ok(_) => {}
+            Err(err) => {
+                log::warn!(
+                    "query config push trace from node {} error,{}",
+                    node.id,
+                    err
+                );
+            }
+        }
+    }
+    list.sort_by_key(|e| Reverse(e.push_time));
+    let list = list.into_iter().skip(offset).take(limit).collect();
+    Ok((size, list))
+}
@@ -3,7 +3,13 @@ use std::{collections::HashMap, sync::Arc};
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    config::core::ConfigKey,
+    config::{
+        config_push_trace::{ConfigPushTraceDto, ConfigPushTraceQueryParam},
+        config_webhook::{ConfigWebhookDeliveryLog, ConfigWebhookLogQueryParam},
+        core::ConfigKey,
+        model::{ConfigBetaRule, ConfigError, ConfigListenerInfoDto, ConfigListenerQueryParam},
+    },
+    naming::naming_persistent::NamingPersistentReq,
     raft::{
         cache::{CacheLimiterReq, CacheManagerResult},
         db::table::{TableManagerQueryReq, TableManagerReq, TableManagerResult},
@@ -21,6 +27,12 @@ pub struct SetConfigReq {
     pub config_key: ConfigKey,
     pub value: Arc<String>,
     pub op_user: Option<Arc<String>>,
+    pub config_type: Option<Arc<String>>,
+    pub desc: Option<Arc<String>>,
+    pub config_tags: Option<Arc<String>>,
+    pub app_name: Option<Arc<String>>,
+    pub cas_md5: Option<Arc<String>>,
+    pub encrypted_data_key: Option<Arc<String>>,
     //pub can_route_to_remote: bool,
     //pub extend_info: Option<HashMap<String,String>>,
 }
@@ -31,6 +43,12 @@ impl SetConfigReq {
             config_key,
             value,
             op_user: None,
+            config_type: None,
+            desc: None,
+            config_tags: None,
+            app_name: None,
+            cas_md5: None,
+            encrypted_data_key: None,
         }
     }
 
@@ -43,6 +61,31 @@ impl SetConfigReq {
             config_key,
             value,
             op_user: Some(op_user),
+            config_type: None,
+            desc: None,
+            config_tags: None,
+            app_name: None,
+            cas_md5: None,
+            encrypted_data_key: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SetBetaConfigReq {
+    pub config_key: ConfigKey,
+    pub value: Arc<String>,
+    pub rule: ConfigBetaRule,
+    pub op_user: Option<Arc<String>>,
+}
+
+impl SetBetaConfigReq {
+    pub fn new(config_key: ConfigKey, value: Arc<String>, rule: ConfigBetaRule) -> Self {
+        Self {
+            config_key,
+            value,
+            rule,
+            op_user: None,
         }
     }
 }
@@ -50,13 +93,17 @@ impl SetConfigReq {
 #[derive(Clone, Debug)]
 pub struct DelConfigReq {
     pub config_key: ConfigKey,
+    pub op_user: Option<Arc<String>>,
     //pub can_route_to_remote: bool,
     //pub extend_info: Option<HashMap<String,String>>,
 }
 
 impl DelConfigReq {
     pub fn new(config_key: ConfigKey) -> Self {
-        Self { config_key }
+        Self {
+            config_key,
+            op_user: None,
+        }
     }
 }
 
@@ -66,12 +113,39 @@ pub enum RouterRequest {
         key: String,
         value: Arc<String>,
         op_user: Option<Arc<String>>,
+        #[serde(default)]
+        config_type: Option<Arc<String>>,
+        #[serde(default)]
+        desc: Option<Arc<String>>,
+        #[serde(default)]
+        config_tags: Option<Arc<String>>,
+        #[serde(default)]
+        app_name: Option<Arc<String>>,
+        #[serde(default)]
+        cas_md5: Option<Arc<String>>,
+        #[serde(default)]
+        encrypted_data_key: Option<Arc<String>>,
         extend_info: HashMap<String, String>,
     },
     ConfigDel {
         key: String,
+        #[serde(default)]
+        op_user: Option<Arc<String>>,
         extend_info: HashMap<String, String>,
     },
+    ConfigSetBeta {
+        key: String,
+        value: Arc<String>,
+        rule: ConfigBetaRule,
+        op_user: Option<Arc<String>>,
+    },
+    ConfigRemoveBeta {
+        key: String,
+    },
+    ConfigPublishBeta {
+        key: String,
+        op_user: Option<Arc<String>>,
+    },
     JoinNode {
         node_id: u64,
         node_addr: Arc<String>,
@@ -85,6 +159,22 @@ pub enum RouterRequest {
     CacheLimiterReq {
         req: CacheLimiterReq,
     },
+    ///查询节点本地的配置监听信息
+    ConfigListenerQuery {
+        param: ConfigListenerQueryParam,
+    },
+    ///查询主节点的webhook投递记录
+    ConfigWebhookLogQuery {
+        param: ConfigWebhookLogQueryParam,
+    },
+    ///查询节点本地的配置推送记录
+    ConfigPushTraceQuery {
+        param: ConfigPushTraceQueryParam,
+    },
+    ///持久化服务实例变更，由主节点写入raft
+    NamingPersistentReq {
+        req: NamingPersistentReq,
+    },
 }
 
 impl From<SetConfigReq> for RouterRequest {
@@ -93,15 +183,33 @@ impl From<SetConfigReq> for RouterRequest {
             key: req.config_key.build_key(),
             value: req.value,
             op_user: req.op_user,
+            config_type: req.config_type,
+            desc: req.desc,
+            config_tags: req.config_tags,
+            app_name: req.app_name,
+            cas_md5: req.cas_md5,
+            encrypted_data_key: req.encrypted_data_key,
             extend_info: Default::default(),
         }
     }
 }
 
+impl From<SetBetaConfigReq> for RouterRequest {
+    fn from(req: SetBetaConfigReq) -> Self {
+        Self::ConfigSetBeta {
+            key: req.config_key.build_key(),
+            value: req.value,
+            rule: req.rule,
+            op_user: req.op_user,
+        }
+    }
+}
+
 impl From<DelConfigReq> for RouterRequest {
     fn from(req: DelConfigReq) -> Self {
         Self::ConfigDel {
             key: req.config_key.build_key(),
+            op_user: req.op_user,
             extend_info: Default::default(),
         }
     }
@@ -116,6 +224,25 @@ impl From<CacheLimiterReq> for RouterRequest {
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum RouterResponse {
     None,
-    TableManagerResult { result: TableManagerResult },
-    CacheManagerResult { result: CacheManagerResult },
+    TableManagerResult {
+        result: TableManagerResult,
+    },
+    CacheManagerResult {
+        result: CacheManagerResult,
+    },
+    ConfigListenerResult {
+        list: Vec<ConfigListenerInfoDto>,
+    },
+    ConfigWebhookLogResult {
+        size: usize,
+        list: Vec<ConfigWebhookDeliveryLog>,
+    },
+    ConfigPushTraceResult {
+        size: usize,
+        list: Vec<ConfigPushTraceDto>,
+    },
+    ///主节点处理配置请求返回的业务错误
+    ConfigError {
+        error: ConfigError,
+    },
 }
@@ -2,14 +2,22 @@ use std::{fmt::Debug, sync::Arc};
 
 use actix::prelude::*;
 
+use crate::config::config_encryption::CONFIG_ENCRYPTION;
+use crate::config::config_schema::ConfigSchemaUtils;
+use crate::config::config_type::ConfigType;
+use crate::raft::db::table::TableManager;
 use crate::raft::filestore::core::FileStore;
+use crate::raft::store::{ClientRequest, ClientResponse};
 use crate::{
-    config::core::{ConfigActor, ConfigAsyncCmd, ConfigCmd},
-    grpc::PayloadUtils,
+    config::core::{ConfigActor, ConfigAsyncCmd, ConfigCmd, ConfigKey, ConfigResult},
+    grpc::{api_model::BaseResponse, PayloadUtils},
     raft::{network::factory::RaftClusterRequestSender, NacosRaft},
 };
+use async_raft_ext::raft::ClientWriteRequest;
 
-use super::model::{DelConfigReq, RouteAddr, RouterRequest, RouterResponse, SetConfigReq};
+use super::model::{
+    DelConfigReq, RouteAddr, RouterRequest, RouterResponse, SetBetaConfigReq, SetConfigReq,
+};
 
 #[derive(Clone)]
 pub struct RaftAddrRouter {
@@ -48,11 +56,19 @@ impl RaftAddrRouter {
             None => Ok(RouteAddr::Unknown),
         }
     }
+
+    ///
+    /// 只在主节点调用，写入raft日志
+    pub async fn client_write(&self, req: ClientRequest) -> anyhow::Result<ClientResponse> {
+        let resp = self.raft.client_write(ClientWriteRequest::new(req)).await?;
+        Ok(resp.data)
+    }
 }
 
 #[derive(Clone, Debug)]
 pub struct ConfigRoute {
     config_addr: Addr<ConfigActor>,
+    table_manager: Addr<TableManager>,
     raft_addr_route: Arc<RaftAddrRouter>,
     cluster_sender: Arc<RaftClusterRequestSender>,
 }
@@ -60,11 +76,13 @@ pub struct ConfigRoute {
 impl ConfigRoute {
     pub fn new(
         config_addr: Addr<ConfigActor>,
+        table_manager: Addr<TableManager>,
         raft_addr_route: Arc<RaftAddrRouter>,
         cluster_sender: Arc<RaftClusterRequestSender>,
     ) -> Self {
         Self {
             config_addr,
+            table_manager,
             raft_addr_route,
             cluster_sender,
         }
@@ -74,23 +92,83 @@ impl ConfigRoute {
         anyhow::anyhow!("unknown the raft leader addr!")
     }
 
-    pub async fn set_config(&self, req: SetConfigReq) -> anyhow::Result<()> {
+    ///
+    /// 按配置类型及schema校验明文内容，content为空表示加密内容无法解密
+    async fn validate_config(
+        &self,
+        config_key: &ConfigKey,
+        config_type: Option<&Arc<String>>,
+        content: Option<&Arc<String>>,
+    ) -> anyhow::Result<()> {
+        if let (Some(config_type), Some(content)) = (config_type, content) {
+            ConfigType::new_by_value(config_type)?.validate(content)?;
+        }
+        ConfigSchemaUtils::validate_config(
+            &self.table_manager,
+            config_key,
+            config_type,
+            content.map(|e| e.as_str()),
+        )
+        .await
+    }
+
+    ///
+    /// 查询已发布配置的类型，配置不存在时返回None
+    async fn get_stored_config_type(
+        &self,
+        config_key: &ConfigKey,
+    ) -> anyhow::Result<Option<Arc<String>>> {
+        match self
+            .config_addr
+            .send(ConfigCmd::GetRaw(config_key.clone()))
+            .await??
+        {
+            ConfigResult::QueryData(data) => Ok(data.config_type),
+            _ => Ok(None),
+        }
+    }
+
+    pub async fn set_config(&self, mut req: SetConfigReq) -> anyhow::Result<()> {
+        //客户端已加密的内容解密后校验，不做二次加密
+        let plain = match &req.encrypted_data_key {
+            Some(data_key) => CONFIG_ENCRYPTION
+                .decrypt(&req.config_key.data_id, Some(data_key), req.value.clone())
+                .ok(),
+            None => Some(req.value.clone()),
+        };
+        //请求未指定类型时按已发布配置的类型校验
+        let config_type = match &req.config_type {
+            Some(v) => Some(v.clone()),
+            None => self.get_stored_config_type(&req.config_key).await?,
+        };
+        self.validate_config(&req.config_key, config_type.as_ref(), plain.as_ref())
+            .await?;
+        if req.encrypted_data_key.is_none() {
+            //加密配置不参与引用展开
+            if !CONFIG_ENCRYPTION.need_encrypt(&req.config_key.data_id) {
+                let cmd = ConfigCmd::CheckInclude(req.config_key.clone(), req.value.clone());
+                self.config_addr.send(cmd).await??;
+            }
+            if let Some((data_key, content)) =
+                CONFIG_ENCRYPTION.encrypt(&req.config_key.data_id, &req.value)?
+            {
+                req.value = content;
+                req.encrypted_data_key = Some(data_key);
+            }
+        }
         match self.raft_addr_route.get_route_addr().await? {
             RouteAddr::Local => {
-                let cmd = ConfigAsyncCmd::Add(req.config_key, req.value, req.op_user);
-                self.config_addr.send(cmd).await?.ok();
+                let cmd = ConfigAsyncCmd::Add(req);
+                self.config_addr.send(cmd).await??;
             }
             RouteAddr::Remote(_, addr) => {
                 let source_req = req.clone();
                 let req: RouterRequest = req.into();
-                let request = serde_json::to_string(&req).unwrap_or_default();
-                let payload = PayloadUtils::build_payload("RaftRouteRequest", request);
-                let resp_payload = self.cluster_sender.send_request(addr, payload).await?;
-                let body_vec = resp_payload.body.unwrap_or_default().value;
-                let _: RouterResponse = serde_json::from_slice(&body_vec)?;
+                self.send_remote_request(addr, req).await?;
                 self.config_addr.do_send(ConfigCmd::SetTmpValue(
                     source_req.config_key,
                     source_req.value,
+                    source_req.encrypted_data_key,
                 ));
             }
             RouteAddr::Unknown => {
@@ -103,7 +181,7 @@ impl ConfigRoute {
     pub async fn del_config(&self, req: DelConfigReq) -> anyhow::Result<()> {
         match self.raft_addr_route.get_route_addr().await? {
             RouteAddr::Local => {
-                let cmd = ConfigAsyncCmd::Delete(req.config_key);
+                let cmd = ConfigAsyncCmd::Delete(req.config_key, req.op_user);
                 self.config_addr.send(cmd).await?.ok();
             }
             RouteAddr::Remote(_, addr) => {
@@ -120,4 +198,96 @@ impl ConfigRoute {
         }
         Ok(())
     }
+
+    pub async fn set_beta_config(&self, req: SetBetaConfigReq) -> anyhow::Result<()> {
+        if CONFIG_ENCRYPTION.need_encrypt(&req.config_key.data_id) {
+            return Err(anyhow::anyhow!(
+                "beta release is not supported for encrypted config"
+            ));
+        }
+        //灰度内容与正式发布使用相同的校验，配置类型取正式配置的类型
+        let config_type = self.get_stored_config_type(&req.config_key).await?;
+        self.validate_config(&req.config_key, config_type.as_ref(), Some(&req.value))
+            .await?;
+        match self.raft_addr_route.get_route_addr().await? {
+            RouteAddr::Local => {
+                let cmd = ConfigAsyncCmd::AddBeta(req.config_key, req.value, req.rule, req.op_user);
+                self.config_addr.send(cmd).await??;
+            }
+            RouteAddr::Remote(_, addr) => {
+                let req: RouterRequest = req.into();
+                self.send_remote_request(addr, req).await?;
+            }
+            RouteAddr::Unknown => {
+                return Err(self.unknown_err());
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn remove_beta_config(&self, config_key: ConfigKey) -> anyhow::Result<()> {
+        match self.raft_addr_route.get_route_addr().await? {
+            RouteAddr::Local => {
+                let cmd = ConfigAsyncCmd::RemoveBeta(config_key);
+                self.config_addr.send(cmd).await??;
+            }
+            RouteAddr::Remote(_, addr) => {
+                let req = RouterRequest::ConfigRemoveBeta {
+                    key: config_key.build_key(),
+                };
+                self.send_remote_request(addr, req).await?;
+            }
+            RouteAddr::Unknown => {
+                return Err(self.unknown_err());
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn publish_beta_config(
+        &self,
+        config_key: ConfigKey,
+        op_user: Option<Arc<String>>,
+    ) -> anyhow::Result<()> {
+        match self.raft_addr_route.get_route_addr().await? {
+            RouteAddr::Local => {
+                let cmd = ConfigAsyncCmd::PublishBeta(config_key, op_user);
+                self.config_addr.send(cmd).await??;
+            }
+            RouteAddr::Remote(_, addr) => {
+                let req = RouterRequest::ConfigPublishBeta {
+                    key: config_key.build_key(),
+                    op_user,
+                };
+                self.send_remote_request(addr, req).await?;
+            }
+            RouteAddr::Unknown => {
+                return Err(self.unknown_err());
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) async fn send_remote_request(
+        &self,
+        addr: Arc<String>,
+        req: RouterRequest,
+    ) -> anyhow::Result<RouterResponse> {
+        let request = serde_json::to_string(&req).unwrap_or_default();
+        let payload = PayloadUtils::build_payload("RaftRouteRequest", request);
+        let resp_payload = self.cluster_sender.send_request(addr, payload).await?;
+        if let Some(t) = PayloadUtils::get_payload_type(&resp_payload) {
+            if t == "ErrorResponse" {
+                let body_vec = resp_payload.body.unwrap_or_default().value;
+                let err: BaseResponse = serde_json::from_slice(&body_vec)?;
+                return Err(anyhow::anyhow!(err.message.unwrap_or_default()));
+            }
+        }
+        let body_vec = resp_payload.body.unwrap_or_default().value;
+        let resp: RouterResponse = serde_json::from_slice(&body_vec)?;
+        if let RouterResponse::ConfigError { error } = resp {
+            return Err(error.into());
+        }
+        Ok(resp)
+    }
 }
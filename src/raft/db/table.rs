@@ -10,12 +10,15 @@ use serde::{Deserialize, Serialize};
 
 use actix::prelude::*;
 
-use crate::common::constant::CACHE_TREE_NAME;
+use crate::common::constant::{
+    CACHE_TREE_NAME, CONFIG_HISTORY_TREE_NAME, CONFIG_WEBHOOK_LOG_TREE_NAME,
+};
 use crate::common::sequence_utils::SimpleSequence;
+use crate::config::config_history::CONFIG_HISTORY_RETENTION;
 use crate::raft::filestore::model::SnapshotRecordDto;
 use crate::raft::filestore::raftsnapshot::{SnapshotWriterActor, SnapshotWriterRequest};
 use crate::{
-    common::string_utils::StringUtils,
+    common::{string_utils::StringUtils, APP_SYS_CONFIG},
     raft::{
         cache::{CacheManager, CacheManagerReq},
         cluster::model::RouterRequest,
@@ -205,6 +208,59 @@ impl TableManager {
         }
     }
 
+    ///
+    /// 按key前缀分页查询
+    pub(crate) fn query_prefix_page_list(
+        &self,
+        name: Arc<String>,
+        prefix: Vec<u8>,
+        offset: Option<i64>,
+        limit: Option<i64>,
+        is_rev: bool,
+    ) -> (usize, Vec<TableKV>) {
+        if let Some(table_info) = self.table_map.get(&name) {
+            let mut keys: Vec<&Vec<u8>> = table_info
+                .table_data
+                .range(prefix.clone()..)
+                .take_while(|(k, _)| k.starts_with(&prefix))
+                .map(|(k, _)| k)
+                .collect();
+            let total = keys.len();
+            let offset = offset.unwrap_or_default() as usize;
+            let limit = limit.map(|v| v as usize).unwrap_or(total);
+            if is_rev {
+                keys.reverse();
+            }
+            let mut ret = vec![];
+            for k in keys.into_iter().skip(offset).take(limit) {
+                if let Some(v) = table_info.table_data.get(k) {
+                    ret.push((k.to_vec(), v.to_vec()));
+                }
+            }
+            (total, ret)
+        } else {
+            (0, vec![])
+        }
+    }
+
+    ///
+    /// 按保留策略裁剪配置历史记录
+    fn trim_config_history(&mut self, key: &[u8]) {
+        if let Some(table_info) = self.table_map.get_mut(&*CONFIG_HISTORY_TREE_NAME) {
+            CONFIG_HISTORY_RETENTION.trim_by_count(&mut table_info.table_data, key);
+        }
+    }
+
+    ///
+    /// webhook投递记录只保留最近的记录，key按投递时间递增
+    fn trim_config_webhook_log(&mut self) {
+        if let Some(table_info) = self.table_map.get_mut(&*CONFIG_WEBHOOK_LOG_TREE_NAME) {
+            while table_info.table_data.len() > APP_SYS_CONFIG.config_webhook_log_size {
+                table_info.table_data.pop_first();
+            }
+        }
+    }
+
     pub(crate) fn query_list_count(&self, name: &Arc<String>, like_key: &Option<String>) -> usize {
         if let Some(table_info) = self.table_map.get(name) {
             if let Some(like_key) = like_key {
@@ -258,15 +314,23 @@ impl TableManager {
     ///
     /// 将数据写入raft snapshot文件中
     ///
-    fn build_snapshot(&self, writer: Addr<SnapshotWriterActor>) -> anyhow::Result<()> {
+    fn build_snapshot(&mut self, writer: Addr<SnapshotWriterActor>) -> anyhow::Result<()> {
+        //生成快照时压缩过期的配置历史记录;
+        //过期时间以raft日志中的修改时间为基准，各节点快照时机不同，压缩前的历史记录条数可能短暂不一致
+        if let Some(table_info) = self.table_map.get_mut(&*CONFIG_HISTORY_TREE_NAME) {
+            let count = CONFIG_HISTORY_RETENTION.compact_by_log_time(&mut table_info.table_data);
+            if count > 0 {
+                log::info!("compact config history, remove count:{}", count);
+            }
+        }
         for table_info in self.table_map.values() {
+            //配置历史、回收站等记录包含完整的配置内容，按阈值压缩，小记录保持原格式
             for (key, value) in &table_info.table_data {
-                let record = SnapshotRecordDto {
-                    tree: table_info.name.clone(),
-                    key: key.to_owned(),
-                    value: value.to_owned(),
-                    op_type: 0,
-                };
+                let record = SnapshotRecordDto::new_with_compress(
+                    table_info.name.clone(),
+                    key.to_owned(),
+                    value.to_owned(),
+                )?;
                 writer.do_send(SnapshotWriterRequest::Record(record));
             }
         }
@@ -365,6 +429,13 @@ pub enum TableManagerQueryReq {
         limit: Option<i64>,
         is_rev: bool,
     },
+    QueryPrefixPageList {
+        table_name: Arc<String>,
+        prefix: Vec<u8>,
+        offset: Option<i64>,
+        limit: Option<i64>,
+        is_rev: bool,
+    },
 }
 
 impl From<TableManagerQueryReq> for RouterRequest {
@@ -419,7 +490,15 @@ impl Handler<TableManagerReq> for TableManager {
                         cache_manager.do_send(req);
                     }
                 }
-                self.insert(table_name, key, value, last_seq_id);
+                if table_name.as_str() == CONFIG_HISTORY_TREE_NAME.as_str() {
+                    self.insert(table_name, key.clone(), value, last_seq_id);
+                    self.trim_config_history(&key);
+                } else if table_name.as_str() == CONFIG_WEBHOOK_LOG_TREE_NAME.as_str() {
+                    self.insert(table_name, key, value, last_seq_id);
+                    self.trim_config_webhook_log();
+                } else {
+                    self.insert(table_name, key, value, last_seq_id);
+                }
                 Ok(TableManagerResult::None)
             }
             TableManagerReq::Remove { table_name, key } => {
@@ -516,6 +595,17 @@ impl Handler<TableManagerQueryReq> for TableManager {
                     self.query_page_list(table_name, like_key, offset, limit, is_rev);
                 Ok(TableManagerResult::PageListResult(size, list))
             }
+            TableManagerQueryReq::QueryPrefixPageList {
+                table_name,
+                prefix,
+                offset,
+                limit,
+                is_rev,
+            } => {
+                let (size, list) =
+                    self.query_prefix_page_list(table_name, prefix, offset, limit, is_rev);
+                Ok(TableManagerResult::PageListResult(size, list))
+            }
         }
     }
 }
@@ -1,7 +1,9 @@
 use async_raft_ext::raft::{Entry, EntryPayload};
 
 use self::model::LogRecordDto;
+use crate::common::compress_utils::{DataCompress, RAFT_DATA_COMPRESS};
 
+use super::db::table::TableManagerReq;
 use super::store::ClientRequest;
 
 pub mod core;
@@ -17,7 +19,8 @@ pub struct StoreUtils;
 
 impl StoreUtils {
     pub fn log_record_to_entry(record: LogRecordDto) -> anyhow::Result<Entry<ClientRequest>> {
-        let payload: EntryPayload<ClientRequest> = serde_json::from_slice(&record.value)?;
+        let value = DataCompress::decompress(record.value)?;
+        let payload: EntryPayload<ClientRequest> = serde_json::from_slice(&value)?;
         let entry = Entry {
             term: record.term,
             index: record.index,
@@ -27,7 +30,16 @@ impl StoreUtils {
     }
 
     pub fn entry_to_record(entry: &Entry<ClientRequest>) -> anyhow::Result<LogRecordDto> {
-        let value = serde_json::to_vec(&entry.payload)?;
+        let mut value = serde_json::to_vec(&entry.payload)?;
+        //只压缩配置内容及表数据写入(配置历史、回收站等)，其它日志保持原格式
+        if let EntryPayload::Normal(normal) = &entry.payload {
+            if let ClientRequest::ConfigSet { .. }
+            | ClientRequest::TableManagerReq(TableManagerReq::Set { .. })
+            | ClientRequest::TableManagerReq(TableManagerReq::SetUseAutoId { .. }) = &normal.data
+            {
+                value = RAFT_DATA_COMPRESS.compress(value)?;
+            }
+        }
         let record = LogRecordDto {
             index: entry.index,
             term: entry.term,
@@ -36,3 +48,33 @@ impl StoreUtils {
         Ok(record)
     }
 }
+
+#[test]
+fn table_log_record_compress() {
+    use async_raft_ext::raft::EntryNormal;
+    use std::sync::Arc;
+
+    let value = "rnacos config history ".repeat(1000).into_bytes();
+    let entry = Entry {
+        term: 1,
+        index: 2,
+        payload: EntryPayload::Normal(EntryNormal {
+            data: ClientRequest::TableManagerReq(TableManagerReq::Set {
+                table_name: Arc::new("config_history".to_owned()),
+                key: b"k".to_vec(),
+                value: value.clone(),
+                last_seq_id: None,
+            }),
+        }),
+    };
+    let record = StoreUtils::entry_to_record(&entry).unwrap();
+    assert!(DataCompress::is_compressed(&record.value));
+    let entry = StoreUtils::log_record_to_entry(record).unwrap();
+    assert_eq!(entry.index, 2);
+    match entry.payload {
+        EntryPayload::Normal(EntryNormal {
+            data: ClientRequest::TableManagerReq(TableManagerReq::Set { value: v, .. }),
+        }) => assert_eq!(v, value),
+        _ => panic!("unexpected payload"),
+    }
+}
@@ -4,6 +4,7 @@ use async_raft_ext::raft::InstallSnapshotRequest;
 use binrw::prelude::*;
 use prost::Message;
 
+use crate::common::compress_utils::{DataCompress, RAFT_DATA_COMPRESS};
 use crate::raft::store::ClientRequest;
 
 use super::log::{
@@ -12,6 +13,10 @@ use super::log::{
 
 pub const LOG_INDEX_HEADER_LEN: u64 = 32;
 
+///
+/// 快照记录的value已压缩
+pub const SNAPSHOT_OP_COMPRESSED: u32 = 1;
+
 ///
 /// ----
 /// index header 32 byte
@@ -169,6 +174,37 @@ impl<'a> From<LogSnapshotItem<'a>> for SnapshotRecordDto {
 }
 
 impl SnapshotRecordDto {
+    ///
+    /// value超过压缩阈值时压缩
+    pub fn new_with_compress(
+        tree: Arc<String>,
+        key: Vec<u8>,
+        value: Vec<u8>,
+    ) -> anyhow::Result<Self> {
+        let value = RAFT_DATA_COMPRESS.compress(value)?;
+        let op_type = if DataCompress::is_compressed(&value) {
+            SNAPSHOT_OP_COMPRESSED
+        } else {
+            0
+        };
+        Ok(Self {
+            tree,
+            key,
+            value,
+            op_type,
+        })
+    }
+
+    ///
+    /// 还原压缩的value
+    pub fn decompress(mut self) -> anyhow::Result<Self> {
+        if self.op_type & SNAPSHOT_OP_COMPRESSED == SNAPSHOT_OP_COMPRESSED {
+            self.value = DataCompress::decompress(self.value)?;
+            self.op_type &= !SNAPSHOT_OP_COMPRESSED;
+        }
+        Ok(self)
+    }
+
     pub fn to_record_do(&self) -> LogSnapshotItem {
         LogSnapshotItem {
             tree: Cow::Borrowed(self.tree.as_ref()),
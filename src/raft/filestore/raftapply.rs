@@ -3,10 +3,15 @@ use std::sync::Arc;
 
 use crate::common::byte_utils::bin_to_id;
 use crate::common::constant::{
-    CACHE_TREE_NAME, CONFIG_TREE_NAME, SEQUENCE_TREE_NAME, SEQ_KEY_CONFIG, USER_TREE_NAME,
+    CACHE_TREE_NAME, CONFIG_HISTORY_TREE_NAME, CONFIG_RECYCLE_TREE_NAME, CONFIG_SCHEMA_TREE_NAME,
+    CONFIG_TREE_NAME, CONFIG_WEBHOOK_LOG_TREE_NAME, CONFIG_WEBHOOK_TREE_NAME,
+    NAMING_PERSISTENT_INSTANCE_TREE_NAME, SEQUENCE_TREE_NAME, SEQ_KEY_CONFIG, USER_TREE_NAME,
 };
+use crate::config::config_history::ConfigHistoryUtils;
 use crate::config::core::{ConfigCmd, ConfigKey};
 use crate::config::model::{ConfigRaftCmd, ConfigValueDO};
+use crate::naming::core::NamingCmd;
+use crate::naming::naming_persistent::{NamingPersistentReq, NamingPersistentUtils};
 use crate::raft::db::table::{TableManagerInnerReq, TableManagerReq};
 use crate::raft::filestore::raftdata::RaftDataWrap;
 use crate::raft::store::{ClientRequest, ClientResponse};
@@ -63,6 +68,12 @@ impl LogRecordLoader for LogRecordLoaderInstance {
                     history_table_id,
                     op_time,
                     op_user,
+                    config_type,
+                    desc,
+                    config_tags,
+                    app_name,
+                    cas_md5,
+                    encrypted_data_key,
                 } => {
                     let cmd = ConfigRaftCmd::ConfigAdd {
                         key,
@@ -71,16 +82,71 @@ impl LogRecordLoader for LogRecordLoaderInstance {
                         history_table_id,
                         op_time,
                         op_user,
+                        config_type,
+                        desc,
+                        config_tags,
+                        app_name,
+                        cas_md5,
+                        encrypted_data_key,
                     };
                     self.data_wrap.config.do_send(cmd);
                 }
-                ClientRequest::ConfigRemove { key } => {
-                    let cmd = ConfigRaftCmd::ConfigRemove { key };
+                ClientRequest::ConfigRemove {
+                    key,
+                    op_user,
+                    op_time,
+                } => {
+                    let cmd = ConfigRaftCmd::ConfigRemove {
+                        key,
+                        op_user,
+                        op_time,
+                    };
+                    self.data_wrap.config.do_send(cmd);
+                }
+                ClientRequest::ConfigSetBeta {
+                    key,
+                    value,
+                    rule,
+                    op_time,
+                    op_user,
+                } => {
+                    let cmd = ConfigRaftCmd::ConfigSetBeta {
+                        key,
+                        value,
+                        rule,
+                        op_time,
+                        op_user,
+                    };
+                    self.data_wrap.config.do_send(cmd);
+                }
+                ClientRequest::ConfigRemoveBeta { key } => {
+                    let cmd = ConfigRaftCmd::ConfigRemoveBeta { key };
+                    self.data_wrap.config.do_send(cmd);
+                }
+                ClientRequest::ConfigPublishBeta {
+                    key,
+                    history_id,
+                    history_table_id,
+                    op_time,
+                    op_user,
+                } => {
+                    let cmd = ConfigRaftCmd::ConfigPublishBeta {
+                        key,
+                        history_id,
+                        history_table_id,
+                        op_time,
+                        op_user,
+                    };
                     self.data_wrap.config.do_send(cmd);
                 }
                 ClientRequest::TableManagerReq(req) => {
                     self.data_wrap.table.do_send(req);
                 }
+                ClientRequest::NamingPersistentReq(req) => {
+                    self.data_wrap
+                        .naming
+                        .do_send(NamingCmd::PersistentUpdate(req));
+                }
             },
             _ => {}
         }
@@ -217,6 +283,19 @@ impl StateApplyManager {
             if record.tree.as_str() == CONFIG_TREE_NAME.as_str() {
                 let config_key = ConfigKey::from(&String::from_utf8(record.key)? as &str);
                 let value_do = ConfigValueDO::from_bytes(&record.value)?;
+                //兼容旧版本快照，配置中的历史记录迁移到历史记录表
+                for item in &value_do.histories {
+                    let req = TableManagerReq::Set {
+                        table_name: CONFIG_HISTORY_TREE_NAME.clone(),
+                        key: ConfigHistoryUtils::build_key(
+                            &config_key,
+                            item.id.unwrap_or_default(),
+                        ),
+                        value: item.to_bytes()?,
+                        last_seq_id: None,
+                    };
+                    data_wrap.table.send(req).await??;
+                }
                 data_wrap
                     .config
                     .send(ConfigCmd::InnerSet(config_key, value_do.into()))
@@ -250,6 +329,30 @@ impl StateApplyManager {
                     last_seq_id: None,
                 };
                 data_wrap.table.send(req).await??;
+            } else if record.tree.as_str() == CONFIG_HISTORY_TREE_NAME.as_str()
+                || record.tree.as_str() == CONFIG_WEBHOOK_TREE_NAME.as_str()
+                || record.tree.as_str() == CONFIG_WEBHOOK_LOG_TREE_NAME.as_str()
+                || record.tree.as_str() == CONFIG_SCHEMA_TREE_NAME.as_str()
+                || record.tree.as_str() == CONFIG_RECYCLE_TREE_NAME.as_str()
+            {
+                let req = TableManagerReq::Set {
+                    table_name: record.tree.clone(),
+                    key: record.key,
+                    value: record.value,
+                    last_seq_id: None,
+                };
+                data_wrap.table.send(req).await??;
+            } else if record.tree.as_str() == NAMING_PERSISTENT_INSTANCE_TREE_NAME.as_str() {
+                //恢复持久化实例，由NamingActor重新写入表中
+                let instance = NamingPersistentUtils::from_bytes(&record.value)?;
+                let req = NamingPersistentReq::UpdateInstance {
+                    instance,
+                    tag: None,
+                };
+                data_wrap
+                    .naming
+                    .send(NamingCmd::PersistentUpdate(req))
+                    .await??;
             }
         }
         Ok(())
@@ -306,6 +409,12 @@ impl StateApplyManager {
                 history_table_id,
                 op_time,
                 op_user,
+                config_type,
+                desc,
+                config_tags,
+                app_name,
+                cas_md5,
+                encrypted_data_key,
             } => {
                 if let Some(raft_data_wrap) = &self.data_wrap {
                     let cmd = ConfigRaftCmd::ConfigAdd {
@@ -315,13 +424,69 @@ impl StateApplyManager {
                         history_table_id,
                         op_time,
                         op_user,
+                        config_type,
+                        desc,
+                        config_tags,
+                        app_name,
+                        cas_md5,
+                        encrypted_data_key,
+                    };
+                    raft_data_wrap.config.do_send(cmd);
+                }
+            }
+            ClientRequest::ConfigRemove {
+                key,
+                op_user,
+                op_time,
+            } => {
+                if let Some(raft_data_wrap) = &self.data_wrap {
+                    let cmd = ConfigRaftCmd::ConfigRemove {
+                        key,
+                        op_user,
+                        op_time,
+                    };
+                    raft_data_wrap.config.do_send(cmd);
+                }
+            }
+            ClientRequest::ConfigSetBeta {
+                key,
+                value,
+                rule,
+                op_time,
+                op_user,
+            } => {
+                if let Some(raft_data_wrap) = &self.data_wrap {
+                    let cmd = ConfigRaftCmd::ConfigSetBeta {
+                        key,
+                        value,
+                        rule,
+                        op_time,
+                        op_user,
                     };
                     raft_data_wrap.config.do_send(cmd);
                 }
             }
-            ClientRequest::ConfigRemove { key } => {
+            ClientRequest::ConfigRemoveBeta { key } => {
                 if let Some(raft_data_wrap) = &self.data_wrap {
-                    let cmd = ConfigRaftCmd::ConfigRemove { key };
+                    let cmd = ConfigRaftCmd::ConfigRemoveBeta { key };
+                    raft_data_wrap.config.do_send(cmd);
+                }
+            }
+            ClientRequest::ConfigPublishBeta {
+                key,
+                history_id,
+                history_table_id,
+                op_time,
+                op_user,
+            } => {
+                if let Some(raft_data_wrap) = &self.data_wrap {
+                    let cmd = ConfigRaftCmd::ConfigPublishBeta {
+                        key,
+                        history_id,
+                        history_table_id,
+                        op_time,
+                        op_user,
+                    };
                     raft_data_wrap.config.do_send(cmd);
                 }
             }
@@ -330,6 +495,13 @@ impl StateApplyManager {
                     raft_data_wrap.table.do_send(req);
                 }
             }
+            ClientRequest::NamingPersistentReq(req) => {
+                if let Some(raft_data_wrap) = &self.data_wrap {
+                    raft_data_wrap
+                        .naming
+                        .do_send(NamingCmd::PersistentUpdate(req));
+                }
+            }
         };
         Ok(())
     }
@@ -360,6 +532,12 @@ impl StateApplyManager {
                 history_table_id,
                 op_time,
                 op_user,
+                config_type,
+                desc,
+                config_tags,
+                app_name,
+                cas_md5,
+                encrypted_data_key,
             } => {
                 let cmd = ConfigRaftCmd::ConfigAdd {
                     key,
@@ -368,12 +546,64 @@ impl StateApplyManager {
                     history_table_id,
                     op_time,
                     op_user,
+                    config_type,
+                    desc,
+                    config_tags,
+                    app_name,
+                    cas_md5,
+                    encrypted_data_key,
+                };
+                Ok(raft_data_wrap.config.send(cmd).await??.into())
+            }
+            ClientRequest::ConfigRemove {
+                key,
+                op_user,
+                op_time,
+            } => {
+                let cmd = ConfigRaftCmd::ConfigRemove {
+                    key,
+                    op_user,
+                    op_time,
+                };
+                raft_data_wrap.config.send(cmd).await??;
+                Ok(ClientResponse::Success)
+            }
+            ClientRequest::ConfigSetBeta {
+                key,
+                value,
+                rule,
+                op_time,
+                op_user,
+            } => {
+                let cmd = ConfigRaftCmd::ConfigSetBeta {
+                    key,
+                    value,
+                    rule,
+                    op_time,
+                    op_user,
                 };
                 raft_data_wrap.config.send(cmd).await??;
                 Ok(ClientResponse::Success)
             }
-            ClientRequest::ConfigRemove { key } => {
-                let cmd = ConfigRaftCmd::ConfigRemove { key };
+            ClientRequest::ConfigRemoveBeta { key } => {
+                let cmd = ConfigRaftCmd::ConfigRemoveBeta { key };
+                raft_data_wrap.config.send(cmd).await??;
+                Ok(ClientResponse::Success)
+            }
+            ClientRequest::ConfigPublishBeta {
+                key,
+                history_id,
+                history_table_id,
+                op_time,
+                op_user,
+            } => {
+                let cmd = ConfigRaftCmd::ConfigPublishBeta {
+                    key,
+                    history_id,
+                    history_table_id,
+                    op_time,
+                    op_user,
+                };
                 raft_data_wrap.config.send(cmd).await??;
                 Ok(ClientResponse::Success)
             }
@@ -381,6 +611,13 @@ impl StateApplyManager {
                 raft_data_wrap.table.send(req).await??;
                 Ok(ClientResponse::Success)
             }
+            ClientRequest::NamingPersistentReq(req) => {
+                raft_data_wrap
+                    .naming
+                    .send(NamingCmd::PersistentUpdate(req))
+                    .await??;
+                Ok(ClientResponse::Success)
+            }
         };
         index_manager.do_send(RaftIndexRequest::SaveLastAppliedLog(last_applied_log));
         r
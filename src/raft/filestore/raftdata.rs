@@ -1,4 +1,5 @@
 use crate::config::core::ConfigActor;
+use crate::naming::core::NamingActor;
 use crate::raft::cache::CacheManager;
 use crate::raft::db::table::TableManager;
 use actix::prelude::*;
@@ -7,6 +8,7 @@ use actix::prelude::*;
 pub struct RaftDataWrap {
     pub(crate) config: Addr<ConfigActor>,
     pub(crate) table: Addr<TableManager>,
+    pub(crate) naming: Addr<NamingActor>,
     //pub(crate) cache: Addr<CacheManager>,
 }
 
@@ -14,11 +16,13 @@ impl RaftDataWrap {
     pub fn new(
         config: Addr<ConfigActor>,
         table: Addr<TableManager>,
+        naming: Addr<NamingActor>,
         _cache: Addr<CacheManager>,
     ) -> Self {
         Self {
             config,
             table,
+            naming,
             //cache,
         }
     }
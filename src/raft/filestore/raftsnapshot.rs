@@ -225,8 +225,8 @@ impl SnapshotReader {
             if let Some(v) = self.message_reader.next_message_vec() {
                 let mut reader = BytesReader::from_bytes(v);
                 let item: LogSnapshotItem = reader.read_message(v)?;
-                let dto = item.into();
-                return Ok(Some(dto));
+                let dto: SnapshotRecordDto = item.into();
+                return Ok(Some(dto.decompress()?));
             }
             let mut buf = vec![0u8; 1024];
             let read_len = self.file.read(&mut buf).await?;
@@ -10,6 +10,8 @@ use serde::Serialize;
 use thiserror::Error;
 
 use super::db::table::TableManagerReq;
+use crate::config::model::{ConfigBetaRule, ConfigRaftResult};
+use crate::naming::naming_persistent::NamingPersistentReq;
 
 pub type NodeId = u64;
 
@@ -27,11 +29,48 @@ pub enum ClientRequest {
         history_table_id: Option<u64>,
         op_time: i64,
         op_user: Option<Arc<String>>,
+        #[serde(default)]
+        config_type: Option<Arc<String>>,
+        #[serde(default)]
+        desc: Option<Arc<String>>,
+        #[serde(default)]
+        config_tags: Option<Arc<String>>,
+        #[serde(default)]
+        app_name: Option<Arc<String>>,
+        ///不为空时，只有当前配置md5一致才更新
+        #[serde(default)]
+        cas_md5: Option<Arc<String>>,
+        #[serde(default)]
+        encrypted_data_key: Option<Arc<String>>,
     },
     ConfigRemove {
         key: String,
+        #[serde(default)]
+        op_user: Option<Arc<String>>,
+        ///删除时间，旧版本日志为0
+        #[serde(default)]
+        op_time: i64,
+    },
+    ConfigSetBeta {
+        key: String,
+        value: Arc<String>,
+        rule: ConfigBetaRule,
+        op_time: i64,
+        op_user: Option<Arc<String>>,
+    },
+    ConfigRemoveBeta {
+        key: String,
+    },
+    ConfigPublishBeta {
+        key: String,
+        history_id: u64,
+        history_table_id: Option<u64>,
+        op_time: i64,
+        op_user: Option<Arc<String>>,
     },
     TableManagerReq(TableManagerReq),
+    ///持久化服务实例变更
+    NamingPersistentReq(NamingPersistentReq),
 }
 
 impl AppData for ClientRequest {}
@@ -40,6 +79,8 @@ impl AppData for ClientRequest {}
 pub enum ClientResponse {
     Success,
     Fail,
+    ///配置cas更新时md5不一致
+    CasConflict,
 }
 
 impl Default for ClientResponse {
@@ -50,6 +91,15 @@ impl Default for ClientResponse {
 
 impl AppDataResponse for ClientResponse {}
 
+impl From<ConfigRaftResult> for ClientResponse {
+    fn from(value: ConfigRaftResult) -> Self {
+        match value {
+            ConfigRaftResult::CasConflict => Self::CasConflict,
+            _ => Self::Success,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Error)]
 pub enum ShutdownError {
     #[error("unsafe storage error")]
@@ -9,7 +9,10 @@ use crate::raft::filestore::raftlog::RaftLogManager;
 use crate::raft::filestore::raftsnapshot::RaftSnapshotManager;
 use crate::{
     common::{appdata::AppShareData, AppSysConfig},
-    config::core::ConfigActor,
+    config::{
+        config_recycle::ConfigRecycleManager, config_webhook::ConfigWebhookManager,
+        core::ConfigActor,
+    },
     grpc::{bistream_manage::BiStreamManage, PayloadUtils},
     naming::{
         cluster::{
@@ -18,6 +21,7 @@ use crate::{
             route::NamingRoute,
         },
         core::NamingActor,
+        health_check::NamingHealthCheckActor,
         naming_delay_nofity::DelayNotifyActor,
     },
     raft::{
@@ -128,6 +132,7 @@ pub async fn config_factory(sys_config: Arc<AppSysConfig>) -> anyhow::Result<Fac
     factory.register(BeanDefinition::from_obj(table_route));
     let config_route = Arc::new(ConfigRoute::new(
         config_addr.clone(),
+        table_manage.clone(),
         raft_addr_router.clone(),
         cluster_sender.clone(),
     ));
@@ -143,6 +148,7 @@ pub async fn config_factory(sys_config: Arc<AppSysConfig>) -> anyhow::Result<Fac
     let naming_route = Arc::new(NamingRoute::new(
         naming_addr.clone(),
         naming_node_manage.clone(),
+        raft_addr_router.clone(),
         cluster_sender.clone(),
     ));
     factory.register(BeanDefinition::from_obj(naming_route.clone()));
@@ -151,11 +157,26 @@ pub async fn config_factory(sys_config: Arc<AppSysConfig>) -> anyhow::Result<Fac
         naming_cluster_delay_notify_addr.clone(),
     ));
 
-    let bistream_manage_addr = BiStreamManage::new().start();
+    let naming_health_check_addr = NamingHealthCheckActor::new(&sys_config).start();
+    factory.register(BeanDefinition::actor_with_inject_from_obj(
+        naming_health_check_addr,
+    ));
+
+    let bistream_manage_addr = BiStreamManage::new(&sys_config).start();
     factory.register(BeanDefinition::actor_with_inject_from_obj(
         bistream_manage_addr.clone(),
     ));
 
+    let config_webhook_manager = ConfigWebhookManager::new(&sys_config).start();
+    factory.register(BeanDefinition::actor_with_inject_from_obj(
+        config_webhook_manager,
+    ));
+
+    let config_recycle_manager = ConfigRecycleManager::new(&sys_config).start();
+    factory.register(BeanDefinition::actor_with_inject_from_obj(
+        config_recycle_manager,
+    ));
+
     let user_manager = UserManager::new().start();
     factory.register(BeanDefinition::actor_with_inject_from_obj(user_manager));
     let cache_manager = CacheManager::new().start();
@@ -171,6 +192,7 @@ pub async fn config_factory(sys_config: Arc<AppSysConfig>) -> anyhow::Result<Fac
     let raft_data_wrap = Arc::new(RaftDataWrap {
         config: config_addr.clone(),
         table: table_manage.clone(),
+        naming: naming_addr.clone(),
         //cache: cache_manager.clone(),
     });
     factory.register(BeanDefinition::from_obj(raft_data_wrap));
@@ -195,6 +217,7 @@ pub fn build_share_data(factory_data: FactoryData) -> anyhow::Result<Arc<AppShar
         raft_table_route: factory_data.get_bean().unwrap(),
         raft_cache_route: factory_data.get_bean().unwrap(),
         user_manager: factory_data.get_actor().unwrap(),
+        config_webhook_manager: factory_data.get_actor().unwrap(),
         cache_manager: factory_data.get_actor().unwrap(),
         factory_data,
     });
@@ -154,6 +154,7 @@ lazy_static::lazy_static! {
         //path
         R::Path("/rnacos/manage/cluster",HTTP_METHOD_GET),
         R::Path("/rnacos/api/console/cluster/cluster_node_list",HTTP_METHOD_GET),
+        R::Path("/rnacos/api/console/cluster/compress_metrics",HTTP_METHOD_GET),
     ]);
 
     static ref M_NAMESPACE_VISITOR: ModuleResource = ModuleResource::new(vec![
@@ -195,14 +196,33 @@ lazy_static::lazy_static! {
         R::WebResource("/manage/config/history"),
         R::WebResource("/rnacos/manage/configs"),
         R::WebResource("/rnacos/manage/config/history"),
+        R::WebResource("/manage/config/listener"),
+        R::WebResource("/rnacos/manage/config/listener"),
+        R::WebResource("/manage/config/push/trace"),
+        R::WebResource("/rnacos/manage/config/push/trace"),
+        R::WebResource("/manage/config/webhook"),
+        R::WebResource("/rnacos/manage/config/webhook"),
         //path
         R::Path("/rnacos/manage/configs",HTTP_METHOD_GET),
         R::Path("/rnacos/api/console/configs",HTTP_METHOD_GET),
         R::Path("/rnacos/api/console/download",HTTP_METHOD_GET),
         R::Path("/rnacos/api/console/cs/configs",HTTP_METHOD_GET),
+        R::Path("/rnacos/api/console/config/search",HTTP_METHOD_GET),
+        R::Path("/rnacos/api/console/config/listener",HTTP_METHOD_GET),
+        R::Path("/rnacos/api/console/config/include/graph",HTTP_METHOD_GET),
+        R::Path("/rnacos/api/console/config/push/trace",HTTP_METHOD_GET),
+        R::Path("/rnacos/api/console/config/webhook",HTTP_METHOD_GET),
+        R::Path("/rnacos/api/console/config/webhook/delivery",HTTP_METHOD_GET),
+        R::Path("/rnacos/api/console/config/schema",HTTP_METHOD_GET),
+        R::Path("/rnacos/api/console/config/schema/validate",HTTP_METHOD_ALL),
+        R::Path("/rnacos/api/console/config/recycle",HTTP_METHOD_GET),
+        R::Path("/rnacos/api/console/config/recycle/detail",HTTP_METHOD_GET),
         //config history
         R::Path("/rnacos/manage/config/history",HTTP_METHOD_GET),
         R::Path("//rnacos/api/console/config/history",HTTP_METHOD_GET),
+        R::Path("/rnacos/api/console/config/history/diff",HTTP_METHOD_GET),
+        //config beta
+        R::Path("/rnacos/api/console/config/beta",HTTP_METHOD_GET),
     ]);
 
     static ref M_CONFIG_MANAGE: ModuleResource = ModuleResource::new(vec![
@@ -211,6 +231,12 @@ lazy_static::lazy_static! {
         R::WebResource("/manage/config/history"),
         R::WebResource("/rnacos/manage/configs"),
         R::WebResource("/rnacos/manage/config/history"),
+        R::WebResource("/manage/config/listener"),
+        R::WebResource("/rnacos/manage/config/listener"),
+        R::WebResource("/manage/config/push/trace"),
+        R::WebResource("/rnacos/manage/config/push/trace"),
+        R::WebResource("/manage/config/webhook"),
+        R::WebResource("/rnacos/manage/config/webhook"),
         R::WebResource("CONFIG_UPDATE"),
         //path
         R::Path("/rnacos/manage/configs",HTTP_METHOD_ALL),
@@ -218,10 +244,28 @@ lazy_static::lazy_static! {
         R::Path("/rnacos/api/console/configs",HTTP_METHOD_GET),
         R::Path("/rnacos/api/console/config/download",HTTP_METHOD_GET),
         R::Path("/rnacos/api/console/config/import",HTTP_METHOD_ALL),
+        R::Path("/rnacos/api/console/config/clone",HTTP_METHOD_ALL),
         R::Path("/rnacos/api/console/cs/configs",HTTP_METHOD_ALL),
+        R::Path("/rnacos/api/console/config/search",HTTP_METHOD_GET),
+        R::Path("/rnacos/api/console/config/listener",HTTP_METHOD_GET),
+        R::Path("/rnacos/api/console/config/include/graph",HTTP_METHOD_GET),
+        R::Path("/rnacos/api/console/config/push/trace",HTTP_METHOD_GET),
+        R::Path("/rnacos/api/console/config/webhook",HTTP_METHOD_ALL),
+        R::Path("/rnacos/api/console/config/webhook/delivery",HTTP_METHOD_GET),
+        R::Path("/rnacos/api/console/config/schema",HTTP_METHOD_ALL),
+        R::Path("/rnacos/api/console/config/schema/validate",HTTP_METHOD_ALL),
+        R::Path("/rnacos/api/console/config/recycle",HTTP_METHOD_GET),
+        R::Path("/rnacos/api/console/config/recycle/detail",HTTP_METHOD_GET),
+        R::Path("/rnacos/api/console/config/recycle/restore",HTTP_METHOD_ALL),
+        R::Path("/rnacos/api/console/config/recycle/purge",HTTP_METHOD_ALL),
         //config history
         R::Path("/rnacos/manage/config/history",HTTP_METHOD_GET),
         R::Path("/rnacos/api/console/config/history",HTTP_METHOD_GET),
+        R::Path("/rnacos/api/console/config/history/rollback",HTTP_METHOD_ALL),
+        R::Path("/rnacos/api/console/config/history/diff",HTTP_METHOD_GET),
+        //config beta
+        R::Path("/rnacos/api/console/config/beta",HTTP_METHOD_ALL),
+        R::Path("/rnacos/api/console/config/beta/publish",HTTP_METHOD_ALL),
     ]);
 
     static ref M_NAMING_VISITOR: ModuleResource = ModuleResource::new(vec![
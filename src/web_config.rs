@@ -1,6 +1,7 @@
 use actix_web::{web, HttpResponse, Responder};
 
 use crate::config::api::app_config as cs_config;
+use crate::config::api_v2::app_config as cs_v2_config;
 
 use crate::naming::api::app_config as ns_config;
 
@@ -77,6 +78,7 @@ pub fn app_config(config: &mut web::ServiceConfig) {
     config.service(web::resource("/nacos/v1/auth/login").route(web::post().to(mock_token)));
     config.service(web::resource("/nacos/v1/auth/users/login").route(web::post().to(mock_token)));
     cs_config(config);
+    cs_v2_config(config);
     ns_config(config);
     raft_config(config);
     console_api_config(config);
@@ -94,6 +96,7 @@ pub fn app_without_no_auth_console_config(config: &mut web::ServiceConfig) {
         .service(web::resource("/nacos/v1/auth/login").route(web::post().to(mock_token)))
         .service(web::resource("/nacos/v1/auth/users/login").route(web::post().to(mock_token)));
     cs_config(config);
+    cs_v2_config(config);
     ns_config(config);
     raft_config(config);
 }